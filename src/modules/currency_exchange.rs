@@ -62,14 +62,7 @@ impl CurrencyExchange {
         // could be refactored
         let key: String = CurrencyExchange::key(currency, &BASE_CURRENCY);
 
-        let data_frame: Result<DataFrame, String> = CsvReadOptions::default()
-            .with_infer_schema_length(None)
-            .with_has_header(true)
-            .with_parse_options(CsvParseOptions::default().with_try_parse_dates(true))
-            .try_into_reader_with_file_path(Some(format!("data/exchange_rate_{}.csv", key).into()))
-            .map_err(|e| format!("Failed to read {} table: {}", key, e))?
-            .finish()
-            .map_err(|e| format!("Failed to load {} table: {}", key, e));
+        let data_frame: Result<DataFrame, String> = Self::read_cache(&key);
 
         if let Err(data_frame) = data_frame {
             return Err(data_frame);
@@ -189,6 +182,27 @@ impl CurrencyExchange {
         currency_exchange
     }
 
+    /// Same as init, but without loading any exchange rate table when every row of the
+    /// data_frame is already in currency_to, as no rate is needed then.
+    pub(crate) fn init_for(currency_to: &Currency, data_frame: &DataFrame) -> CurrencyExchange {
+        let currency_to: String = currency_to.to_string();
+        let all_in_currency_to: bool = data_frame
+            .column("currency")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .all(|currency| currency == Some(currency_to.as_str()));
+
+        match all_in_currency_to {
+            true => CurrencyExchange {
+                hash_map_raw: HashMap::new(),
+                hash_map: HashMap::new(),
+            },
+            false => Self::init(),
+        }
+    }
+
     /// Saves the currency exchange tables.
     fn save(&mut self) -> () {
         for (key, data_frame) in self.hash_map_raw.iter_mut() {
@@ -196,17 +210,42 @@ impl CurrencyExchange {
                 return;
             }
 
-            let mut file = File::create(format!("data/exchange_rate_{}.csv", key))
-                .expect(format!("Could not create file {}_table.csv", key).as_str());
-
-            CsvWriter::new(&mut file)
-                .include_header(true)
-                .with_separator(b',')
-                .finish(data_frame)
-                .expect(format!("Failed to save {} table.", key).as_str());
+            Self::write_cache(key, data_frame);
         }
     }
 
+    /// Keys of the exchange rate tables kept on disk, one per currency other than the base one.
+    pub(crate) fn cache_keys() -> Vec<String> {
+        Currency::iter()
+            .filter(|currency| *currency != BASE_CURRENCY)
+            .map(|currency| CurrencyExchange::key(&currency, &BASE_CURRENCY))
+            .collect()
+    }
+
+    /// Reads an exchange rate table from disk, without completing it with fresh data.
+    pub(crate) fn read_cache(key: &str) -> Result<DataFrame, String> {
+        CsvReadOptions::default()
+            .with_infer_schema_length(None)
+            .with_has_header(true)
+            .with_parse_options(CsvParseOptions::default().with_try_parse_dates(true))
+            .try_into_reader_with_file_path(Some(format!("data/exchange_rate_{}.csv", key).into()))
+            .map_err(|e| format!("Failed to read {} table: {}", key, e))?
+            .finish()
+            .map_err(|e| format!("Failed to load {} table: {}", key, e))
+    }
+
+    /// Writes an exchange rate table to disk.
+    pub(crate) fn write_cache(key: &str, data_frame: &mut DataFrame) -> () {
+        let mut file = File::create(format!("data/exchange_rate_{}.csv", key))
+            .expect(format!("Could not create file {}_table.csv", key).as_str());
+
+        CsvWriter::new(&mut file)
+            .include_header(true)
+            .with_separator(b',')
+            .finish(data_frame)
+            .expect(format!("Failed to save {} table.", key).as_str());
+    }
+
     /// Creates a key for the currency exchange HashMap (just the concatenation of the currency
     /// names)
     fn key(currency_from: &Currency, currency_to: &Currency) -> String {
@@ -0,0 +1,346 @@
+use crate::modules::database::summaries::TimeUnit;
+use crate::modules::database::DataBase;
+use crate::modules::financial::Currency;
+use chrono::{Duration, NaiveDate};
+use polars::prelude::*;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// How many times the median of its entity or subcategory an expense must be to be unusual.
+const OUTLIER_FACTOR: f64 = 3.0;
+
+/// Fewest other expenses of the entity or subcategory for their median to be meaningful.
+const MIN_COMPARABLE_EXPENSES: usize = 3;
+
+/// Number of previous months in the rolling average of a category.
+const ROLLING_MONTHS: usize = 3;
+
+/// How many times its rolling average the spending of a category must be to be unusual.
+const SPIKE_FACTOR: f64 = 1.5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AnomalyKind {
+    /// An expense far above the median of its entity or subcategory
+    Outlier,
+    /// Expenses with the same amount, entity and day, in different parties
+    Duplicate,
+    /// A month of a category well above its rolling average
+    Spike,
+}
+
+// Conversion to string
+impl Display for AnomalyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            AnomalyKind::Outlier => "Unusual amount".to_string(),
+            AnomalyKind::Duplicate => "Possible duplicate".to_string(),
+            AnomalyKind::Spike => "Spending spike".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Something unusual in the expenses, to be reviewed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Anomaly {
+    pub(crate) kind: AnomalyKind,
+    /// Identifies the anomaly from one detection to the next, so that it stays dismissed. Made
+    /// of the dates, entities, amounts and categories involved rather than of row IDs, which
+    /// change when the expenses are edited
+    pub(crate) key: String,
+    pub(crate) date: NaiveDate,
+    pub(crate) value: f64,
+    pub(crate) currency: Currency,
+    pub(crate) description: String,
+    /// Parties of the expenses involved
+    pub(crate) party_ids: Vec<i64>,
+}
+
+/// Spending of a category in a month, and the parties it comes from.
+type MonthlySpending = (f64, Vec<i64>);
+
+/// Median of the sorted values once one of the values equal to value is left out.
+fn median_without(sorted: &[f64], value: f64) -> f64 {
+    let position: usize = sorted.partition_point(|other| *other < value);
+    let other = |i: usize| match i < position {
+        true => sorted[i],
+        false => sorted[i + 1],
+    };
+    let count: usize = sorted.len() - 1;
+
+    (other((count - 1) / 2) + other(count / 2)) / 2.0
+}
+
+/// An expense, as read for the detection.
+struct Expense {
+    value: f64,
+    currency: String,
+    date: NaiveDate,
+    category: String,
+    subcategory: String,
+    entity_id: i64,
+    party_id: i64,
+}
+
+impl DataBase {
+    fn anomaly_expenses(&self) -> Vec<Expense> {
+        let expenses: DataFrame = self
+            .expenses_table
+            .data_frame
+            .clone()
+            .lazy()
+            .sort(["date", "expense_id"], Default::default())
+            .collect()
+            .unwrap();
+
+        let text = |name: &str, i: usize| -> String {
+            expenses
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(i)
+                .unwrap()
+                .to_string()
+        };
+        let id = |name: &str, i: usize| -> i64 {
+            expenses
+                .column(name)
+                .unwrap()
+                .i64()
+                .unwrap()
+                .get(i)
+                .unwrap()
+        };
+        let dates: Vec<NaiveDate> = expenses
+            .column("date")
+            .unwrap()
+            .date()
+            .unwrap()
+            .as_date_iter()
+            .map(|date| date.unwrap())
+            .collect();
+
+        dates
+            .into_iter()
+            .enumerate()
+            .map(|(i, date)| Expense {
+                value: expenses
+                    .column("value")
+                    .unwrap()
+                    .f64()
+                    .unwrap()
+                    .get(i)
+                    .unwrap(),
+                currency: text("currency", i),
+                date,
+                category: text("category", i),
+                subcategory: text("subcategory", i),
+                entity_id: id("entity_id", i),
+                party_id: id("party_id", i),
+            })
+            .collect()
+    }
+
+    /// Looks for unusual expenses: amounts far above the median of their entity or, failing
+    /// that, of their subcategory, the same amount spent at the same entity twice on a day,
+    /// and months in which a category is spent well above the average of the previous months.
+    /// Amounts are compared within their currency. Dismissed anomalies are left out, and the
+    /// rest are sorted from the most recent.
+    pub(crate) fn anomalies(&self) -> Vec<Anomaly> {
+        let expenses: Vec<Expense> = self.anomaly_expenses();
+        let currency =
+            |currency: &str| Currency::from_str(currency).expect("Failed to find currency");
+        let mut anomalies: Vec<Anomaly> = Vec::new();
+
+        // Outliers
+        let mut by_entity: BTreeMap<(i64, &str), Vec<f64>> = BTreeMap::new();
+        let mut by_subcategory: BTreeMap<(&str, &str, &str), Vec<f64>> = BTreeMap::new();
+        for expense in expenses.iter() {
+            by_entity
+                .entry((expense.entity_id, expense.currency.as_str()))
+                .or_default()
+                .push(expense.value);
+            by_subcategory
+                .entry((
+                    expense.category.as_str(),
+                    expense.subcategory.as_str(),
+                    expense.currency.as_str(),
+                ))
+                .or_default()
+                .push(expense.value);
+        }
+        for values in by_entity.values_mut().chain(by_subcategory.values_mut()) {
+            values.sort_by(f64::total_cmp);
+        }
+        for expense in expenses.iter() {
+            // Entity first, then subcategory
+            let comparisons: [(bool, &Vec<f64>); 2] = [
+                (
+                    true,
+                    &by_entity[&(expense.entity_id, expense.currency.as_str())],
+                ),
+                (
+                    false,
+                    &by_subcategory[&(
+                        expense.category.as_str(),
+                        expense.subcategory.as_str(),
+                        expense.currency.as_str(),
+                    )],
+                ),
+            ];
+            let outlier = comparisons
+                .into_iter()
+                .find(|(_is_entity, values)| values.len() > MIN_COMPARABLE_EXPENSES)
+                .map(|(is_entity, values)| (is_entity, median_without(values, expense.value)))
+                .filter(|(_is_entity, median)| {
+                    *median > 0.0 && expense.value > OUTLIER_FACTOR * median
+                });
+            if let Some((is_entity, median)) = outlier {
+                let entity_name: String = self.entity(expense.entity_id).name();
+                let name: String = match is_entity {
+                    true => entity_name.clone(),
+                    false => format!("{}, {}", expense.category, expense.subcategory),
+                };
+                anomalies.push(Anomaly {
+                    kind: AnomalyKind::Outlier,
+                    key: format!(
+                        "outlier:{}:{}:{}:{}:{:.2}:{}",
+                        expense.date,
+                        entity_name,
+                        expense.category,
+                        expense.subcategory,
+                        expense.value,
+                        expense.currency
+                    ),
+                    date: expense.date,
+                    value: expense.value,
+                    currency: currency(expense.currency.as_str()),
+                    description: format!(
+                        "{:.1} times the median of {} ({:.2})",
+                        expense.value / median,
+                        name,
+                        median
+                    ),
+                    party_ids: vec![expense.party_id],
+                });
+            }
+        }
+
+        // Duplicates
+        let mut same_day: BTreeMap<(NaiveDate, i64, &str, String), Vec<&Expense>> = BTreeMap::new();
+        for expense in expenses.iter() {
+            same_day
+                .entry((
+                    expense.date,
+                    expense.entity_id,
+                    expense.currency.as_str(),
+                    format!("{:.2}", expense.value),
+                ))
+                .or_default()
+                .push(expense);
+        }
+        for ((date, entity_id, currency_name, value), group) in same_day.iter() {
+            let mut party_ids: Vec<i64> = group.iter().map(|expense| expense.party_id).collect();
+            party_ids.sort();
+            party_ids.dedup();
+            if party_ids.len() < 2 {
+                continue;
+            }
+            let entity_name: String = self.entity(*entity_id).name();
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::Duplicate,
+                key: format!(
+                    "duplicate:{}:{}:{}:{}",
+                    date, entity_name, value, currency_name
+                ),
+                date: *date,
+                value: group[0].value,
+                currency: currency(currency_name),
+                description: format!(
+                    "{} expenses at {} on the same day",
+                    group.len(),
+                    entity_name
+                ),
+                party_ids,
+            });
+        }
+
+        // Spikes, month by month from the first expense
+        if let (Some(first), Some(last)) = (expenses.first(), expenses.last()) {
+            let mut months: Vec<NaiveDate> = Vec::new();
+            let mut month: (NaiveDate, NaiveDate) =
+                self.settings.periods.period(&TimeUnit::Month, first.date);
+            while month.0 <= last.date {
+                months.push(month.0);
+                month = self
+                    .settings
+                    .periods
+                    .period(&TimeUnit::Month, month.1 + Duration::days(1));
+            }
+
+            let mut by_category: BTreeMap<(&str, &str), Vec<MonthlySpending>> = BTreeMap::new();
+            for expense in expenses.iter() {
+                let (from, _to) = self.settings.periods.period(&TimeUnit::Month, expense.date);
+                let i: usize = months.partition_point(|month| *month < from);
+                let totals = by_category
+                    .entry((expense.category.as_str(), expense.currency.as_str()))
+                    .or_insert_with(|| vec![(0.0, Vec::new()); months.len()]);
+                totals[i].0 += expense.value;
+                if !totals[i].1.contains(&expense.party_id) {
+                    totals[i].1.push(expense.party_id);
+                }
+            }
+
+            for ((category, currency_name), totals) in by_category {
+                for i in ROLLING_MONTHS..months.len() {
+                    let average: f64 = totals[i - ROLLING_MONTHS..i]
+                        .iter()
+                        .map(|(total, _party_ids)| total)
+                        .sum::<f64>()
+                        / ROLLING_MONTHS as f64;
+                    let (total, party_ids) = &totals[i];
+                    if average > 0.0 && *total > SPIKE_FACTOR * average {
+                        // Named after the calendar month holding most of the period, which
+                        // does not move with the day months start on
+                        let calendar_month: NaiveDate = months[i] + Duration::days(14);
+                        anomalies.push(Anomaly {
+                            kind: AnomalyKind::Spike,
+                            key: format!(
+                                "spike:{}:{}:{}",
+                                category,
+                                currency_name,
+                                calendar_month.format("%Y-%m")
+                            ),
+                            date: months[i],
+                            value: *total,
+                            currency: currency(currency_name),
+                            description: format!(
+                                "{} in the month from {}, {:.1} times the average of the previous {} months ({:.2})",
+                                category,
+                                months[i],
+                                total / average,
+                                ROLLING_MONTHS,
+                                average
+                            ),
+                            party_ids: party_ids.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        anomalies.retain(|anomaly| !self.dismissal_table.is_dismissed(anomaly.key.as_str()));
+        anomalies.sort_by_key(|anomaly| Reverse(anomaly.date));
+
+        anomalies
+    }
+
+    /// Marks an anomaly as reviewed on date, so that it is not flagged again.
+    pub(crate) fn dismiss_anomaly(&mut self, anomaly: &Anomaly, date: NaiveDate) -> i64 {
+        self.dismissal_table
+            .insert_dismissal(anomaly.key.as_str(), date)
+    }
+}
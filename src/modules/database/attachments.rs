@@ -0,0 +1,130 @@
+use crate::modules::database::DataBase;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{create_dir_all, read, read_dir, remove_file, File};
+use std::io::Write;
+use std::path::Path;
+
+/// Folder where attached files are kept, named after the hash of their content.
+const ATTACHMENTS_DIRECTORY: &str = "data/attachments";
+
+/// A file to be attached to a party, such as a receipt, an invoice or a warranty.
+pub(crate) struct Attachment {
+    pub(crate) file_name: String,
+    pub(crate) content: Vec<u8>,
+}
+
+impl Attachment {
+    /// Reads the file at the given path.
+    pub(crate) fn read(path: &str) -> Result<Attachment, String> {
+        let content: Vec<u8> = read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let file_name: String = Path::new(path)
+            .file_name()
+            .map_or(path.to_string(), |file_name| {
+                file_name.to_string_lossy().to_string()
+            });
+
+        Ok(Attachment { file_name, content })
+    }
+
+    /// Name under which the file is stored: the SHA-256 of its content, plus the original
+    /// extension so that it can still be opened with the right program. Identical files
+    /// attached several times are therefore stored only once.
+    pub(crate) fn stored_file_name(&self) -> String {
+        let hash: String = Sha256::digest(&self.content)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        match Path::new(&self.file_name).extension() {
+            Some(extension) => format!("{}.{}", hash, extension.to_string_lossy().to_lowercase()),
+            None => hash,
+        }
+    }
+}
+
+impl DataBase {
+    /// Stores a file and links it to a party. Returns the ID of the attachment.
+    pub(crate) fn attach(&mut self, party_id: i64, attachment: &Attachment) -> Result<i64, String> {
+        let stored_file_name: String = attachment.stored_file_name();
+        let path: String = format!("{}/{}", ATTACHMENTS_DIRECTORY, stored_file_name);
+
+        if !Path::new(&path).exists() {
+            create_dir_all(ATTACHMENTS_DIRECTORY)
+                .map_err(|e| format!("Failed to create {}: {}", ATTACHMENTS_DIRECTORY, e))?;
+            File::create(&path)
+                .and_then(|mut file| file.write_all(&attachment.content))
+                .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        }
+
+        Ok(self.attachment_table.insert_attachment(
+            party_id,
+            attachment.file_name.as_str(),
+            stored_file_name.as_str(),
+        ))
+    }
+
+    /// Returns the ID, the original name and the stored path of every file attached to a party.
+    pub(crate) fn attachments(&self, party_id: i64) -> Vec<(i64, String, String)> {
+        self.attachment_table
+            .party_attachments(party_id)
+            .into_iter()
+            .map(|(attachment_id, file_name, stored_file_name)| {
+                (
+                    attachment_id,
+                    file_name,
+                    format!("{}/{}", ATTACHMENTS_DIRECTORY, stored_file_name),
+                )
+            })
+            .collect()
+    }
+
+    /// Reads back the files attached to a party, so that they can be attached again after the
+    /// party is edited.
+    pub(crate) fn load_attachments(&self, party_id: i64) -> Vec<Attachment> {
+        self.attachments(party_id)
+            .into_iter()
+            .filter_map(|(_attachment_id, file_name, path)| {
+                read(path)
+                    .ok()
+                    .map(|content| Attachment { file_name, content })
+            })
+            .collect()
+    }
+
+    /// Returns the stored name of every attached file that is not in the attachments folder,
+    /// e.g. after restoring a snapshot taken on another computer.
+    pub(crate) fn missing_attachments(&self) -> Vec<String> {
+        let mut stored_file_names: Vec<String> = self.attachment_table.stored_file_names();
+        stored_file_names.sort();
+        stored_file_names.dedup();
+
+        stored_file_names
+            .into_iter()
+            .filter(|stored_file_name| {
+                !Path::new(ATTACHMENTS_DIRECTORY)
+                    .join(stored_file_name)
+                    .exists()
+            })
+            .collect()
+    }
+
+    /// Removes the stored files that no attachment refers to anymore.
+    pub(crate) fn remove_orphan_attachments(&self) -> () {
+        let Ok(entries) = read_dir(ATTACHMENTS_DIRECTORY) else {
+            return;
+        };
+
+        let stored_file_names: HashSet<String> = self
+            .attachment_table
+            .stored_file_names()
+            .into_iter()
+            .collect();
+        for entry in entries.flatten() {
+            let file_name: String = entry.file_name().to_string_lossy().to_string();
+            if !stored_file_names.contains(&file_name) {
+                let _ = remove_file(entry.path());
+            }
+        }
+    }
+}
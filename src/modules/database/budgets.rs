@@ -0,0 +1,319 @@
+use crate::modules::currency_exchange::CurrencyExchange;
+use crate::modules::database::summaries::TimeUnit;
+use crate::modules::database::{data_frame_to_csv_string, DataBase};
+use crate::modules::financial::Currency;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Amount that may be spent on an expense category, or on one of its subcategories, in every
+/// period.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Budget {
+    pub(crate) category: String,
+    /// The whole category is budgeted if there is none
+    pub(crate) subcategory: Option<String>,
+    pub(crate) period: TimeUnit,
+    pub(crate) value: f64,
+    pub(crate) currency: Currency,
+    /// Whether what is left of, or overspent on, a period is carried over to the next one
+    pub(crate) rollover: bool,
+}
+
+impl DataBase {
+    /// Saves a budget, updating the one of the same category, subcategory and period if there
+    /// was one, which keeps its ID and creation date. Returns the ID of the budget.
+    pub(crate) fn set_budget(&mut self, budget: &Budget) -> i64 {
+        let period: String = budget.period.to_string();
+        if let Some(budget_id) = self.budget_table.find_budget(
+            budget.category.as_str(),
+            budget.subcategory.as_deref(),
+            period.as_str(),
+        ) {
+            self.budget_table.update_budget(
+                budget_id,
+                budget.value,
+                &budget.currency,
+                budget.rollover,
+            );
+            return budget_id;
+        }
+
+        self.budget_table.insert_budget(
+            budget.category.as_str(),
+            budget.subcategory.as_deref(),
+            period.as_str(),
+            budget.value,
+            &budget.currency,
+            budget.rollover,
+        )
+    }
+
+    pub(crate) fn delete_budget(&mut self, budget_id: i64) -> () {
+        self.budget_table.delete_budget(budget_id);
+    }
+
+    /// Returns every budget with its ID and creation date, sorted by category and subcategory.
+    pub(crate) fn budgets(&self) -> Vec<(i64, Budget, NaiveDate)> {
+        let records: DataFrame = self
+            .budget_table
+            .data_frame
+            .clone()
+            .lazy()
+            .sort(["category", "subcategory"], Default::default())
+            .collect()
+            .unwrap();
+
+        let column = |name: &str| records.column(name).unwrap().clone();
+        let ids = column("budget_id");
+        let categories = column("category");
+        let subcategories = column("subcategory");
+        let periods = column("period");
+        let values = column("value");
+        let currencies = column("currency");
+        let rollovers = column("rollover");
+        let creation_dates = column("creation_date");
+
+        (0..records.height())
+            .map(|i| {
+                let budget: Budget = Budget {
+                    category: categories.str().unwrap().get(i).unwrap().to_string(),
+                    subcategory: subcategories.str().unwrap().get(i).map(String::from),
+                    period: TimeUnit::from_str(periods.str().unwrap().get(i).unwrap())
+                        .expect("Failed to read budget period"),
+                    value: values.f64().unwrap().get(i).unwrap(),
+                    currency: Currency::from_str(currencies.str().unwrap().get(i).unwrap())
+                        .expect("Failed to read budget currency"),
+                    rollover: rollovers.bool().unwrap().get(i).unwrap(),
+                };
+                let creation_date: NaiveDate = creation_dates
+                    .date()
+                    .unwrap()
+                    .as_date_iter()
+                    .nth(i)
+                    .flatten()
+                    .unwrap();
+
+                (ids.i64().unwrap().get(i).unwrap(), budget, creation_date)
+            })
+            .collect()
+    }
+
+    pub(crate) fn budget_vs_actual(&self, date: NaiveDate) -> String {
+        data_frame_to_csv_string(&mut self.budget_vs_actual_data_frame(date))
+    }
+
+    /// Compares every budget with the expenses of its period containing the date, converted to
+    /// the currency of the budget at the exchange rate of their dates. Budgets with rollover
+    /// carry over what was left of, or overspent on, their previous periods since they were
+    /// created. The projected spend extrapolates the expenses so far to the whole period.
+    pub(crate) fn budget_vs_actual_data_frame(&self, date: NaiveDate) -> DataFrame {
+        let periods = &self.settings.periods;
+
+        let mut expenses: HashMap<Currency, DataFrame> = HashMap::new();
+        let mut rows: Vec<[AnyValue; 14]> = Vec::new();
+        for (_budget_id, budget, creation_date) in self.budgets() {
+            let expenses: &DataFrame =
+                expenses.entry(budget.currency.clone()).or_insert_with(|| {
+                    let data_frame: DataFrame = self
+                        .expenses_table
+                        .data_frame
+                        .clone()
+                        .select(["date", "category", "subcategory", "currency", "value"])
+                        .unwrap();
+                    CurrencyExchange::init_for(&budget.currency, &data_frame)
+                        .exchange_currencies(&budget.currency, data_frame)
+                });
+
+            let spent =
+                |date_from: NaiveDate, date_to: NaiveDate| -> f64 {
+                    let mut filter: Expr = col("category").eq(lit(budget.category.as_str())).and(
+                        col("date").is_between(lit(date_from), lit(date_to), ClosedInterval::Both),
+                    );
+                    if let Some(subcategory) = &budget.subcategory {
+                        filter = filter.and(col("subcategory").eq(lit(subcategory.as_str())));
+                    }
+
+                    expenses
+                        .clone()
+                        .lazy()
+                        .filter(filter)
+                        .select([col("value").sum()])
+                        .collect()
+                        .unwrap()
+                        .column("value")
+                        .unwrap()
+                        .f64()
+                        .unwrap()
+                        .get(0)
+                        .unwrap_or(0.0)
+                };
+
+            let (date_from, date_to) = periods.period(&budget.period, date);
+
+            let mut carried_over: f64 = 0.0;
+            if budget.rollover {
+                let first_date: NaiveDate = periods.period(&budget.period, creation_date).0;
+                let mut period_from: NaiveDate = first_date;
+                while period_from < date_from {
+                    carried_over += budget.value;
+                    period_from =
+                        periods.period(&budget.period, period_from).1 + chrono::Duration::days(1);
+                }
+                if first_date < date_from {
+                    carried_over -= spent(first_date, date_from - chrono::Duration::days(1));
+                }
+            }
+
+            let available: f64 = budget.value + carried_over;
+            let actual: f64 = spent(date_from, date_to);
+            let elapsed_days: i64 = (date.min(date_to) - date_from).num_days() + 1;
+            let period_days: i64 = (date_to - date_from).num_days() + 1;
+            let projected: f64 = actual * period_days as f64 / elapsed_days as f64;
+
+            let status: &str = if actual > available {
+                "Over budget"
+            } else if projected > available {
+                "Projected over"
+            } else {
+                "On track"
+            };
+            let used: Option<f64> = match available > 0.0 {
+                true => Some(actual * 100.0 / available),
+                false => None,
+            };
+
+            rows.push([
+                AnyValue::StringOwned(budget.category.clone().into()),
+                AnyValue::StringOwned(
+                    budget
+                        .subcategory
+                        .clone()
+                        .unwrap_or(String::from("All"))
+                        .into(),
+                ),
+                AnyValue::StringOwned(budget.period.to_string().into()),
+                AnyValue::StringOwned(budget.currency.to_string().into()),
+                AnyValue::StringOwned(date_from.to_string().into()),
+                AnyValue::StringOwned(date_to.to_string().into()),
+                AnyValue::Float64(budget.value),
+                AnyValue::Float64(carried_over),
+                AnyValue::Float64(available),
+                AnyValue::Float64(actual),
+                AnyValue::Float64(available - actual),
+                used.map_or(AnyValue::Null, AnyValue::Float64),
+                AnyValue::Float64(projected),
+                AnyValue::StringOwned(status.into()),
+            ]);
+        }
+
+        let names: [&str; 14] = [
+            "Category",
+            "Subcategory",
+            "Period",
+            "Currency",
+            "From",
+            "To",
+            "Budget",
+            "Carried Over",
+            "Available",
+            "Actual",
+            "Remaining",
+            "% Used",
+            "Projected",
+            "Status",
+        ];
+        let columns: Vec<Column> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let values: Vec<AnyValue> = rows.iter().map(|row| row[i].clone()).collect();
+                let data_type: DataType = match i {
+                    6..=12 => DataType::Float64,
+                    _ => DataType::String,
+                };
+                Column::from(
+                    Series::from_any_values_and_dtype((*name).into(), &values, &data_type, true)
+                        .expect("Failed to build budget column"),
+                )
+            })
+            .collect();
+
+        DataFrame::new(columns)
+            .expect("Failed to compare budgets")
+            .lazy()
+            .with_columns([
+                col("Budget").round(2),
+                col("Carried Over").round(2),
+                col("Available").round(2),
+                col("Actual").round(2),
+                col("Remaining").round(2),
+                col("% Used").round(2),
+                col("Projected").round(2),
+            ])
+            .collect()
+            .unwrap()
+    }
+
+    /// Returns how much has been spent and how much was available this month, in currency_to,
+    /// over the monthly budgets. Subcategory budgets only count when their category has no
+    /// monthly budget of its own. Returns None if there are no monthly budgets.
+    pub(crate) fn budget_progress(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+    ) -> Option<(f64, f64)> {
+        let currency_exchange: CurrencyExchange = CurrencyExchange::init();
+        let budget_vs_actual: DataFrame = self
+            .budget_vs_actual_data_frame(date)
+            .lazy()
+            .filter(col("Period").eq(lit(TimeUnit::Month.to_string())))
+            .collect()
+            .unwrap();
+        if budget_vs_actual.height() == 0 {
+            return None;
+        }
+
+        let text = |name: &str, i: usize| -> String {
+            budget_vs_actual
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(i)
+                .unwrap()
+                .to_string()
+        };
+        let number = |name: &str, i: usize| -> f64 {
+            budget_vs_actual
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .get(i)
+                .unwrap()
+        };
+
+        let budgeted_categories: Vec<String> = (0..budget_vs_actual.height())
+            .filter(|i| text("Subcategory", *i) == "All")
+            .map(|i| text("Category", i))
+            .collect();
+
+        let (mut actual, mut available): (f64, f64) = (0.0, 0.0);
+        for i in 0..budget_vs_actual.height() {
+            if text("Subcategory", i) != "All" && budgeted_categories.contains(&text("Category", i))
+            {
+                continue;
+            }
+            let currency_from: Currency =
+                Currency::from_str(text("Currency", i).as_str()).expect("Failed to find currency");
+            let exchange_rate: f64 =
+                currency_exchange.exchange_currency(&currency_from, currency_to, date);
+            actual += number("Actual", i) * exchange_rate;
+            available += number("Available", i) * exchange_rate;
+        }
+
+        Some((actual, available))
+    }
+}
@@ -0,0 +1,300 @@
+use crate::modules::database::DataBase;
+use crate::modules::financial::Transaction;
+use chrono::{Duration, Months, NaiveDate};
+use polars::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Months of history the averages of the forecast are taken from, when there are that many.
+const LOOKBACK_MONTHS: u32 = 12;
+
+/// Length of the periods whose net flows give the spread of the forecast, in days.
+const BUCKET_DAYS: i64 = 30;
+
+/// Expected balance of an account on a day of the forecast, with its uncertainty band of one
+/// standard deviation.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ForecastPoint {
+    pub(crate) date: NaiveDate,
+    pub(crate) account_id: i64,
+    pub(crate) expected: f64,
+    pub(crate) low: f64,
+    pub(crate) high: f64,
+}
+
+impl DataBase {
+    /// Forecasts the balance of every account at the end of every day after date, for months
+    /// months, in the currency of the account.
+    ///
+    /// The forecast starts from the balances at the end of date and adds the occurrences of the
+    /// recurring parties still to come, on their days. The incomes and expenses of the
+    /// categories without recurring parties are projected at their average over the last
+    /// months, spread evenly over the days. Each currency's average net flow is shared among
+    /// the accounts in that currency, in proportion to their fund movements over the same
+    /// months. The band widens with the variability of the monthly net flows.
+    pub(crate) fn forecast(&self, date: NaiveDate, months: u32) -> Vec<ForecastPoint> {
+        let end_date: NaiveDate = date + Months::new(months);
+        let balances: DataFrame = self.account_balances_at(date);
+        let accounts: Vec<(i64, String, f64)> = (0..balances.height())
+            .map(|i| {
+                (
+                    balances
+                        .column("account_id")
+                        .unwrap()
+                        .i64()
+                        .unwrap()
+                        .get(i)
+                        .unwrap(),
+                    balances
+                        .column("currency")
+                        .unwrap()
+                        .str()
+                        .unwrap()
+                        .get(i)
+                        .unwrap()
+                        .to_string(),
+                    balances
+                        .column("total_value")
+                        .unwrap()
+                        .f64()
+                        .unwrap()
+                        .get(i)
+                        .unwrap(),
+                )
+            })
+            .collect();
+
+        // Known recurring items. Occurrences already due but not yet entered are expected on
+        // the first day of the forecast.
+        let mut scheduled: HashMap<(i64, NaiveDate), f64> = HashMap::new();
+        let mut covered: HashSet<(String, String)> = HashSet::new();
+        for (_template_id, template, next_date) in self.templates() {
+            for transaction in template.transactions.iter() {
+                if let Transaction::Income {
+                    category,
+                    subcategory,
+                    ..
+                }
+                | Transaction::Expense {
+                    category,
+                    subcategory,
+                    ..
+                } = transaction
+                {
+                    covered.insert((category.clone(), subcategory.clone()));
+                }
+            }
+
+            let mut occurrence_date: NaiveDate = next_date;
+            while occurrence_date <= end_date
+                && template
+                    .end_date
+                    .is_none_or(|template_end_date| occurrence_date <= template_end_date)
+            {
+                let day: NaiveDate = occurrence_date.max(date + Duration::days(1));
+                for transaction in template.transactions.iter() {
+                    match transaction {
+                        Transaction::Credit {
+                            value, account_id, ..
+                        } => *scheduled.entry((*account_id, day)).or_default() += value,
+                        Transaction::Debit {
+                            value, account_id, ..
+                        } => *scheduled.entry((*account_id, day)).or_default() -= value,
+                        _ => (),
+                    }
+                }
+                occurrence_date = template.recurrence.following(occurrence_date);
+            }
+        }
+
+        // Average net flow per day of the other categories, and its variance per day, by
+        // currency. Only whole periods of BUCKET_DAYS before date are considered.
+        let first_date: Option<NaiveDate> = [
+            &self.incomes_table.data_frame,
+            &self.expenses_table.data_frame,
+        ]
+        .into_iter()
+        .filter_map(|data_frame| {
+            data_frame
+                .column("date")
+                .unwrap()
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .flatten()
+                .min()
+        })
+        .min();
+        let lookback_days: i64 = first_date
+            .map_or(0, |first_date| (date - first_date).num_days() + 1)
+            .min((date - (date - Months::new(LOOKBACK_MONTHS))).num_days());
+        let bucket_count: i64 = (lookback_days / BUCKET_DAYS).max(1);
+        let lookback_start: NaiveDate = date - Duration::days(bucket_count * BUCKET_DAYS - 1);
+
+        let mut buckets: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for (data_frame, sign) in [
+            (&self.incomes_table.data_frame, 1.0),
+            (&self.expenses_table.data_frame, -1.0),
+        ] {
+            let records: DataFrame = data_frame
+                .clone()
+                .lazy()
+                .filter(
+                    col("date")
+                        .gt_eq(lit(lookback_start))
+                        .and(col("date").lt_eq(lit(date))),
+                )
+                .collect()
+                .unwrap();
+            let text = |name: &str, i: usize| -> String {
+                records
+                    .column(name)
+                    .unwrap()
+                    .str()
+                    .unwrap()
+                    .get(i)
+                    .unwrap()
+                    .to_string()
+            };
+            let dates: Vec<NaiveDate> = records
+                .column("date")
+                .unwrap()
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .map(|date| date.unwrap())
+                .collect();
+
+            for (i, record_date) in dates.into_iter().enumerate() {
+                if covered.contains(&(text("category", i), text("subcategory", i))) {
+                    continue;
+                }
+                let bucket: usize = ((date - record_date).num_days() / BUCKET_DAYS) as usize;
+                buckets
+                    .entry(text("currency", i))
+                    .or_insert_with(|| vec![0.0; bucket_count as usize])[bucket] += sign
+                    * records
+                        .column("value")
+                        .unwrap()
+                        .f64()
+                        .unwrap()
+                        .get(i)
+                        .unwrap();
+            }
+        }
+        let flows: HashMap<String, (f64, f64)> = buckets
+            .into_iter()
+            .map(|(currency, totals)| {
+                let mean: f64 = totals.iter().sum::<f64>() / totals.len() as f64;
+                let variance: f64 = match totals.len() > 1 {
+                    true => {
+                        totals
+                            .iter()
+                            .map(|total| (total - mean).powi(2))
+                            .sum::<f64>()
+                            / (totals.len() - 1) as f64
+                    }
+                    false => 0.0,
+                };
+                (
+                    currency,
+                    (mean / BUCKET_DAYS as f64, variance / BUCKET_DAYS as f64),
+                )
+            })
+            .collect();
+
+        // Share of every account in the flows of its currency.
+        let movements: DataFrame = self
+            .funds_table
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(
+                col("date")
+                    .gt_eq(lit(lookback_start))
+                    .and(col("date").lt_eq(lit(date))),
+            )
+            .group_by(["account_id"])
+            .agg([col("value").abs().sum()])
+            .collect()
+            .unwrap();
+        let movement = |account_id: i64| -> f64 {
+            movements
+                .clone()
+                .lazy()
+                .filter(col("account_id").eq(lit(account_id)))
+                .collect()
+                .unwrap()
+                .column("value")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .next()
+                .flatten()
+                .unwrap_or(0.0)
+        };
+        let account_movements: Vec<f64> = accounts
+            .iter()
+            .map(|(account_id, _currency, _balance)| movement(*account_id))
+            .collect();
+        let share = |i: usize| -> f64 {
+            let currency: &String = &accounts[i].1;
+            let same_currency: Vec<usize> = (0..accounts.len())
+                .filter(|j| accounts[*j].1 == *currency)
+                .collect();
+            let total: f64 = same_currency.iter().map(|j| account_movements[*j]).sum();
+            match total > 0.0 {
+                true => account_movements[i] / total,
+                false => 1.0 / same_currency.len() as f64,
+            }
+        };
+
+        let mut points: Vec<ForecastPoint> = Vec::new();
+        for (i, (account_id, currency, balance)) in accounts.iter().enumerate() {
+            let (rate, variance) = flows.get(currency).cloned().unwrap_or((0.0, 0.0));
+            let share: f64 = share(i);
+            let mut scheduled_balance: f64 = *balance;
+            let mut day: NaiveDate = date + Duration::days(1);
+            let mut days: f64 = 1.0;
+            while day <= end_date {
+                scheduled_balance += scheduled.get(&(*account_id, day)).unwrap_or(&0.0);
+                let expected: f64 = scheduled_balance + share * rate * days;
+                let deviation: f64 = share * (variance * days).sqrt();
+                points.push(ForecastPoint {
+                    date: day,
+                    account_id: *account_id,
+                    expected,
+                    low: expected - deviation,
+                    high: expected + deviation,
+                });
+                day += Duration::days(1);
+                days += 1.0;
+            }
+        }
+
+        points
+    }
+
+    /// Returns the accounts projected to go negative within the forecast, with the first day
+    /// they are expected to and their lowest expected balance, in the currency of the account.
+    pub(crate) fn forecast_warnings(
+        &self,
+        date: NaiveDate,
+        months: u32,
+    ) -> Vec<(i64, NaiveDate, f64)> {
+        let mut warnings: BTreeMap<i64, (NaiveDate, f64)> = BTreeMap::new();
+        for point in self.forecast(date, months) {
+            if point.expected < 0.0 {
+                let warning = warnings
+                    .entry(point.account_id)
+                    .or_insert((point.date, point.expected));
+                warning.1 = warning.1.min(point.expected);
+            }
+        }
+
+        warnings
+            .into_iter()
+            .map(|(account_id, (date, lowest))| (account_id, date, lowest))
+            .collect()
+    }
+}
@@ -0,0 +1,193 @@
+use crate::modules::currency_exchange::CurrencyExchange;
+use crate::modules::database::summaries::{FundGrouping, Valuation};
+use crate::modules::database::{data_frame_to_csv_string, DataBase};
+use crate::modules::financial::Currency;
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Part of the funds earmarked for a purpose, e.g. an emergency fund or a new car. Goals do not
+/// move money between accounts.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Goal {
+    pub(crate) name: String,
+    pub(crate) target_value: f64,
+    pub(crate) currency: Currency,
+    pub(crate) target_date: NaiveDate,
+}
+
+/// Amount to set aside every month from date on to allocate remaining by target_date. What is
+/// due after the target date must be allocated at once.
+pub(crate) fn monthly_contribution(remaining: f64, date: NaiveDate, target_date: NaiveDate) -> f64 {
+    if remaining <= 0.0 {
+        return 0.0;
+    }
+    let mut months_left: i32 =
+        (target_date.year() - date.year()) * 12 + target_date.month() as i32 - date.month() as i32;
+    if target_date.day() < date.day() {
+        months_left -= 1;
+    }
+
+    remaining / months_left.max(1) as f64
+}
+
+impl DataBase {
+    pub(crate) fn insert_goal(&mut self, goal: &Goal) -> i64 {
+        self.goal_table.insert_goal(
+            goal.name.as_str(),
+            goal.target_value,
+            &goal.currency,
+            goal.target_date,
+        )
+    }
+
+    /// Deletes a goal and its allocations.
+    pub(crate) fn delete_goal(&mut self, goal_id: i64) -> () {
+        self.allocation_table.delete_goal(goal_id);
+        self.goal_table.delete_goal(goal_id);
+    }
+
+    pub(crate) fn allocate(&mut self, goal_id: i64, value: f64, date: NaiveDate) -> i64 {
+        self.allocation_table
+            .insert_allocation(goal_id, value, date)
+    }
+
+    /// Returns every goal with its ID, sorted by target date.
+    pub(crate) fn goals(&self) -> Vec<(i64, Goal)> {
+        let records: DataFrame = self
+            .goal_table
+            .data_frame
+            .clone()
+            .lazy()
+            .sort(["target_date", "name"], Default::default())
+            .collect()
+            .unwrap();
+
+        let column = |name: &str| records.column(name).unwrap().clone();
+        let ids = column("goal_id");
+        let names = column("name");
+        let target_values = column("target_value");
+        let currencies = column("currency");
+        let target_dates = column("target_date");
+
+        (0..records.height())
+            .map(|i| {
+                let goal: Goal = Goal {
+                    name: names.str().unwrap().get(i).unwrap().to_string(),
+                    target_value: target_values.f64().unwrap().get(i).unwrap(),
+                    currency: Currency::from_str(currencies.str().unwrap().get(i).unwrap())
+                        .expect("Failed to read goal currency"),
+                    target_date: target_dates
+                        .date()
+                        .unwrap()
+                        .as_date_iter()
+                        .nth(i)
+                        .flatten()
+                        .unwrap(),
+                };
+
+                (ids.i64().unwrap().get(i).unwrap(), goal)
+            })
+            .collect()
+    }
+
+    /// Returns what has been allocated to a goal until the end of date, in its currency.
+    pub(crate) fn allocated(&self, goal_id: i64, date: NaiveDate) -> f64 {
+        self.allocation_table.allocated(goal_id, date)
+    }
+
+    pub(crate) fn goals_report(&self, currency_to: &Currency, date: NaiveDate) -> String {
+        data_frame_to_csv_string(&mut self.goals_report_data_frame(currency_to, date))
+    }
+
+    /// Returns the progress of every goal at the end of date and the monthly contribution still
+    /// needed to reach it, followed by the funds left unallocated and the total funds, where
+    /// overdrawn accounts count negatively. Amounts are converted to currency_to at the exchange
+    /// rate of date.
+    pub(crate) fn goals_report_data_frame(
+        &self,
+        currency_to: &Currency,
+        date: NaiveDate,
+    ) -> DataFrame {
+        let currency_exchange: CurrencyExchange =
+            CurrencyExchange::init_for(currency_to, &self.goal_table.data_frame);
+
+        let mut names: Vec<String> = Vec::new();
+        let mut target_dates: Vec<Option<String>> = Vec::new();
+        let mut targets: Vec<Option<f64>> = Vec::new();
+        let mut allocations: Vec<f64> = Vec::new();
+        let mut remainings: Vec<Option<f64>> = Vec::new();
+        let mut progresses: Vec<Option<f64>> = Vec::new();
+        let mut contributions: Vec<Option<f64>> = Vec::new();
+        for (goal_id, goal) in self.goals() {
+            let exchange_rate: f64 =
+                currency_exchange.exchange_currency(&goal.currency, currency_to, date);
+            let target: f64 = goal.target_value * exchange_rate;
+            let allocated: f64 = self.allocated(goal_id, date) * exchange_rate;
+            let remaining: f64 = (target - allocated).max(0.0);
+
+            names.push(goal.name);
+            target_dates.push(Some(goal.target_date.to_string()));
+            targets.push(Some(target));
+            allocations.push(allocated);
+            remainings.push(Some(remaining));
+            progresses.push(match target > 0.0 {
+                true => Some(allocated * 100.0 / target),
+                false => None,
+            });
+            contributions.push(Some(monthly_contribution(
+                remaining,
+                date,
+                goal.target_date,
+            )));
+        }
+
+        let funds: f64 = self
+            .fund_stand_at_data_frame(
+                date,
+                Some(currency_to),
+                &Valuation::Historical,
+                &FundGrouping::Account,
+                None,
+            )
+            .unwrap()
+            .column(currency_to.to_string().as_str())
+            .unwrap()
+            .f64()
+            .unwrap()
+            .sum()
+            .unwrap_or(0.0);
+        let allocated: f64 = allocations.iter().sum();
+
+        for (name, value) in [("Unallocated", funds - allocated), ("Total Funds", funds)] {
+            names.push(String::from(name));
+            target_dates.push(None);
+            targets.push(None);
+            allocations.push(value);
+            remainings.push(None);
+            progresses.push(None);
+            contributions.push(None);
+        }
+
+        df!(
+            "Goal" => names,
+            "Target Date" => target_dates,
+            "Target" => targets,
+            "Allocated" => allocations,
+            "Remaining" => remainings,
+            "% Progress" => progresses,
+            "Monthly Contribution" => contributions
+        )
+        .expect("Failed to report goals")
+        .lazy()
+        .with_columns([
+            col("Target").round(2),
+            col("Allocated").round(2),
+            col("Remaining").round(2),
+            col("% Progress").round(2),
+            col("Monthly Contribution").round(2),
+        ])
+        .collect()
+        .unwrap()
+    }
+}
@@ -0,0 +1,322 @@
+use crate::modules::currency_exchange::CurrencyExchange;
+use crate::modules::database::summaries::{FundGrouping, Valuation};
+use crate::modules::database::{data_frame_to_csv_string, DataBase};
+use crate::modules::financial::{AccountType, Currency};
+use chrono::{Duration, Months, NaiveDate};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Assumptions of a financial independence projection. Returns and rates are yearly, in
+/// percent.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct IndependencePlan {
+    /// Months of history the average savings and expenses are taken from
+    pub(crate) window_months: u32,
+    pub(crate) deposit_return: f64,
+    pub(crate) investment_return: f64,
+    pub(crate) cash_return: f64,
+    /// Share of the net worth that can be withdrawn every year once independent
+    pub(crate) withdrawal_rate: f64,
+    /// What-if change of the savings, in percent of the average ones
+    pub(crate) savings_change: f64,
+    /// What-if change of every return, in percentage points
+    pub(crate) return_change: f64,
+}
+
+impl Default for IndependencePlan {
+    fn default() -> Self {
+        IndependencePlan {
+            window_months: 12,
+            deposit_return: 1.0,
+            investment_return: 5.0,
+            cash_return: 0.0,
+            withdrawal_rate: 4.0,
+            savings_change: 0.0,
+            return_change: 0.0,
+        }
+    }
+}
+
+impl IndependencePlan {
+    /// Expected yearly return of the accounts of a type, what-if change included.
+    pub(crate) fn expected_return(&self, account_type: &AccountType) -> f64 {
+        let expected_return: f64 = match account_type {
+            AccountType::Deposit => self.deposit_return,
+            AccountType::Investment => self.investment_return,
+            AccountType::Cash => self.cash_return,
+        };
+
+        expected_return + self.return_change
+    }
+
+    /// Whether the plan can be projected: without a positive withdrawal rate, no net worth is
+    /// ever enough.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.withdrawal_rate > 0.0
+    }
+
+    /// Same plan without the what-if changes.
+    pub(crate) fn baseline(&self) -> IndependencePlan {
+        IndependencePlan {
+            savings_change: 0.0,
+            return_change: 0.0,
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn is_what_if(&self) -> bool {
+        (self.savings_change != 0.0) | (self.return_change != 0.0)
+    }
+}
+
+/// Projection of the net worth until financial independence, when the yearly withdrawals it
+/// allows cover the yearly expenses.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct IndependenceProjection {
+    pub(crate) net_worth: f64,
+    pub(crate) monthly_savings: f64,
+    pub(crate) yearly_expenses: f64,
+    /// Yearly return of the current mix of account types, in percent
+    pub(crate) expected_return: f64,
+    /// Net worth needed to be independent
+    pub(crate) target: f64,
+    /// None if the target is not reached within the projection
+    pub(crate) months_to_independence: Option<u32>,
+    /// Net worth at the end of every month, starting at date
+    pub(crate) trajectory: Vec<(NaiveDate, f64)>,
+}
+
+/// Reads a measure of a projection, for the report.
+type Measure = fn(&IndependenceProjection) -> Option<f64>;
+
+/// Sum of the records of a table from date_from to date_to, both included, converted to
+/// currency_to at the exchange rate of their dates.
+pub(crate) fn converted_total(
+    data_frame: &DataFrame,
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+    currency_to: &Currency,
+) -> f64 {
+    let records: DataFrame = data_frame
+        .clone()
+        .lazy()
+        .filter(col("date").is_between(lit(date_from), lit(date_to), ClosedInterval::Both))
+        .select([col("date"), col("currency"), col("value")])
+        .collect()
+        .unwrap();
+    if records.is_empty() {
+        return 0.0;
+    }
+
+    CurrencyExchange::init_for(currency_to, &records)
+        .exchange_currencies(currency_to, records)
+        .column("value")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .sum()
+        .unwrap_or(0.0)
+}
+
+/// Months of the window_months up to date that the records of the data_frames cover, from the
+/// first one on, so that averages over a shorter history are not diluted by empty months.
+pub(crate) fn covered_months(
+    data_frames: &[&DataFrame],
+    date: NaiveDate,
+    window_months: u32,
+) -> u32 {
+    let window_months: u32 = window_months.max(1);
+    let first_date: Option<NaiveDate> = data_frames
+        .iter()
+        .filter_map(|data_frame| {
+            data_frame
+                .column("date")
+                .unwrap()
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .flatten()
+                .min()
+        })
+        .min();
+
+    match first_date {
+        Some(first_date) => (1..=window_months)
+            .find(|months| date - Months::new(*months) + Duration::days(1) <= first_date)
+            .unwrap_or(window_months),
+        None => window_months,
+    }
+}
+
+impl DataBase {
+    /// Projects the net worth at date, in currency_to, for years years. Every month it grows by
+    /// the expected return of its current mix of account types and by the average monthly
+    /// savings, income minus expenses, over the window of the plan, or over the months since the
+    /// first record if fewer. Independence comes once
+    /// the withdrawal rate of the net worth covers the average yearly expenses.
+    pub(crate) fn independence_projection(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        plan: &IndependencePlan,
+        years: u32,
+    ) -> IndependenceProjection {
+        let fund_stand: DataFrame = self
+            .fund_stand_at_data_frame(
+                date,
+                Some(currency_to),
+                &Valuation::Historical,
+                &FundGrouping::AccountType,
+                None,
+            )
+            .unwrap();
+        let balances: Vec<(AccountType, f64)> = fund_stand
+            .column("Account Type")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .zip(
+                fund_stand
+                    .column(currency_to.to_string().as_str())
+                    .unwrap()
+                    .f64()
+                    .unwrap(),
+            )
+            .map(|(account_type, value)| {
+                (
+                    AccountType::from_str(account_type.unwrap())
+                        .expect("Failed to find account type"),
+                    value.unwrap(),
+                )
+            })
+            .collect();
+        let net_worth: f64 = balances.iter().map(|(_account_type, value)| value).sum();
+        let positive_worth: f64 = balances
+            .iter()
+            .map(|(_account_type, value)| value.max(0.0))
+            .sum();
+        let expected_return: f64 = match positive_worth > 0.0 {
+            true => {
+                balances
+                    .iter()
+                    .map(|(account_type, value)| {
+                        value.max(0.0) * plan.expected_return(account_type)
+                    })
+                    .sum::<f64>()
+                    / positive_worth
+            }
+            false => plan.expected_return(&AccountType::Deposit),
+        };
+
+        let window_months: u32 = covered_months(
+            &[
+                &self.incomes_table.data_frame,
+                &self.expenses_table.data_frame,
+            ],
+            date,
+            plan.window_months,
+        );
+        let date_from: NaiveDate = date - Months::new(window_months) + Duration::days(1);
+        let income: f64 =
+            converted_total(&self.incomes_table.data_frame, date_from, date, currency_to);
+        let expenses: f64 = converted_total(
+            &self.expenses_table.data_frame,
+            date_from,
+            date,
+            currency_to,
+        );
+        let monthly_savings: f64 =
+            (income - expenses) / window_months as f64 * (1.0 + plan.savings_change / 100.0);
+        let yearly_expenses: f64 = expenses / window_months as f64 * 12.0;
+        let target: f64 = yearly_expenses / (plan.withdrawal_rate / 100.0);
+
+        let monthly_return: f64 = (1.0 + expected_return / 100.0).powf(1.0 / 12.0) - 1.0;
+        let mut trajectory: Vec<(NaiveDate, f64)> = vec![(date, net_worth)];
+        let mut months_to_independence: Option<u32> = match net_worth >= target {
+            true => Some(0),
+            false => None,
+        };
+        let mut value: f64 = net_worth;
+        for month in 1..=years * 12 {
+            value = value * (1.0 + monthly_return) + monthly_savings;
+            trajectory.push((date + Months::new(month), value));
+            if months_to_independence.is_none() && value >= target {
+                months_to_independence = Some(month);
+            }
+        }
+
+        IndependenceProjection {
+            net_worth,
+            monthly_savings,
+            yearly_expenses,
+            expected_return,
+            target,
+            months_to_independence,
+            trajectory,
+        }
+    }
+
+    /// Returns a csv in String format with the independence report.
+    pub(crate) fn independence_report(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        plan: &IndependencePlan,
+        years: u32,
+    ) -> String {
+        data_frame_to_csv_string(&mut self.independence_report_data_frame(
+            date,
+            currency_to,
+            plan,
+            years,
+        ))
+    }
+
+    /// Compares the projections of the baseline and of the what-if scenario of the plan, one
+    /// measure per row.
+    pub(crate) fn independence_report_data_frame(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        plan: &IndependencePlan,
+        years: u32,
+    ) -> DataFrame {
+        let projections: [IndependenceProjection; 2] = [
+            self.independence_projection(date, currency_to, &plan.baseline(), years),
+            self.independence_projection(date, currency_to, plan, years),
+        ];
+        let measures: [(&str, Measure); 7] = [
+            ("Net worth", |projection| Some(projection.net_worth)),
+            ("Monthly savings", |projection| {
+                Some(projection.monthly_savings)
+            }),
+            ("Yearly expenses", |projection| {
+                Some(projection.yearly_expenses)
+            }),
+            ("Expected return (%)", |projection| {
+                Some(projection.expected_return)
+            }),
+            ("Target net worth", |projection| Some(projection.target)),
+            ("Years to independence", |projection| {
+                projection
+                    .months_to_independence
+                    .map(|months| months as f64 / 12.0)
+            }),
+            ("Final net worth", |projection| {
+                projection.trajectory.last().map(|(_date, value)| *value)
+            }),
+        ];
+
+        df!(
+            "Measure" => measures.iter().map(|(name, _measure)| name.to_string()).collect::<Vec<String>>(),
+            "Baseline" => measures.iter().map(|(_name, measure)| measure(&projections[0])).collect::<Vec<Option<f64>>>(),
+            "What If" => measures.iter().map(|(_name, measure)| measure(&projections[1])).collect::<Vec<Option<f64>>>()
+        )
+        .expect("Failed to report independence projection")
+        .lazy()
+        .with_columns([col("Baseline").round(2), col("What If").round(2)])
+        .collect()
+        .unwrap()
+    }
+}
@@ -0,0 +1,1065 @@
+use crate::modules::database::summaries::{FundGrouping, Valuation};
+use crate::modules::database::DataBase;
+use crate::modules::financial::{
+    Account, AccountType, Currency, Entity, EntityType, Party, Transaction,
+};
+use chrono::{Days, NaiveDate};
+use polars::prelude::*;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::fs::{create_dir, read_to_string, File};
+use std::io::Write;
+use std::iter::zip;
+use std::path::Path;
+use std::str::FromStr;
+use strum_macros::EnumIter;
+
+const OPENING_BALANCES_ACCOUNT: &str = "Equity:Opening-Balances";
+const UNKNOWN_PAYEE: &str = "Unknown";
+
+/// Plain-text accounting formats the ledger can be exported to. Journals in either of them
+/// can also be imported.
+#[derive(Debug, PartialEq, Eq, EnumIter, Clone, Default)]
+pub(crate) enum JournalFormat {
+    #[default]
+    Beancount,
+    Hledger,
+}
+
+impl JournalFormat {
+    fn extension(&self) -> &str {
+        match self {
+            JournalFormat::Beancount => "beancount",
+            JournalFormat::Hledger => "journal",
+        }
+    }
+}
+
+// Conversion to string
+impl Display for JournalFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            JournalFormat::Beancount => "Beancount".to_string(),
+            JournalFormat::Hledger => "Hledger".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// One leg of a journal transaction. Positive values increase the account, following the
+/// usual double-entry convention where incomes are negative and expenses positive.
+pub(crate) struct Posting {
+    pub(crate) account: String,
+    pub(crate) value: f64,
+    pub(crate) currency: Currency,
+}
+
+/// A dated, balanced set of postings, as written in plain-text accounting journals.
+pub(crate) struct JournalTransaction {
+    pub(crate) date: NaiveDate,
+    pub(crate) payee: String,
+    pub(crate) narration: String,
+    pub(crate) postings: Vec<Posting>,
+}
+
+/// Turns free text into a valid account name component: words are capitalized and joined
+/// with dashes, and anything that is not a letter or a digit is dropped.
+pub(crate) fn account_component(text: &str) -> String {
+    let component: String = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            let first: String = chars.next().unwrap().to_uppercase().collect();
+            format!("{}{}", first, chars.as_str())
+        })
+        .collect::<Vec<String>>()
+        .join("-");
+
+    if component.is_empty() {
+        String::from("Unknown")
+    } else {
+        component
+    }
+}
+
+/// Builds a hierarchical account name out of a root and a list of free-text components. Empty
+/// components are skipped, so that a missing subcategory does not produce an empty level.
+fn account_name(root: &str, components: &[String]) -> String {
+    let mut name: String = root.to_string();
+    for component in components.iter().filter(|component| !component.is_empty()) {
+        name = format!("{}:{}", name, account_component(component));
+    }
+
+    name
+}
+
+/// Wraps a string in double quotes, replacing the ones it may contain.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "'"))
+}
+
+impl DataBase {
+    fn asset_account_name(&self, account_id: i64) -> String {
+        account_name("Assets", &[self.account(account_id).name()])
+    }
+
+    /// Translates a party into a journal transaction, dated on the earliest of its
+    /// transactions.
+    fn journal_transaction(&self, party: &mut Party) -> JournalTransaction {
+        let mut postings: Vec<Posting> = Vec::new();
+        let mut payees: Vec<String> = Vec::new();
+        let mut narrations: Vec<String> = Vec::new();
+
+        for transaction in party.iter() {
+            let posting: Posting = match transaction {
+                Transaction::Income {
+                    value,
+                    currency,
+                    category,
+                    subcategory,
+                    description,
+                    entity_id,
+                    ..
+                }
+                | Transaction::Expense {
+                    value,
+                    currency,
+                    category,
+                    subcategory,
+                    description,
+                    entity_id,
+                    ..
+                } => {
+                    let payee: String = self.entity(*entity_id).name();
+                    if !payee.is_empty() & !payees.contains(&payee) {
+                        payees.push(payee);
+                    }
+                    if !description.is_empty() & !narrations.contains(description) {
+                        narrations.push(description.to_string());
+                    }
+
+                    let (root, sign): (&str, f64) = match transaction {
+                        Transaction::Income { .. } => ("Income", -1.0),
+                        _ => ("Expenses", 1.0),
+                    };
+                    Posting {
+                        account: account_name(
+                            root,
+                            &[category.to_string(), subcategory.to_string()],
+                        ),
+                        value: sign * value,
+                        currency: currency.clone(),
+                    }
+                }
+                Transaction::Credit {
+                    value,
+                    currency,
+                    account_id,
+                    ..
+                } => Posting {
+                    account: self.asset_account_name(*account_id),
+                    value: *value,
+                    currency: currency.clone(),
+                },
+                Transaction::Debit {
+                    value,
+                    currency,
+                    account_id,
+                    ..
+                } => Posting {
+                    account: self.asset_account_name(*account_id),
+                    value: -value,
+                    currency: currency.clone(),
+                },
+            };
+            postings.push(posting);
+        }
+
+        let date: NaiveDate = party
+            .iter()
+            .map(|transaction| *transaction.date())
+            .min()
+            .unwrap_or(party.creation_date);
+
+        JournalTransaction {
+            date,
+            payee: payees.join(", "),
+            narration: narrations.join("; "),
+            postings,
+        }
+    }
+
+    /// Returns one opening-balance transaction per account with a non-zero initial balance,
+    /// dated on the creation date of the account.
+    fn opening_balances(&self) -> Vec<JournalTransaction> {
+        let account_table: &DataFrame = &self.account_table.data_frame;
+        let account_ids = account_table.column("account_id").unwrap().i64().unwrap();
+        let creation_dates = account_table
+            .column("creation_date")
+            .unwrap()
+            .date()
+            .unwrap()
+            .as_date_iter();
+
+        let mut opening_balances: Vec<JournalTransaction> = Vec::new();
+        for (account_id, creation_date) in zip(account_ids.into_no_null_iter(), creation_dates) {
+            let account = self.account(account_id);
+            if account.initial_balance().abs() < 0.005 {
+                continue;
+            }
+
+            opening_balances.push(JournalTransaction {
+                date: creation_date.expect("Found account without creation date"),
+                payee: String::from("Opening balance"),
+                narration: account.name(),
+                postings: vec![
+                    Posting {
+                        account: self.asset_account_name(account_id),
+                        value: account.initial_balance(),
+                        currency: account.currency().clone(),
+                    },
+                    Posting {
+                        account: String::from(OPENING_BALANCES_ACCOUNT),
+                        value: -account.initial_balance(),
+                        currency: account.currency().clone(),
+                    },
+                ],
+            });
+        }
+
+        opening_balances
+    }
+
+    /// Returns the whole ledger as a chronologically sorted list of journal transactions,
+    /// starting with the opening balances of the accounts.
+    pub(crate) fn journal_transactions(&self) -> Vec<JournalTransaction> {
+        let mut journal_transactions: Vec<JournalTransaction> = self.opening_balances();
+        for party_id in self.iter_party_ids() {
+            let mut party: Party = self.party(party_id);
+            if party.is_empty() {
+                continue;
+            }
+            journal_transactions.push(self.journal_transaction(&mut party));
+        }
+        journal_transactions.sort_by_key(|journal_transaction| journal_transaction.date);
+
+        journal_transactions
+    }
+
+    /// Checks that the asset balances implied by the journal match the ones reported by the
+    /// fund stand, and returns them, keyed by account name and currency.
+    fn check_journal_balances(
+        &self,
+        journal_transactions: &[JournalTransaction],
+    ) -> Result<BTreeMap<(String, String), f64>, String> {
+        let mut journal_balances: BTreeMap<(String, String), f64> = BTreeMap::new();
+        for journal_transaction in journal_transactions {
+            for posting in journal_transaction.postings.iter() {
+                if posting.account.starts_with("Assets:") {
+                    *journal_balances
+                        .entry((posting.account.clone(), posting.currency.to_string()))
+                        .or_insert(0.0) += posting.value;
+                }
+            }
+        }
+
+        let last_date: NaiveDate = journal_transactions
+            .iter()
+            .map(|journal_transaction| journal_transaction.date)
+            .max()
+            .unwrap_or_default();
+        let fund_stand: DataFrame = self.fund_stand_at_data_frame(
+            last_date,
+            None,
+            &Valuation::default(),
+            &FundGrouping::Account,
+            None,
+        )?;
+        let mut fund_balances: BTreeMap<(String, String), f64> = BTreeMap::new();
+        let names = fund_stand.column("Name").unwrap().str().unwrap();
+        let currencies = fund_stand.column("Currency").unwrap().str().unwrap();
+        let total_values = fund_stand.column("Total Value").unwrap().f64().unwrap();
+        for ((name, currency), total_value) in zip(zip(names, currencies), total_values) {
+            *fund_balances
+                .entry((
+                    account_name("Assets", &[name.unwrap().to_string()]),
+                    currency.unwrap().to_string(),
+                ))
+                .or_insert(0.0) += total_value.unwrap_or(0.0);
+        }
+
+        let mut mismatches: Vec<String> = Vec::new();
+        for key in journal_balances.keys().chain(fund_balances.keys()) {
+            let journal_balance: f64 = *journal_balances.get(key).unwrap_or(&0.0);
+            let fund_balance: f64 = *fund_balances.get(key).unwrap_or(&0.0);
+            if (journal_balance - fund_balance).abs() >= 0.01 {
+                mismatches.push(format!(
+                    "{} ({}): journal {:.2}, fund stand {:.2}",
+                    key.0, key.1, journal_balance, fund_balance
+                ));
+            }
+        }
+        mismatches.dedup();
+
+        if mismatches.is_empty() {
+            Ok(journal_balances)
+        } else {
+            Err(format!(
+                "Journal balances do not match the fund stand: {}",
+                mismatches.join(", ")
+            ))
+        }
+    }
+
+    /// Renders the whole ledger as a plain-text accounting journal. The journal ends with a
+    /// balance assertion per asset account, so that the accounting tool double checks that
+    /// the totals match the fund stand.
+    pub(crate) fn journal(&self, journal_format: &JournalFormat) -> Result<String, String> {
+        let journal_transactions: Vec<JournalTransaction> = self.journal_transactions();
+        let balances: BTreeMap<(String, String), f64> =
+            self.check_journal_balances(&journal_transactions)?;
+
+        let mut open_dates: BTreeMap<String, NaiveDate> = BTreeMap::new();
+        for journal_transaction in journal_transactions.iter() {
+            for posting in journal_transaction.postings.iter() {
+                open_dates
+                    .entry(posting.account.clone())
+                    .and_modify(|date| *date = (*date).min(journal_transaction.date))
+                    .or_insert(journal_transaction.date);
+            }
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        match journal_format {
+            JournalFormat::Beancount => {
+                lines.push(format!(
+                    "option \"operating_currency\" \"{}\"",
+                    Currency::default()
+                ));
+                lines.push(String::new());
+                for (account, date) in open_dates.iter() {
+                    lines.push(format!("{} open {}", date, account));
+                }
+            }
+            JournalFormat::Hledger => {
+                for account in open_dates.keys() {
+                    lines.push(format!("account {}", account));
+                }
+            }
+        }
+        lines.push(String::new());
+
+        for journal_transaction in journal_transactions.iter() {
+            let header: String = match journal_format {
+                JournalFormat::Beancount => format!(
+                    "{} * {} {}",
+                    journal_transaction.date,
+                    quote(journal_transaction.payee.as_str()),
+                    quote(journal_transaction.narration.as_str())
+                ),
+                JournalFormat::Hledger => format!(
+                    "{} * {} | {}",
+                    journal_transaction.date,
+                    journal_transaction.payee,
+                    journal_transaction.narration
+                ),
+            };
+            lines.push(header.trim_end().to_string());
+
+            for posting in journal_transaction.postings.iter() {
+                lines.push(format!(
+                    "    {:<50}  {:>12.2} {}",
+                    posting.account, posting.value, posting.currency
+                ));
+            }
+            lines.push(String::new());
+        }
+
+        // Balance assertions are checked at the beginning of the day, hence the day after.
+        let assertion_date: NaiveDate = journal_transactions
+            .iter()
+            .map(|journal_transaction| journal_transaction.date)
+            .max()
+            .unwrap_or_default()
+            .checked_add_days(Days::new(1))
+            .unwrap();
+        match journal_format {
+            JournalFormat::Beancount => {
+                for ((account, currency), balance) in balances.iter() {
+                    lines.push(format!(
+                        "{} balance {:<50} {:>12.2} {}",
+                        assertion_date, account, balance, currency
+                    ));
+                }
+            }
+            JournalFormat::Hledger => {
+                lines.push(format!("{} Balance assertions", assertion_date));
+                for ((account, currency), balance) in balances.iter() {
+                    lines.push(format!(
+                        "    {:<50}  {:>12} {} = {:.2} {}",
+                        account, 0, currency, balance, currency
+                    ));
+                }
+            }
+        }
+
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// Writes the journal into the data folder and returns the path of the written file.
+    pub(crate) fn export_journal(&self, journal_format: &JournalFormat) -> Result<String, String> {
+        let journal: String = self.journal(journal_format)?;
+
+        let file_name: String = format!("data/ledger.{}", journal_format.extension());
+        let path: &Path = Path::new(&file_name);
+        if !path.parent().expect("path does not have parent").exists() {
+            let _ = create_dir(path.parent().expect("path does not have parents"));
+        }
+
+        File::create(path)
+            .and_then(|mut file| file.write_all(journal.as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+
+        Ok(file_name)
+    }
+}
+
+/// What a journal account becomes once imported.
+#[derive(Debug, PartialEq, Eq, EnumIter, Clone)]
+pub(crate) enum JournalAccountRole {
+    Account,
+    Income,
+    Expense,
+    OpeningBalance,
+}
+
+// Conversion to string
+impl Display for JournalAccountRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            JournalAccountRole::Account => "Account".to_string(),
+            JournalAccountRole::Income => "Income".to_string(),
+            JournalAccountRole::Expense => "Expense".to_string(),
+            JournalAccountRole::OpeningBalance => "Opening balance".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// How a single journal account is translated: into an account of a given type, or into an
+/// income or expense category and subcategory.
+pub(crate) struct JournalAccountMapping {
+    pub(crate) journal_account: String,
+    pub(crate) role: JournalAccountRole,
+    pub(crate) account_type: AccountType,
+    pub(crate) category: String,
+    pub(crate) subcategory: String,
+}
+
+/// Translation of the account hierarchy of a journal into the concepts of the database.
+#[derive(Default)]
+pub(crate) struct JournalMapping {
+    pub(crate) accounts: Vec<JournalAccountMapping>,
+    pub(crate) country: String,
+}
+
+/// Turns an account name component back into free text.
+fn free_text(component: &str) -> String {
+    component.replace('-', " ").trim().to_string()
+}
+
+impl JournalMapping {
+    /// Proposes a mapping out of the usual top-level accounts (Assets, Liabilities, Income,
+    /// Expenses and Equity). Account types are guessed from the account names, and categories
+    /// from the first two levels below Income and Expenses.
+    pub(crate) fn guess(journal_transactions: &[JournalTransaction], country: String) -> Self {
+        let mut journal_accounts: Vec<String> = journal_transactions
+            .iter()
+            .flat_map(|journal_transaction| journal_transaction.postings.iter())
+            .map(|posting| posting.account.clone())
+            .collect();
+        journal_accounts.sort();
+        journal_accounts.dedup();
+
+        let accounts: Vec<JournalAccountMapping> = journal_accounts
+            .into_iter()
+            .map(|journal_account| {
+                let components: Vec<&str> = journal_account.split(':').collect();
+                let role: JournalAccountRole = match components[0].to_lowercase().as_str() {
+                    "income" | "incomes" | "revenue" | "revenues" => JournalAccountRole::Income,
+                    "expenses" | "expense" => JournalAccountRole::Expense,
+                    "equity" => JournalAccountRole::OpeningBalance,
+                    _ => JournalAccountRole::Account,
+                };
+
+                let lowercase_account: String = journal_account.to_lowercase();
+                let account_type: AccountType = if ["cash", "wallet", "pocket"]
+                    .iter()
+                    .any(|keyword| lowercase_account.contains(keyword))
+                {
+                    AccountType::Cash
+                } else if [
+                    "invest",
+                    "broker",
+                    "stock",
+                    "share",
+                    "fund",
+                    "pension",
+                    "retirement",
+                    "etf",
+                ]
+                .iter()
+                .any(|keyword| lowercase_account.contains(keyword))
+                {
+                    AccountType::Investment
+                } else {
+                    AccountType::Deposit
+                };
+
+                let category: String = components
+                    .get(1)
+                    .map_or(free_text(components[0]), |component| free_text(component));
+                let subcategory: String = components
+                    .iter()
+                    .skip(2)
+                    .map(|component| free_text(component))
+                    .collect::<Vec<String>>()
+                    .join(" / ");
+
+                JournalAccountMapping {
+                    journal_account,
+                    role,
+                    account_type,
+                    category,
+                    subcategory,
+                }
+            })
+            .collect();
+
+        JournalMapping { accounts, country }
+    }
+
+    fn get(&self, journal_account: &str) -> Option<&JournalAccountMapping> {
+        self.accounts
+            .iter()
+            .find(|mapping| mapping.journal_account == journal_account)
+    }
+}
+
+/// Outcome of a journal import. Refused transactions are listed with the reason.
+#[derive(Default)]
+pub(crate) struct JournalImportReport {
+    pub(crate) parties: usize,
+    pub(crate) entities: usize,
+    pub(crate) accounts: usize,
+    pub(crate) refused: Vec<String>,
+}
+
+impl Display for JournalImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Imported {} parties, created {} entities and {} accounts. Refused {} transactions.",
+            self.parties,
+            self.entities,
+            self.accounts,
+            self.refused.len()
+        )
+    }
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+/// Parses an amount such as "-45.30 EUR", "EUR -45.30", "€45.30" or "1,234.50 CHF".
+fn parse_amount(text: &str) -> Result<(f64, Currency), String> {
+    let commodity: String = text
+        .chars()
+        .filter(|c| !(c.is_ascii_digit() || " .,-+'\"".contains(*c)))
+        .collect();
+    let mut number: String = text
+        .chars()
+        .filter(|c| c.is_ascii_digit() || ".,-+".contains(*c))
+        .collect();
+
+    // whichever separator comes last is the decimal mark
+    match (number.rfind('.'), number.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => {
+            number = number.replace('.', "").replace(',', ".");
+        }
+        (None, Some(comma)) if number.len() - comma - 1 != 3 => {
+            number = number.replace(',', ".");
+        }
+        _ => {
+            number = number.replace(',', "");
+        }
+    }
+
+    let value: f64 = number
+        .parse::<f64>()
+        .map_err(|_e| format!("could not parse amount \"{}\"", text))?;
+    let currency: Currency = match commodity.as_str() {
+        "€" => Currency::EUR,
+        "Fr" | "Fr." | "SFr" => Currency::CHF,
+        "kr" => Currency::SEK,
+        "" => return Err(format!("amount \"{}\" has no commodity", text)),
+        commodity => Currency::from_str(commodity.to_uppercase().as_str())
+            .map_err(|_e| format!("unsupported commodity \"{}\"", commodity))?,
+    };
+
+    Ok((value, currency))
+}
+
+/// Work in progress while parsing a transaction block.
+struct PendingTransaction {
+    line_number: usize,
+    journal_transaction: JournalTransaction,
+    amountless_account: Option<String>,
+    error: Option<String>,
+}
+
+impl PendingTransaction {
+    /// Fills in the posting without amount, if any, and reports whether the transaction
+    /// could be parsed.
+    fn finish(mut self) -> Result<JournalTransaction, String> {
+        let context: String = format!(
+            "Line {} ({} {})",
+            self.line_number, self.journal_transaction.date, self.journal_transaction.payee
+        );
+        if let Some(error) = self.error {
+            return Err(format!("{}: {}", context, error));
+        }
+
+        if let Some(account) = self.amountless_account {
+            let mut totals: BTreeMap<String, (f64, Currency)> = BTreeMap::new();
+            for posting in self.journal_transaction.postings.iter() {
+                totals
+                    .entry(posting.currency.to_string())
+                    .or_insert((0.0, posting.currency.clone()))
+                    .0 += posting.value;
+            }
+            for (_, (total, currency)) in totals {
+                if total.abs() >= 0.005 {
+                    self.journal_transaction.postings.push(Posting {
+                        account: account.clone(),
+                        value: -total,
+                        currency,
+                    });
+                }
+            }
+        }
+
+        Ok(self.journal_transaction)
+    }
+}
+
+/// Parses a plain-text accounting journal, in hledger/ledger or beancount syntax. Every
+/// transaction is returned either parsed or with the reason why it could not be. Directives
+/// other than transactions (account declarations, prices, balance assertions...) are ignored.
+pub(crate) fn parse_journal(journal: &str) -> Vec<Result<JournalTransaction, String>> {
+    let posting_separator: Regex = Regex::new(r"\t|\s{2,}").unwrap();
+    let metadata: Regex = Regex::new(r"^[a-z][a-zA-Z0-9_-]*:\s").unwrap();
+    let quoted: Regex = Regex::new(r#""([^"]*)""#).unwrap();
+    let beancount_directives: [&str; 11] = [
+        "open",
+        "close",
+        "balance",
+        "pad",
+        "price",
+        "note",
+        "document",
+        "event",
+        "commodity",
+        "custom",
+        "query",
+    ];
+
+    let mut results: Vec<Result<JournalTransaction, String>> = Vec::new();
+    let mut pending: Option<PendingTransaction> = None;
+    for (i, line) in journal.lines().enumerate() {
+        let line_number: usize = i + 1;
+        let is_indented: bool = line.starts_with(' ') | line.starts_with('\t');
+        let trimmed: &str = line.trim();
+
+        if !is_indented | trimmed.is_empty() {
+            if let Some(pending_transaction) = pending.take() {
+                results.push(pending_transaction.finish());
+            }
+        }
+        if trimmed.is_empty() || trimmed.starts_with([';', '#', '*', '%', '|']) {
+            continue;
+        }
+
+        if !is_indented {
+            let mut tokens = trimmed.splitn(2, char::is_whitespace);
+            let date_token: &str = tokens.next().unwrap_or_default();
+            let Some(date) = parse_date(date_token.split('=').next().unwrap_or_default()) else {
+                continue; // directive, periodic or automated transaction
+            };
+
+            let mut rest: &str = tokens.next().unwrap_or_default().trim();
+            if beancount_directives
+                .iter()
+                .any(|directive| rest.split_whitespace().next() == Some(directive))
+            {
+                continue;
+            }
+            rest = rest
+                .trim_start_matches("txn")
+                .trim_start_matches(['*', '!'])
+                .trim_start();
+            if rest.starts_with('(') {
+                rest = rest.split_once(')').map_or("", |(_, rest)| rest).trim();
+            }
+
+            let strings: Vec<String> = quoted
+                .captures_iter(rest)
+                .map(|captures| captures[1].to_string())
+                .collect();
+            let (payee, narration): (String, String) = match strings.len() {
+                0 => {
+                    let description: &str = rest.split(';').next().unwrap_or_default();
+                    match description.split_once('|') {
+                        Some((payee, narration)) => {
+                            (payee.trim().to_string(), narration.trim().to_string())
+                        }
+                        None => (description.trim().to_string(), String::new()),
+                    }
+                }
+                1 => (String::new(), strings[0].clone()),
+                _ => (strings[0].clone(), strings[1].clone()),
+            };
+
+            pending = Some(PendingTransaction {
+                line_number,
+                journal_transaction: JournalTransaction {
+                    date,
+                    payee,
+                    narration,
+                    postings: Vec::new(),
+                },
+                amountless_account: None,
+                error: None,
+            });
+            continue;
+        }
+
+        let Some(pending_transaction) = pending.as_mut() else {
+            continue; // postings of ignored blocks
+        };
+        if metadata.is_match(trimmed) {
+            continue;
+        }
+
+        let posting: &str = trimmed.split(';').next().unwrap_or_default().trim();
+        let (account, amount): (&str, &str) = match posting_separator.split(posting).next() {
+            Some(account) if account.len() < posting.len() => {
+                (account, posting[account.len()..].trim())
+            }
+            _ => match posting.split_once(char::is_whitespace) {
+                Some((account, amount)) if parse_amount(amount.trim()).is_ok() => {
+                    (account, amount.trim())
+                }
+                _ => (posting, ""),
+            },
+        };
+
+        if account.starts_with('(') {
+            continue; // unbalanced virtual posting
+        }
+        let account: String = account.trim_matches(['[', ']']).to_string();
+        let amount: &str = amount.split('=').next().unwrap_or_default().trim();
+
+        if amount.contains('@') {
+            pending_transaction.error = Some(String::from(
+                "transactions with prices or costs cannot be balanced per currency",
+            ));
+        } else if amount.is_empty() {
+            if pending_transaction.amountless_account.is_some() {
+                pending_transaction.error =
+                    Some(String::from("more than one posting without amount"));
+            }
+            pending_transaction.amountless_account = Some(account);
+        } else {
+            match parse_amount(amount) {
+                Ok((value, currency)) => {
+                    pending_transaction
+                        .journal_transaction
+                        .postings
+                        .push(Posting {
+                            account,
+                            value,
+                            currency,
+                        })
+                }
+                Err(e) => pending_transaction.error = Some(e),
+            }
+        }
+    }
+    if let Some(pending_transaction) = pending.take() {
+        results.push(pending_transaction.finish());
+    }
+
+    results
+}
+
+/// Reads and parses a journal file.
+pub(crate) fn load_journal(path: &str) -> Result<Vec<Result<JournalTransaction, String>>, String> {
+    read_to_string(path)
+        .map(|journal| parse_journal(journal.as_str()))
+        .map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+impl DataBase {
+    /// Imports the transactions of a journal. Accounts and entities are reused when they
+    /// already exist with the same name (and currency), and created otherwise. Transactions
+    /// that only move funds from the opening balances become the initial balance of the
+    /// accounts they open. Any other transaction becomes a party, unless it cannot be balanced
+    /// per currency or touches an account that is not mapped, in which case it is refused.
+    pub(crate) fn import_journal(
+        &mut self,
+        journal_transactions: &[JournalTransaction],
+        mapping: &JournalMapping,
+    ) -> JournalImportReport {
+        let mut report: JournalImportReport = JournalImportReport::default();
+
+        let role = |account: &str| mapping.get(account).map(|mapping| mapping.role.clone());
+        let is_opening_balance = |journal_transaction: &JournalTransaction| {
+            journal_transaction.postings.iter().all(|posting| {
+                matches!(
+                    role(posting.account.as_str()),
+                    Some(JournalAccountRole::Account) | Some(JournalAccountRole::OpeningBalance)
+                )
+            }) & journal_transaction.postings.iter().any(|posting| {
+                role(posting.account.as_str()) == Some(JournalAccountRole::OpeningBalance)
+            })
+        };
+
+        // First the transactions are validated, so that refused transactions leave no trace.
+        // Account IDs are not known yet, any placeholder does for the validation.
+        let placeholder_ids: HashMap<(String, String), i64> = journal_transactions
+            .iter()
+            .flat_map(|journal_transaction| journal_transaction.postings.iter())
+            .filter(|posting| role(posting.account.as_str()) == Some(JournalAccountRole::Account))
+            .map(|posting| ((posting.account.clone(), posting.currency.to_string()), 0))
+            .collect();
+        let mut accepted_transactions: Vec<&JournalTransaction> = Vec::new();
+        for journal_transaction in journal_transactions {
+            if is_opening_balance(journal_transaction) {
+                accepted_transactions.push(journal_transaction);
+                continue;
+            }
+            if journal_transaction
+                .postings
+                .iter()
+                .all(|posting| posting.value.abs() < 0.005)
+            {
+                continue;
+            }
+
+            if let Err(error) = journal_party(journal_transaction, mapping, &placeholder_ids, 0) {
+                report.refused.push(format!(
+                    "{} {}: {}",
+                    journal_transaction.date, journal_transaction.payee, error
+                ));
+                continue;
+            }
+            accepted_transactions.push(journal_transaction);
+        }
+
+        // Then the accounts, with their initial balances and creation dates.
+        let mut new_accounts: BTreeMap<(String, String), (Currency, f64, NaiveDate)> =
+            BTreeMap::new();
+        for journal_transaction in accepted_transactions.iter() {
+            let is_opening: bool = is_opening_balance(journal_transaction);
+            for posting in journal_transaction.postings.iter() {
+                if role(posting.account.as_str()) != Some(JournalAccountRole::Account) {
+                    continue;
+                }
+                let initial_balance: f64 = if is_opening { posting.value } else { 0.0 };
+                new_accounts
+                    .entry((posting.account.clone(), posting.currency.to_string()))
+                    .and_modify(|(_, balance, date)| {
+                        *balance += initial_balance;
+                        *date = (*date).min(journal_transaction.date);
+                    })
+                    .or_insert((
+                        posting.currency.clone(),
+                        initial_balance,
+                        journal_transaction.date,
+                    ));
+            }
+        }
+
+        let mut existing_accounts: HashMap<(String, String), i64> = HashMap::new();
+        for account_id in self.iter_account_ids() {
+            let account: Account = self.account(account_id);
+            existing_accounts.insert((account.name(), account.currency().to_string()), account_id);
+        }
+
+        let mut account_ids: HashMap<(String, String), i64> = HashMap::new();
+        for ((journal_account, currency_name), (currency, initial_balance, creation_date)) in
+            new_accounts
+        {
+            let account_mapping: &JournalAccountMapping =
+                mapping.get(journal_account.as_str()).unwrap();
+            let components: Vec<&str> = journal_account.split(':').collect();
+            let name: String = if components.len() > 1 {
+                components[1..]
+                    .iter()
+                    .map(|component| free_text(component))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            } else {
+                free_text(components[0])
+            };
+
+            let account_id: i64 =
+                match existing_accounts.get(&(name.clone(), currency_name.clone())) {
+                    Some(account_id) => {
+                        if initial_balance.abs() >= 0.005 {
+                            report.refused.push(format!(
+                                "Opening balance of {} ignored, the account already exists",
+                                journal_account
+                            ));
+                        }
+                        *account_id
+                    }
+                    None => {
+                        report.accounts += 1;
+                        self.account_table.insert_account_created_at(
+                            &Account::new(
+                                name,
+                                mapping.country.clone(),
+                                currency,
+                                account_mapping.account_type.clone(),
+                                initial_balance,
+                            ),
+                            creation_date,
+                        )
+                    }
+                };
+            account_ids.insert((journal_account, currency_name), account_id);
+        }
+
+        // Finally the parties, creating the entities on the go. Transactions without payee
+        // are assigned to an entity of their own.
+        let mut entity_ids: HashMap<String, i64> = HashMap::new();
+        for entity_id in self.iter_entity_ids() {
+            entity_ids.insert(self.entity(entity_id).name(), entity_id);
+        }
+
+        for journal_transaction in accepted_transactions {
+            if is_opening_balance(journal_transaction) {
+                continue;
+            }
+
+            let payee: String = if journal_transaction.payee.is_empty() {
+                String::from(UNKNOWN_PAYEE)
+            } else {
+                journal_transaction.payee.clone()
+            };
+            let entity_id: i64 = if let Some(entity_id) = entity_ids.get(&payee) {
+                *entity_id
+            } else {
+                let entity_id: i64 = self.insert_entity(&Entity::new(
+                    payee.clone(),
+                    mapping.country.clone(),
+                    EntityType::default(),
+                    String::new(),
+                ));
+                entity_ids.insert(payee, entity_id);
+                report.entities += 1;
+                entity_id
+            };
+
+            let mut party: Party =
+                journal_party(journal_transaction, mapping, &account_ids, entity_id).unwrap();
+            self.insert_party(&mut party);
+            report.parties += 1;
+        }
+
+        report
+    }
+}
+
+/// Translates a journal transaction into a party, or explains why it cannot be.
+fn journal_party(
+    journal_transaction: &JournalTransaction,
+    mapping: &JournalMapping,
+    account_ids: &HashMap<(String, String), i64>,
+    entity_id: i64,
+) -> Result<Party, String> {
+    let mut party: Party = Party::default();
+    let mut error: Option<String> = None;
+    for posting in journal_transaction.postings.iter() {
+        let Some(account_mapping) = mapping.get(posting.account.as_str()) else {
+            error = Some(format!("{} is not mapped", posting.account));
+            break;
+        };
+
+        let transaction: Transaction = match account_mapping.role {
+            JournalAccountRole::Account => {
+                let account_id: i64 = *account_ids
+                    .get(&(posting.account.clone(), posting.currency.to_string()))
+                    .unwrap();
+                if posting.value >= 0.0 {
+                    Transaction::Credit {
+                        value: posting.value,
+                        currency: posting.currency.clone(),
+                        date: journal_transaction.date,
+                        account_id,
+                    }
+                } else {
+                    Transaction::Debit {
+                        value: -posting.value,
+                        currency: posting.currency.clone(),
+                        date: journal_transaction.date,
+                        account_id,
+                    }
+                }
+            }
+            JournalAccountRole::Income => Transaction::Income {
+                value: -posting.value,
+                currency: posting.currency.clone(),
+                date: journal_transaction.date,
+                category: account_mapping.category.clone(),
+                subcategory: account_mapping.subcategory.clone(),
+                description: journal_transaction.narration.clone(),
+                entity_id,
+            },
+            JournalAccountRole::Expense => Transaction::Expense {
+                value: posting.value,
+                currency: posting.currency.clone(),
+                date: journal_transaction.date,
+                category: account_mapping.category.clone(),
+                subcategory: account_mapping.subcategory.clone(),
+                description: journal_transaction.narration.clone(),
+                entity_id,
+                quantity: None,
+            },
+            JournalAccountRole::OpeningBalance => {
+                error = Some(format!(
+                    "{} can only be used to open accounts",
+                    posting.account
+                ));
+                break;
+            }
+        };
+        party.add_transaction(transaction);
+    }
+
+    if error.is_none() & !party.is_valid() {
+        error = Some(String::from("not balanced per currency"));
+    }
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(party),
+    }
+}
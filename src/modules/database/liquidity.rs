@@ -0,0 +1,161 @@
+use crate::modules::database::independence::{converted_total, covered_months};
+use crate::modules::database::summaries::{FundGrouping, Valuation};
+use crate::modules::database::{data_frame_to_csv_string, DataBase};
+use crate::modules::financial::{AccountType, Currency};
+use chrono::{Duration, Months, NaiveDate};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Months of history the average expenses of the runway warning are taken from.
+pub(crate) const RUNWAY_WINDOW_MONTHS: u32 = 12;
+
+/// Liquid funds at a date, those in deposit and cash accounts, and the average monthly expenses
+/// they have to cover.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Runway {
+    pub(crate) date: NaiveDate,
+    pub(crate) liquid_funds: f64,
+    pub(crate) monthly_expenses: f64,
+}
+
+impl Runway {
+    /// Months of average expenses covered by the liquid funds, or None without expenses.
+    pub(crate) fn months(&self) -> Option<f64> {
+        match self.monthly_expenses > 0.0 {
+            true => Some(self.liquid_funds / self.monthly_expenses),
+            false => None,
+        }
+    }
+}
+
+impl DataBase {
+    /// Value of the deposit and cash accounts at the end of date, overdrawn ones included, in
+    /// currency_to at the exchange rate of date. Investment accounts are left out.
+    pub(crate) fn liquid_funds_at(&self, date: NaiveDate, currency_to: &Currency) -> f64 {
+        let fund_stand: DataFrame = self.fund_stand_at_data_frame(
+            date,
+            Some(currency_to),
+            &Valuation::Historical,
+            &FundGrouping::AccountType,
+            None,
+        )
+        .unwrap();
+
+        fund_stand
+            .column("Account Type")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .zip(
+                fund_stand
+                    .column(currency_to.to_string().as_str())
+                    .unwrap()
+                    .f64()
+                    .unwrap(),
+            )
+            .filter(|(account_type, _value)| {
+                AccountType::from_str(account_type.unwrap()).expect("Failed to find account type")
+                    != AccountType::Investment
+            })
+            .map(|(_account_type, value)| value.unwrap())
+            .sum()
+    }
+
+    /// Runway at the end of date, with the expenses averaged over the window_months before it,
+    /// or over the months since the first expense if fewer.
+    pub(crate) fn runway_at(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        window_months: u32,
+    ) -> Runway {
+        let window_months: u32 =
+            covered_months(&[&self.expenses_table.data_frame], date, window_months);
+        let date_from: NaiveDate = date - Months::new(window_months) + Duration::days(1);
+        let expenses: f64 = converted_total(
+            &self.expenses_table.data_frame,
+            date_from,
+            date,
+            currency_to,
+        );
+
+        Runway {
+            date,
+            liquid_funds: self.liquid_funds_at(date, currency_to),
+            monthly_expenses: expenses / window_months as f64,
+        }
+    }
+
+    /// Runway every month, on the same day of the month as date, from the month of the first
+    /// expense until date.
+    pub(crate) fn runway_history(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        window_months: u32,
+    ) -> Vec<Runway> {
+        let first_date: NaiveDate = self
+            .expenses_table
+            .data_frame
+            .column("date")
+            .unwrap()
+            .date()
+            .unwrap()
+            .as_date_iter()
+            .flatten()
+            .min()
+            .unwrap_or(date)
+            .min(date);
+
+        let mut months: u32 = 0;
+        while date - Months::new(months + 1) >= first_date {
+            months += 1;
+        }
+
+        (0..=months)
+            .rev()
+            .map(|month| self.runway_at(date - Months::new(month), currency_to, window_months))
+            .collect()
+    }
+
+    /// Returns a csv in String format with the runway history.
+    pub(crate) fn runway_report(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        window_months: u32,
+    ) -> String {
+        data_frame_to_csv_string(&mut self.runway_report_data_frame(
+            date,
+            currency_to,
+            window_months,
+        ))
+    }
+
+    /// Same as runway_history, but as a DataFrame.
+    pub(crate) fn runway_report_data_frame(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        window_months: u32,
+    ) -> DataFrame {
+        let history: Vec<Runway> = self.runway_history(date, currency_to, window_months);
+
+        df!(
+            "Date" => history.iter().map(|runway| runway.date).collect::<Vec<NaiveDate>>(),
+            "Liquid Funds" => history.iter().map(|runway| runway.liquid_funds).collect::<Vec<f64>>(),
+            "Monthly Expenses" => history.iter().map(|runway| runway.monthly_expenses).collect::<Vec<f64>>(),
+            "Runway (Months)" => history.iter().map(|runway| runway.months()).collect::<Vec<Option<f64>>>()
+        )
+        .expect("Failed to report runway")
+        .lazy()
+        .with_columns([
+            col("Liquid Funds").round(2),
+            col("Monthly Expenses").round(2),
+            col("Runway (Months)").round(1),
+        ])
+        .collect()
+        .unwrap()
+    }
+}
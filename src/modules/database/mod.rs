@@ -1,8 +1,24 @@
+pub mod anomalies;
+pub mod attachments;
+pub mod budgets;
+pub mod forecast;
+pub mod goals;
+pub mod independence;
+pub mod journal;
+pub mod liquidity;
 mod palettes;
 pub mod plotter;
+pub mod recurring;
+pub mod settings;
+pub mod snapshot;
+pub mod spreadsheet;
+pub mod subscriptions;
+pub mod suggestions;
 pub mod summaries;
+pub mod unit_prices;
 pub mod views;
 
+use crate::modules::database::settings::Settings;
 use crate::modules::financial::*;
 use crate::modules::tables::*;
 use polars::prelude::*;
@@ -47,6 +63,14 @@ pub struct DataBase {
     party_table: PartyTable,
     entity_table: EntityTable,
     account_table: AccountTable,
+    attachment_table: AttachmentTable,
+    budget_table: BudgetTable,
+    goal_table: GoalTable,
+    allocation_table: AllocationTable,
+    template_table: TemplateTable,
+    template_transaction_table: TemplateTransactionTable,
+    dismissal_table: DismissalTable,
+    settings: Settings,
 }
 
 impl DataBase {
@@ -57,6 +81,14 @@ impl DataBase {
         let party_table = *PartyTable::new();
         let entity_table = *EntityTable::new();
         let account_table = *AccountTable::new();
+        let attachment_table = *AttachmentTable::new();
+        let budget_table = *BudgetTable::new();
+        let goal_table = *GoalTable::new();
+        let allocation_table = *AllocationTable::new();
+        let template_table = *TemplateTable::new();
+        let template_transaction_table = *TemplateTransactionTable::new();
+        let dismissal_table = *DismissalTable::new();
+        let settings = Settings::default();
 
         DataBase {
             incomes_table,
@@ -65,6 +97,14 @@ impl DataBase {
             party_table,
             entity_table,
             account_table,
+            attachment_table,
+            budget_table,
+            goal_table,
+            allocation_table,
+            template_table,
+            template_transaction_table,
+            dismissal_table,
+            settings,
         }
     }
 
@@ -75,15 +115,35 @@ impl DataBase {
         let party_table = *PartyTable::init();
         let entity_table = *EntityTable::init();
         let account_table = *AccountTable::init();
-
-        DataBase {
+        let attachment_table = *AttachmentTable::init();
+        let budget_table = *BudgetTable::init();
+        let goal_table = *GoalTable::init();
+        let allocation_table = *AllocationTable::init();
+        let template_table = *TemplateTable::init();
+        let template_transaction_table = *TemplateTransactionTable::init();
+        let dismissal_table = *DismissalTable::init();
+        let settings = Settings::init();
+
+        let data_base: DataBase = DataBase {
             incomes_table,
             expenses_table,
             funds_table,
             party_table,
             entity_table,
             account_table,
-        }
+            attachment_table,
+            budget_table,
+            goal_table,
+            allocation_table,
+            template_table,
+            template_transaction_table,
+            dismissal_table,
+            settings,
+        };
+        // Files of the parties deleted during the last session are only removed now
+        data_base.remove_orphan_attachments();
+
+        data_base
     }
 
     pub fn save(&mut self) -> () {
@@ -93,15 +153,24 @@ impl DataBase {
         self.party_table.save();
         self.entity_table.save();
         self.account_table.save();
+        self.attachment_table.save();
+        self.budget_table.save();
+        self.goal_table.save();
+        self.allocation_table.save();
+        self.template_table.save();
+        self.template_transaction_table.save();
+        self.dismissal_table.save();
     }
 
-    pub fn insert_party(&mut self, party: &mut Party) -> () {
+    pub fn insert_party(&mut self, party: &mut Party) -> i64 {
         let party_id: i64 = self.party_table.next_id();
         for transaction in party.iter() {
             self.insert_transaction(&transaction, party_id);
         }
 
         self.party_table.insert_party(party);
+
+        party_id
     }
 
     fn insert_transaction(&mut self, transaction: &Transaction, party_id: i64) -> () {
@@ -156,6 +225,10 @@ impl DataBase {
         self.account_table.iter()
     }
 
+    pub(crate) fn iter_party_ids(&self) -> IntoIter<i64> {
+        self.party_table.iter()
+    }
+
     pub(crate) fn entity_countries(&self) -> Vec<String> {
         self.entity_table.countries()
     }
@@ -188,16 +261,22 @@ impl DataBase {
         }
     }
 
+    pub(crate) fn units(&self) -> Vec<String> {
+        self.expenses_table.units()
+    }
+
     pub(crate) fn entity_subtypes(&self) -> Vec<String> {
         self.entity_table.subtypes()
     }
 
-    /// Deletes from the database all records from the party.
+    /// Deletes from the database all records from the party. The attached files stay on disk
+    /// until the next start, so that an edited party can still get them back.
     pub(crate) fn delete_party(&mut self, party_id: i64) -> () {
         self.incomes_table.delete_party(party_id);
         self.expenses_table.delete_party(party_id);
         self.funds_table.delete_party(party_id);
         self.party_table.delete_party(party_id);
+        self.attachment_table.delete_party(party_id);
     }
 
     pub(crate) fn party(&self, party_id: i64) -> Party {
@@ -1,13 +1,17 @@
 use crate::modules::currency_exchange::CurrencyExchange;
+use crate::modules::database::independence::{IndependencePlan, IndependenceProjection};
 use crate::modules::database::palettes::fetch_palette;
+use crate::modules::database::summaries::{FundGrouping, TimeUnit};
 use crate::modules::database::DataBase;
 use crate::modules::financial::Currency;
-use chrono::{Months, NaiveDate};
+use chrono::{Local, Months, NaiveDate};
 use plotters::prelude::*;
 use polars::prelude::*;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::fs::{create_dir, File};
 use std::path::Path;
+use std::str::FromStr;
 use strum_macros::EnumIter;
 
 enum Extrema {
@@ -109,8 +113,10 @@ fn extreme_value(data_frame: &DataFrame, extrema: Extrema) -> f64 {
 
 impl DataBase {
     // Writes a funds evolution plot (and optionally a csv too), with x-axis
-    // date, and y-axis total funds.
-    pub(crate) fn funds_evolution(&self, currency_to: &Currency) -> () {
+    // date, and y-axis total funds. If forecast_months is not zero, the plot
+    // continues with the forecast of the total funds for that many months,
+    // converted at today's exchange rates, and its uncertainty band.
+    pub(crate) fn funds_evolution(&self, currency_to: &Currency, forecast_months: u32) -> () {
         let currency_exchange: CurrencyExchange = CurrencyExchange::init();
 
         // Fetch the ammounts in the different accounts in the date
@@ -153,12 +159,7 @@ impl DataBase {
             .group_by_dynamic(
                 col("date"),
                 [],
-                DynamicGroupOptions {
-                    every: Duration::parse("1d"),
-                    period: Duration::parse("1d"),
-                    offset: Duration::parse("0"),
-                    ..Default::default()
-                },
+                self.settings.periods.group_options(&TimeUnit::Day),
             )
             .agg([col("value").sum()])
             .collect()
@@ -211,7 +212,56 @@ impl DataBase {
             .into_no_null_iter()
             .collect();
 
-        let bankrupcy_values: Vec<f64> = vec![0.0; values.len()];
+        // Expected total funds with their low and high bounds, by day.
+        let today: NaiveDate = Local::now().date_naive();
+        let mut forecast: BTreeMap<NaiveDate, (f64, f64, f64)> = BTreeMap::new();
+        if forecast_months > 0 {
+            let account_currencies: DataFrame = self.account_table.data_frame.clone();
+            let exchange_rates: BTreeMap<i64, f64> = (0..account_currencies.height())
+                .map(|i| {
+                    let currency_from: Currency = Currency::from_str(
+                        account_currencies
+                            .column("currency")
+                            .unwrap()
+                            .str()
+                            .unwrap()
+                            .get(i)
+                            .unwrap(),
+                    )
+                    .expect("Failed to find currency");
+                    (
+                        account_currencies
+                            .column("account_id")
+                            .unwrap()
+                            .i64()
+                            .unwrap()
+                            .get(i)
+                            .unwrap(),
+                        currency_exchange.exchange_currency(&currency_from, currency_to, today),
+                    )
+                })
+                .collect();
+            for point in self.forecast(today, forecast_months) {
+                let exchange_rate: f64 = exchange_rates[&point.account_id];
+                let total = forecast.entry(point.date).or_insert((0.0, 0.0, 0.0));
+                total.0 += point.expected * exchange_rate;
+                total.1 += point.low * exchange_rate;
+                total.2 += point.high * exchange_rate;
+            }
+        }
+
+        let last_date: NaiveDate = match forecast.keys().next_back() {
+            Some(forecast_date) => *forecast_date,
+            None => dates[dates.len() - 1],
+        };
+        let min_value: f64 = forecast
+            .values()
+            .map(|(_, low, _)| *low)
+            .fold(0.0, f64::min);
+        let max_value: f64 = forecast
+            .values()
+            .map(|(_, _, high)| *high)
+            .fold(values.iter().cloned().fold(f64::NAN, f64::max), f64::max);
 
         // Then create the plot
         let root = SVGBackend::new("figures/funds_evolution.svg", (800, 640)).into_drawing_area();
@@ -221,10 +271,7 @@ impl DataBase {
             .caption("Evolution of Total Funds", ("sans-serif", 20).into_font())
             .set_label_area_size(LabelAreaPosition::Left, 60)
             .set_label_area_size(LabelAreaPosition::Bottom, 60)
-            .build_cartesian_2d(
-                dates[0]..dates[dates.len() - 1],
-                0.0..values.iter().cloned().fold(0. / 0., f64::max),
-            )
+            .build_cartesian_2d(dates[0]..last_date, min_value..max_value)
             .expect("Failed to build chart");
 
         chart
@@ -244,24 +291,43 @@ impl DataBase {
             ))
             .expect("Failed to draw line")
             .label("Total Funds")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLACK));
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK));
+
+        if !forecast.is_empty() {
+            // The band goes along the high bounds and back along the low ones.
+            let band: Vec<(NaiveDate, f64)> = forecast
+                .iter()
+                .map(|(d, (_, _, high))| (*d, *high))
+                .chain(forecast.iter().rev().map(|(d, (_, low, _))| (*d, *low)))
+                .collect();
+            chart
+                .draw_series(std::iter::once(Polygon::new(band, BLUE.mix(0.2).filled())))
+                .expect("Failed to draw band")
+                .label("Forecast Uncertainty")
+                .legend(|(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 20, y + 5)], BLUE.mix(0.2).filled())
+                });
+
+            chart
+                .draw_series(LineSeries::new(
+                    forecast.iter().map(|(d, (expected, _, _))| (*d, *expected)),
+                    &BLUE,
+                ))
+                .expect("Failed to draw line")
+                .label("Forecast")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+        }
 
         chart
-            .draw_series(LineSeries::new(
-                dates
-                    .iter()
-                    .zip(bankrupcy_values.iter())
-                    .map(|(d, v)| (*d, *v)),
-                &RED,
-            ))
+            .draw_series(LineSeries::new([(dates[0], 0.0), (last_date, 0.0)], &RED))
             .expect("Failed to draw line")
             .label("Bankrupcy")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
         chart
             .configure_series_labels()
-            .border_style(&BLACK)
-            .background_style(&WHITE.mix(0.8))
+            .border_style(BLACK)
+            .background_style(WHITE.mix(0.8))
             .draw()
             .unwrap();
 
@@ -289,12 +355,7 @@ impl DataBase {
             .group_by_dynamic(
                 col("date"),
                 [col("category")],
-                DynamicGroupOptions {
-                    every: Duration::parse("1mo"),
-                    period: Duration::parse("1mo"),
-                    offset: Duration::parse("0"),
-                    ..Default::default()
-                },
+                self.settings.periods.group_options(&TimeUnit::Month),
             )
             .agg([col("value").sum()])
             .collect()
@@ -454,9 +515,432 @@ impl DataBase {
         // Finally, create the legend and export.
         chart
             .configure_series_labels()
-            .border_style(&BLACK)
-            .background_style(&WHITE.mix(0.8))
+            .border_style(BLACK)
+            .background_style(WHITE.mix(0.8))
+            .draw()
+            .unwrap();
+    }
+
+    // Writes a line plot of the cash flow table into path: income, expenses and net savings per
+    // period on the left axis, and the savings rate on the right one.
+    pub(crate) fn cash_flow_plot(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+        path: &str,
+    ) -> () {
+        let result: DataFrame = self.cash_flow_data_frame(currency_to, time_unit);
+
+        // Extract data as vectors.
+        let dates: Vec<NaiveDate> = result
+            .column(time_unit.to_string().as_str())
+            .expect("Could not find date column")
+            .date()
+            .expect("Could not convert date column to date")
+            .as_date_iter()
+            .map(|opt_date| opt_date.expect("Found null value in date column"))
+            .collect::<Vec<NaiveDate>>();
+        if dates.is_empty() {
+            return;
+        }
+
+        let series = |name: &str| -> Vec<Option<f64>> {
+            result
+                .column(name)
+                .expect("Could not find column")
+                .f64()
+                .expect("Could not convert column to f64")
+                .into_iter()
+                .collect()
+        };
+        let lines: Vec<(&str, Vec<Option<f64>>, RGBColor)> = vec![
+            ("Income", series("Income"), GREEN),
+            ("Expenses", series("Expenses"), RED),
+            ("Net Savings", series("Net Savings"), BLACK),
+        ];
+        let savings_rates: Vec<Option<f64>> = series("% Savings Rate");
+
+        let values = lines
+            .iter()
+            .flat_map(|(_, values, _)| values.iter().flatten());
+        let min_value: f64 = values.clone().cloned().fold(0.0, f64::min);
+        let max_value: f64 = values.cloned().fold(0.0, f64::max);
+        let min_rate: f64 = savings_rates.iter().flatten().cloned().fold(0.0, f64::min);
+        let max_rate: f64 = savings_rates
+            .iter()
+            .flatten()
+            .cloned()
+            .fold(100.0, f64::max);
+
+        // A single period still needs some width to be plotted.
+        let last_date: NaiveDate = match time_unit {
+            TimeUnit::Day => dates[dates.len() - 1] + chrono::Duration::days(1),
+            TimeUnit::Week => dates[dates.len() - 1] + chrono::Duration::weeks(1),
+            TimeUnit::Month => dates[dates.len() - 1]
+                .checked_add_months(Months::new(1))
+                .unwrap(),
+            TimeUnit::Year => dates[dates.len() - 1]
+                .checked_add_months(Months::new(12))
+                .unwrap(),
+        };
+
+        // Then create the plot
+        let root = SVGBackend::new(path, (800, 640)).into_drawing_area();
+        root.fill(&WHITE).expect("Failed to fill plotting root");
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("Cash Flow and Savings Rate by {}", time_unit),
+                ("sans-serif", 20).into_font(),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 60)
+            .set_label_area_size(LabelAreaPosition::Right, 60)
+            .set_label_area_size(LabelAreaPosition::Bottom, 60)
+            .build_cartesian_2d(
+                dates[0]..last_date,
+                (min_value * 1.05)..(max_value * 1.05 + 0.001),
+            )
+            .expect("Failed to build chart")
+            .set_secondary_coord(dates[0]..last_date, (min_rate * 1.05)..(max_rate * 1.05));
+
+        chart
+            .configure_mesh()
+            .x_desc("Time")
+            .x_label_style(("sans-serif", 15).into_font())
+            .y_desc(currency_to.to_string().as_str())
+            .y_label_formatter(&|y| format!("{:.0}", *y))
+            .y_label_style(("sans-serif", 15).into_font())
+            .draw()
+            .expect("Failed to draw");
+
+        chart
+            .configure_secondary_axes()
+            .y_desc("Savings Rate")
+            .y_label_formatter(&|y| format!("{:.0}%", *y))
+            .label_style(("sans-serif", 15).into_font())
+            .draw()
+            .expect("Failed to draw secondary axis");
+
+        for (label, values, colour) in lines.iter() {
+            chart
+                .draw_series(LineSeries::new(
+                    dates
+                        .iter()
+                        .zip(values.iter())
+                        .map(|(d, v)| (*d, v.unwrap_or(0.0))),
+                    colour,
+                ))
+                .expect("Failed to draw line")
+                .label(*label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], colour));
+        }
+
+        chart
+            .draw_secondary_series(PointSeries::of_element(
+                dates
+                    .iter()
+                    .zip(savings_rates.iter())
+                    .filter_map(|(d, v)| v.map(|v| (*d, v))),
+                4,
+                &BLUE,
+                &|coord, size, style| Circle::new(coord, size, style.filled()),
+            ))
+            .expect("Failed to draw points")
+            .label("Savings Rate")
+            .legend(|(x, y)| Circle::new((x + 10, y), 4, BLUE.filled()));
+
+        chart
+            .configure_series_labels()
+            .border_style(BLACK)
+            .background_style(WHITE.mix(0.8))
+            .draw()
+            .unwrap();
+
+        // Finally save the plot
+        root.present().expect("Failed to present plot");
+    }
+
+    // Writes a stacked area plot of the balance history, one layer per account or group of
+    // accounts, so that the top of the stack is the total of the selected accounts.
+    pub(crate) fn balance_history_plot(
+        &self,
+        currency_to: &Currency,
+        grouping: &FundGrouping,
+        account_ids: &[i64],
+    ) -> () {
+        let result: DataFrame =
+            self.balance_history_data_frame(Some(currency_to), grouping, account_ids);
+        if result.height() == 0 {
+            return;
+        }
+
+        // Extract data as vectors.
+        let dates: Vec<NaiveDate> = result
+            .column("Date")
+            .expect("Could not find date column")
+            .date()
+            .expect("Could not convert date column to date")
+            .as_date_iter()
+            .map(|opt_date| opt_date.expect("Found null value in date column"))
+            .collect::<Vec<NaiveDate>>();
+
+        // Each layer is drawn on top of the previous ones.
+        let mut layers: Vec<(String, Vec<f64>)> = vec![];
+        let mut stacked_values: Vec<f64> = vec![0.0; dates.len()];
+        for column in result.get_columns().iter().skip(1) {
+            let values: Vec<f64> = column
+                .f64()
+                .expect("Could not convert column to f64")
+                .into_iter()
+                .map(|value| value.unwrap_or(0.0))
+                .collect();
+            for (stacked_value, value) in stacked_values.iter_mut().zip(values.iter()) {
+                *stacked_value += value;
+            }
+            layers.push((column.name().to_string(), stacked_values.clone()));
+        }
+
+        let min_value: f64 = layers
+            .iter()
+            .flat_map(|(_, values)| values.iter())
+            .cloned()
+            .fold(0.0, f64::min);
+        let max_value: f64 = layers
+            .iter()
+            .flat_map(|(_, values)| values.iter())
+            .cloned()
+            .fold(0.0, f64::max);
+
+        // Then create the plot
+        let root = SVGBackend::new("figures/balance_history.svg", (800, 640)).into_drawing_area();
+        root.fill(&WHITE).expect("Failed to fill plotting root");
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("Balance History by {}", grouping),
+                ("sans-serif", 20).into_font(),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 60)
+            .set_label_area_size(LabelAreaPosition::Bottom, 60)
+            .build_cartesian_2d(
+                dates[0]..(dates[dates.len() - 1] + chrono::Duration::days(1)),
+                (min_value * 1.05)..(max_value * 1.05 + 0.001),
+            )
+            .expect("Failed to build chart");
+
+        chart
+            .configure_mesh()
+            .x_desc("Time")
+            .x_label_style(("sans-serif", 15).into_font())
+            .y_desc(currency_to.to_string().as_str())
+            .y_label_formatter(&|y| format!("{:.0}", *y))
+            .y_label_style(("sans-serif", 15).into_font())
+            .draw()
+            .expect("Failed to draw");
+
+        // The highest layer is drawn first, so that the lower ones cover it.
+        let palette: Vec<RGBAColor> = fetch_palette(layers.len());
+        for (index, (label, values)) in layers.iter().enumerate().rev() {
+            let colour = palette[index % palette.len()];
+            chart
+                .draw_series(AreaSeries::new(
+                    dates.iter().zip(values.iter()).map(|(d, v)| (*d, *v)),
+                    0.0,
+                    colour.filled(),
+                ))
+                .expect("Failed to draw area")
+                .label(label.as_str())
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 20, y + 5)], colour.filled())
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .border_style(BLACK)
+            .background_style(WHITE.mix(0.8))
             .draw()
             .unwrap();
+
+        // Finally save the plot
+        root.present().expect("Failed to present plot");
+    }
+
+    // Writes a line plot of the projected net worth until financial independence, with the
+    // target net worth, and the what-if scenario of the plan if it has any change.
+    pub(crate) fn independence_plot(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        plan: &IndependencePlan,
+        years: u32,
+    ) -> () {
+        let mut projections: Vec<(&str, IndependenceProjection, RGBColor)> = vec![(
+            "Baseline",
+            self.independence_projection(date, currency_to, &plan.baseline(), years),
+            BLACK,
+        )];
+        if plan.is_what_if() {
+            projections.push((
+                "What If",
+                self.independence_projection(date, currency_to, plan, years),
+                BLUE,
+            ));
+        }
+        let target: f64 = projections[0].1.target;
+        let first_date: NaiveDate = date;
+        let last_date: NaiveDate = projections[0].1.trajectory.last().unwrap().0 + Months::new(1);
+
+        let values = projections
+            .iter()
+            .flat_map(|(_, projection, _)| projection.trajectory.iter().map(|(_, v)| *v));
+        let min_value: f64 = values.clone().fold(0.0, f64::min);
+        let max_value: f64 = values.fold(target, f64::max);
+
+        // Then create the plot
+        let root = SVGBackend::new("figures/independence.svg", (800, 640)).into_drawing_area();
+        root.fill(&WHITE).expect("Failed to fill plotting root");
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                "Net Worth until Financial Independence",
+                ("sans-serif", 20).into_font(),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 80)
+            .set_label_area_size(LabelAreaPosition::Bottom, 60)
+            .build_cartesian_2d(
+                first_date..last_date,
+                (min_value * 1.05)..(max_value * 1.05 + 0.001),
+            )
+            .expect("Failed to build chart");
+
+        chart
+            .configure_mesh()
+            .x_desc("Time")
+            .x_label_style(("sans-serif", 15).into_font())
+            .y_desc(currency_to.to_string().as_str())
+            .y_label_formatter(&|y| format!("{:.0}", *y))
+            .y_label_style(("sans-serif", 15).into_font())
+            .draw()
+            .expect("Failed to draw");
+
+        for (label, projection, colour) in projections.iter() {
+            chart
+                .draw_series(LineSeries::new(
+                    projection.trajectory.iter().map(|(d, v)| (*d, *v)),
+                    colour,
+                ))
+                .expect("Failed to draw line")
+                .label(*label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], colour));
+
+            // Mark the month independence is reached.
+            if let Some(months) = projection.months_to_independence {
+                chart
+                    .draw_series(PointSeries::of_element(
+                        std::iter::once(projection.trajectory[months as usize]),
+                        5,
+                        colour,
+                        &|coord, size, style| Circle::new(coord, size, style.filled()),
+                    ))
+                    .expect("Failed to draw point");
+            }
+        }
+
+        chart
+            .draw_series(LineSeries::new(
+                [(first_date, target), (last_date, target)],
+                &GREEN,
+            ))
+            .expect("Failed to draw line")
+            .label("Independence Target")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], GREEN));
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .border_style(BLACK)
+            .background_style(WHITE.mix(0.8))
+            .draw()
+            .unwrap();
+
+        // Finally save the plot
+        root.present().expect("Failed to present plot");
+    }
+
+    // Writes a line plot of the months of expenses covered by the liquid funds, with the minimum
+    // runway of the settings.
+    pub(crate) fn runway_plot(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        window_months: u32,
+    ) -> () {
+        let points: Vec<(NaiveDate, f64)> = self
+            .runway_history(date, currency_to, window_months)
+            .into_iter()
+            .filter_map(|runway| runway.months().map(|months| (runway.date, months)))
+            .collect();
+        let min_runway: f64 = self.settings.min_runway_months as f64;
+        let first_date: NaiveDate = points.first().map_or(date, |(d, _)| *d) - Months::new(1);
+        let last_date: NaiveDate = date + Months::new(1);
+        let min_value: f64 = points.iter().map(|(_, v)| *v).fold(0.0, f64::min);
+        let max_value: f64 = points.iter().map(|(_, v)| *v).fold(min_runway, f64::max);
+
+        // Then create the plot
+        let root = SVGBackend::new("figures/runway.svg", (800, 640)).into_drawing_area();
+        root.fill(&WHITE).expect("Failed to fill plotting root");
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                "Months of Expenses Covered by Liquid Funds",
+                ("sans-serif", 20).into_font(),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 80)
+            .set_label_area_size(LabelAreaPosition::Bottom, 60)
+            .build_cartesian_2d(
+                first_date..last_date,
+                (min_value * 1.05)..(max_value * 1.05 + 0.001),
+            )
+            .expect("Failed to build chart");
+
+        chart
+            .configure_mesh()
+            .x_desc("Time")
+            .x_label_style(("sans-serif", 15).into_font())
+            .y_desc("Months")
+            .y_label_formatter(&|y| format!("{:.1}", *y))
+            .y_label_style(("sans-serif", 15).into_font())
+            .draw()
+            .expect("Failed to draw");
+
+        chart
+            .draw_series(LineSeries::new(points.iter().copied(), &BLACK))
+            .expect("Failed to draw line")
+            .label("Runway")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK));
+
+        if min_runway > 0.0 {
+            chart
+                .draw_series(LineSeries::new(
+                    [(first_date, min_runway), (last_date, min_runway)],
+                    &RED,
+                ))
+                .expect("Failed to draw line")
+                .label("Minimum Runway")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .border_style(BLACK)
+            .background_style(WHITE.mix(0.8))
+            .draw()
+            .unwrap();
+
+        // Finally save the plot
+        root.present().expect("Failed to present plot");
     }
 }
@@ -0,0 +1,329 @@
+use crate::modules::database::DataBase;
+use crate::modules::financial::{Party, Transaction};
+use chrono::{Datelike, Months, NaiveDate};
+use polars::prelude::*;
+use std::fmt::Display;
+
+/// When the occurrences of a recurring party are due.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Recurrence {
+    /// Every month on the day, or on the last day of shorter months
+    Monthly { day: u32 },
+    /// Every year on the day of the month, or on the last day of shorter months
+    Yearly { month: u32, day: u32 },
+    /// Every number of weeks from the start date
+    Weekly { weeks: u32 },
+}
+
+/// The day of the month of the date, or the last day of that month if it is shorter.
+fn day_of_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    (0..4)
+        .find_map(|days_less| NaiveDate::from_ymd_opt(year, month, day - days_less))
+        .expect("Failed to find day of month")
+}
+
+impl Recurrence {
+    /// Compact representation of the rule, as stored in the template table.
+    pub(crate) fn rule(&self) -> String {
+        match self {
+            Recurrence::Monthly { day } => format!("monthly:{}", day),
+            Recurrence::Yearly { month, day } => format!("yearly:{}-{}", month, day),
+            Recurrence::Weekly { weeks } => format!("weekly:{}", weeks),
+        }
+    }
+
+    pub(crate) fn from_rule(rule: &str) -> Result<Recurrence, String> {
+        let error = || format!("Invalid recurrence rule: {}", rule);
+        let number = |number: &str, max: u32| -> Result<u32, String> {
+            number
+                .parse::<u32>()
+                .ok()
+                .filter(|number| (1..=max).contains(number))
+                .ok_or_else(error)
+        };
+
+        match rule.split_once(":").ok_or_else(error)? {
+            ("monthly", day) => Ok(Recurrence::Monthly {
+                day: number(day, 31)?,
+            }),
+            ("yearly", month_day) => {
+                let (month, day) = month_day.split_once("-").ok_or_else(error)?;
+                Ok(Recurrence::Yearly {
+                    month: number(month, 12)?,
+                    day: number(day, 31)?,
+                })
+            }
+            ("weekly", weeks) => Ok(Recurrence::Weekly {
+                weeks: number(weeks, 52)?,
+            }),
+            _ => Err(error()),
+        }
+    }
+
+    /// First occurrence on or after the start date.
+    pub(crate) fn first(&self, start_date: NaiveDate) -> NaiveDate {
+        match self {
+            Recurrence::Monthly { day } => {
+                let occurrence: NaiveDate =
+                    day_of_month(start_date.year(), start_date.month(), *day);
+                if occurrence < start_date {
+                    self.following(occurrence)
+                } else {
+                    occurrence
+                }
+            }
+            Recurrence::Yearly { month, day } => {
+                let occurrence: NaiveDate = day_of_month(start_date.year(), *month, *day);
+                if occurrence < start_date {
+                    self.following(occurrence)
+                } else {
+                    occurrence
+                }
+            }
+            Recurrence::Weekly { .. } => start_date,
+        }
+    }
+
+    /// Occurrence following the one on date.
+    pub(crate) fn following(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Recurrence::Monthly { day } => {
+                let next_month: NaiveDate = date.with_day(1).unwrap() + Months::new(1);
+                day_of_month(next_month.year(), next_month.month(), *day)
+            }
+            Recurrence::Yearly { month, day } => day_of_month(date.year() + 1, *month, *day),
+            Recurrence::Weekly { weeks } => date + chrono::Duration::weeks(*weeks as i64),
+        }
+    }
+}
+
+// Conversion to string
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Recurrence::Monthly { day } => format!("Monthly on day {}", day),
+            Recurrence::Yearly { month, day } => format!("Yearly on {}/{}", day, month),
+            Recurrence::Weekly { weeks: 1 } => String::from("Weekly"),
+            Recurrence::Weekly { weeks } => format!("Every {} weeks", weeks),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl Default for Recurrence {
+    fn default() -> Self {
+        Recurrence::Monthly { day: 1 }
+    }
+}
+
+/// A party entered again and again, e.g. the rent or the salary.
+#[derive(Clone)]
+pub(crate) struct Template {
+    pub(crate) name: String,
+    /// Balanced transactions of every occurrence, whose dates are ignored
+    pub(crate) transactions: Vec<Transaction>,
+    pub(crate) recurrence: Recurrence,
+    pub(crate) start_date: NaiveDate,
+    /// No occurrences are due after it, if there is one
+    pub(crate) end_date: Option<NaiveDate>,
+}
+
+/// An occurrence of a template, due but not yet entered.
+#[derive(Clone)]
+pub(crate) struct Occurrence {
+    pub(crate) template_id: i64,
+    pub(crate) name: String,
+    pub(crate) date: NaiveDate,
+    pub(crate) transactions: Vec<Transaction>,
+}
+
+impl Occurrence {
+    /// Amount of the occurrence: what is earned plus what is taken from the accounts.
+    pub(crate) fn value(&self) -> f64 {
+        self.transactions
+            .iter()
+            .filter(|transaction| {
+                matches!(
+                    transaction,
+                    Transaction::Income { .. } | Transaction::Debit { .. }
+                )
+            })
+            .map(|transaction| transaction.value())
+            .sum()
+    }
+
+    /// The party of the occurrence, with every transaction scaled so that its amount becomes
+    /// value. Scaling them all keeps the party balanced.
+    pub(crate) fn party(&self, value: f64) -> Party {
+        let original_value: f64 = self.value();
+        let factor: f64 = match original_value == 0.0 {
+            true => 1.0,
+            false => value / original_value,
+        };
+
+        Party::new(
+            self.transactions
+                .iter()
+                .map(|transaction| {
+                    let mut transaction: Transaction = transaction.clone();
+                    match &mut transaction {
+                        Transaction::Income { value, .. }
+                        | Transaction::Expense { value, .. }
+                        | Transaction::Credit { value, .. }
+                        | Transaction::Debit { value, .. } => *value *= factor,
+                    }
+                    transaction
+                })
+                .collect(),
+        )
+    }
+}
+
+impl DataBase {
+    /// Saves a template. Its first occurrence is due on the first day matching the recurrence
+    /// from the start date on.
+    pub(crate) fn insert_template(&mut self, template: &Template) -> i64 {
+        let template_id: i64 = self.template_table.insert_template(
+            template.name.as_str(),
+            template.recurrence.rule().as_str(),
+            template.start_date,
+            template.end_date,
+            template.recurrence.first(template.start_date),
+        );
+        for transaction in template.transactions.iter() {
+            self.template_transaction_table
+                .insert_transaction(template_id, transaction);
+        }
+
+        template_id
+    }
+
+    /// Deletes a template. The parties already entered from it are kept.
+    pub(crate) fn delete_template(&mut self, template_id: i64) -> () {
+        self.template_transaction_table.delete_template(template_id);
+        self.template_table.delete_template(template_id);
+    }
+
+    /// Returns every template with its ID and the date its next occurrence is due, sorted by
+    /// name.
+    pub(crate) fn templates(&self) -> Vec<(i64, Template, NaiveDate)> {
+        let records: DataFrame = self
+            .template_table
+            .data_frame
+            .clone()
+            .lazy()
+            .sort(["name"], Default::default())
+            .collect()
+            .unwrap();
+
+        let date = |name: &str, i: usize| -> Option<NaiveDate> {
+            records
+                .column(name)
+                .unwrap()
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .nth(i)
+                .flatten()
+        };
+
+        (0..records.height())
+            .map(|i| {
+                let template_id: i64 = records
+                    .column("template_id")
+                    .unwrap()
+                    .i64()
+                    .unwrap()
+                    .get(i)
+                    .unwrap();
+                let start_date: NaiveDate = date("start_date", i).unwrap();
+                let template: Template = Template {
+                    name: records
+                        .column("name")
+                        .unwrap()
+                        .str()
+                        .unwrap()
+                        .get(i)
+                        .unwrap()
+                        .to_string(),
+                    transactions: self
+                        .template_transaction_table
+                        .transactions(template_id, start_date),
+                    recurrence: Recurrence::from_rule(
+                        records
+                            .column("rule")
+                            .unwrap()
+                            .str()
+                            .unwrap()
+                            .get(i)
+                            .unwrap(),
+                    )
+                    .unwrap(),
+                    start_date,
+                    end_date: date("end_date", i),
+                };
+
+                (template_id, template, date("next_date", i).unwrap())
+            })
+            .collect()
+    }
+
+    /// Returns the occurrences of every template due until date included, oldest first.
+    pub(crate) fn due_occurrences(&self, date: NaiveDate) -> Vec<Occurrence> {
+        let mut occurrences: Vec<Occurrence> = Vec::new();
+        for (template_id, template, next_date) in self.templates() {
+            let mut occurrence_date: NaiveDate = next_date;
+            while occurrence_date <= date
+                && template
+                    .end_date
+                    .is_none_or(|end_date| occurrence_date <= end_date)
+            {
+                occurrences.push(Occurrence {
+                    template_id,
+                    name: template.name.clone(),
+                    date: occurrence_date,
+                    transactions: self
+                        .template_transaction_table
+                        .transactions(template_id, occurrence_date),
+                });
+                occurrence_date = template.recurrence.following(occurrence_date);
+            }
+        }
+        occurrences.sort_by_key(|occurrence| occurrence.date);
+
+        occurrences
+    }
+
+    /// Enters an occurrence as a party whose amount is value. Returns the ID of the party, or
+    /// an error if the value is not positive.
+    pub(crate) fn confirm_occurrence(
+        &mut self,
+        occurrence: &Occurrence,
+        value: f64,
+    ) -> Result<i64, String> {
+        if value <= 0.0 {
+            return Err(format!(
+                "The amount of {} on {} must be positive",
+                occurrence.name, occurrence.date
+            ));
+        }
+        let party_id: i64 = self.insert_party(&mut occurrence.party(value));
+        self.skip_occurrence(occurrence);
+
+        Ok(party_id)
+    }
+
+    /// Marks an occurrence as handled without entering it.
+    pub(crate) fn skip_occurrence(&mut self, occurrence: &Occurrence) -> () {
+        let template = self
+            .templates()
+            .into_iter()
+            .find(|(template_id, _template, _next_date)| *template_id == occurrence.template_id);
+        if let Some((template_id, template, next_date)) = template {
+            let following: NaiveDate = template.recurrence.following(occurrence.date);
+            if following > next_date {
+                self.template_table.set_next_date(template_id, following);
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::modules::database::summaries::TimeUnit;
+use crate::modules::database::DataBase;
+use chrono::{Datelike, Months, NaiveDate, Weekday};
+use polars::prelude::*;
+use serde_json::{json, Value};
+use std::fs::{create_dir_all, read_to_string, File};
+use std::io::Write;
+
+/// File where the settings are kept.
+const SETTINGS_FILE: &str = "data/settings.json";
+
+/// How the days, weeks, months and years are delimited when aggregating transactions over time.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PeriodDefinition {
+    /// Day of the month on which months start, from 1 to 28, e.g. the pay day
+    pub(crate) month_start_day: u32,
+    /// Day on which weeks start
+    pub(crate) week_start: Weekday,
+    /// Month on which years start, from 1 to 12
+    pub(crate) year_start_month: u32,
+    /// Day of the month on which years start, from 1 to 28
+    pub(crate) year_start_day: u32,
+}
+
+impl PeriodDefinition {
+    /// Offset of the periods of the time_unit from the calendar ones, in the format of polars
+    /// durations.
+    pub(crate) fn offset(&self, time_unit: &TimeUnit) -> String {
+        match time_unit {
+            TimeUnit::Day => String::from("0"),
+            TimeUnit::Week => format!("{}d", self.week_start.num_days_from_monday()),
+            TimeUnit::Month => format!("{}d", self.month_start_day - 1),
+            TimeUnit::Year => format!(
+                "{}mo{}d",
+                self.year_start_month - 1,
+                self.year_start_day - 1
+            ),
+        }
+    }
+
+    /// First day of the month the date belongs to.
+    pub(crate) fn month_start(&self, date: NaiveDate) -> NaiveDate {
+        let month_start: NaiveDate = date.with_day(self.month_start_day).unwrap();
+        if month_start > date {
+            month_start - Months::new(1)
+        } else {
+            month_start
+        }
+    }
+
+    /// First day of the year the date belongs to.
+    pub(crate) fn year_start(&self, date: NaiveDate) -> NaiveDate {
+        let year_start: NaiveDate =
+            NaiveDate::from_ymd_opt(date.year(), self.year_start_month, self.year_start_day)
+                .unwrap();
+        if year_start > date {
+            year_start - Months::new(12)
+        } else {
+            year_start
+        }
+    }
+
+    /// First and last day of the period of the time_unit the date belongs to.
+    pub(crate) fn period(&self, time_unit: &TimeUnit, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match time_unit {
+            TimeUnit::Day => (date, date),
+            TimeUnit::Week => {
+                let days: i64 = (date.weekday().num_days_from_monday() as i64
+                    - self.week_start.num_days_from_monday() as i64)
+                    .rem_euclid(7);
+                let start: NaiveDate = date - chrono::Duration::days(days);
+                (start, start + chrono::Duration::days(6))
+            }
+            TimeUnit::Month => {
+                let start: NaiveDate = self.month_start(date);
+                (start, start + Months::new(1) - chrono::Duration::days(1))
+            }
+            TimeUnit::Year => {
+                let start: NaiveDate = self.year_start(date);
+                (start, start + Months::new(12) - chrono::Duration::days(1))
+            }
+        }
+    }
+
+    /// Options with which to group_by_dynamic by the periods of the time_unit. Every window is
+    /// labelled by the date on which it starts.
+    pub(crate) fn group_options(&self, time_unit: &TimeUnit) -> DynamicGroupOptions {
+        DynamicGroupOptions {
+            every: Duration::parse(time_unit.duration()),
+            period: Duration::parse(time_unit.duration()),
+            offset: Duration::parse(self.offset(time_unit).as_str()),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for PeriodDefinition {
+    fn default() -> Self {
+        PeriodDefinition {
+            month_start_day: 1,
+            week_start: Weekday::Mon,
+            year_start_month: 1,
+            year_start_day: 1,
+        }
+    }
+}
+
+/// Preferences of the user, stored next to the tables.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Settings {
+    pub(crate) periods: PeriodDefinition,
+    /// Months of average expenses the liquid funds should cover, from 0, no minimum, to 24
+    pub(crate) min_runway_months: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            periods: PeriodDefinition::default(),
+            min_runway_months: 3,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, or returns the default settings if there is none.
+    pub(crate) fn init() -> Settings {
+        read_to_string(SETTINGS_FILE)
+            .ok()
+            .and_then(|json| Settings::from_json(json.as_str()).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        let settings: Value = json!({
+            "periods": {
+                "month_start_day": self.periods.month_start_day,
+                "week_start": self.periods.week_start.num_days_from_monday(),
+                "year_start_month": self.periods.year_start_month,
+                "year_start_day": self.periods.year_start_day,
+            },
+            "liquidity": {
+                "min_runway_months": self.min_runway_months,
+            }
+        });
+
+        serde_json::to_string_pretty(&settings).unwrap()
+    }
+
+    /// Parses the settings, checking that every day and month is within its range. Missing
+    /// settings take their default value.
+    pub(crate) fn from_json(json: &str) -> Result<Settings, String> {
+        let settings: Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid settings: {}", e))?;
+        let default: Settings = Settings::default();
+
+        let setting = |name: &str, default: u32, max: u32| -> Result<u32, String> {
+            match settings["periods"].get(name) {
+                None => Ok(default),
+                Some(value) => value
+                    .as_u64()
+                    .filter(|value| (1..=max as u64).contains(value))
+                    .map(|value| value as u32)
+                    .ok_or(format!("Invalid {}: {}", name, value)),
+            }
+        };
+
+        let week_start: Weekday = match settings["periods"].get("week_start") {
+            None => default.periods.week_start,
+            Some(value) => value
+                .as_u64()
+                .and_then(|value| Weekday::try_from(value as u8).ok())
+                .ok_or(format!("Invalid week_start: {}", value))?,
+        };
+
+        let min_runway_months: u32 = match settings["liquidity"].get("min_runway_months") {
+            None => default.min_runway_months,
+            Some(value) => value
+                .as_u64()
+                .filter(|value| *value <= 24)
+                .map(|value| value as u32)
+                .ok_or(format!("Invalid min_runway_months: {}", value))?,
+        };
+
+        Ok(Settings {
+            periods: PeriodDefinition {
+                month_start_day: setting("month_start_day", 1, 28)?,
+                week_start,
+                year_start_month: setting("year_start_month", 1, 12)?,
+                year_start_day: setting("year_start_day", 1, 28)?,
+            },
+            min_runway_months,
+        })
+    }
+
+    pub(crate) fn save(&self) -> Result<(), String> {
+        create_dir_all("data").map_err(|e| format!("Failed to create data: {}", e))?;
+        File::create(SETTINGS_FILE)
+            .and_then(|mut file| file.write_all(self.to_json().as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", SETTINGS_FILE, e))
+    }
+}
+
+impl DataBase {
+    pub(crate) fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Replaces the settings and writes them to the settings file.
+    pub(crate) fn set_settings(&mut self, settings: Settings) -> Result<(), String> {
+        settings.save()?;
+        self.settings = settings;
+        Ok(())
+    }
+}
@@ -0,0 +1,638 @@
+use crate::modules::currency_exchange::CurrencyExchange;
+use crate::modules::database::settings::Settings;
+use crate::modules::database::DataBase;
+use crate::modules::financial::Currency;
+use crate::modules::tables::*;
+use chrono::{Local, NaiveDate};
+use polars::prelude::*;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
+use std::fs::{create_dir, read_to_string, remove_file, File};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Tag identifying snapshot files.
+const SNAPSHOT_FORMAT: &str = "delphis_nap snapshot";
+
+/// Version of the layout of the snapshot files. Bump it whenever the tables change, and teach
+/// the import how to read the older versions.
+///
+/// 1: the six ledger tables and the exchange rates.
+/// 2: adds the attachment table.
+/// 3: adds the budget table.
+/// 4: adds the goal and allocation tables.
+/// 5: adds the template and template transaction tables.
+/// 6: adds the dismissal table.
+/// 7: adds the quantity and unit columns to the expense table.
+const SNAPSHOT_SCHEMA_VERSION: i64 = 7;
+
+/// Name under which a column type is stored in the snapshot.
+fn type_name(data_type: &DataType) -> Result<&str, String> {
+    match data_type {
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => Ok("integer"),
+        DataType::Float32 | DataType::Float64 => Ok("float"),
+        DataType::String => Ok("string"),
+        DataType::Date => Ok("date"),
+        DataType::Boolean => Ok("boolean"),
+        data_type => Err(format!("columns of type {} cannot be saved", data_type)),
+    }
+}
+
+/// Converts a table into a list of column definitions and a list of records.
+fn data_frame_to_json(data_frame: &DataFrame) -> Value {
+    let mut columns: Vec<Value> = Vec::new();
+    let mut rows: Vec<Map<String, Value>> = vec![Map::new(); data_frame.height()];
+
+    for column in data_frame.get_columns() {
+        let type_name: &str = type_name(column.dtype()).unwrap();
+        let values: Vec<Value> = match type_name {
+            "integer" => column
+                .cast(&DataType::Int64)
+                .unwrap()
+                .i64()
+                .unwrap()
+                .into_iter()
+                .map(|value| json!(value))
+                .collect(),
+            "float" => column
+                .cast(&DataType::Float64)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .map(|value| json!(value))
+                .collect(),
+            "string" => column
+                .str()
+                .unwrap()
+                .into_iter()
+                .map(|value| json!(value))
+                .collect(),
+            "date" => column
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .map(|value| json!(value.map(|date| date.to_string())))
+                .collect(),
+            _ => column
+                .bool()
+                .unwrap()
+                .into_iter()
+                .map(|value| json!(value))
+                .collect(),
+        };
+
+        for (row, value) in rows.iter_mut().zip(values) {
+            row.insert(column.name().to_string(), value);
+        }
+        columns.push(json!({"name": column.name().as_str(), "type": type_name}));
+    }
+
+    json!({"columns": columns, "rows": rows})
+}
+
+/// Rebuilds a table out of its records, checking that every column of the expected schema is
+/// there with the right type.
+fn json_to_data_frame(name: &str, table: &Value, schema: &Schema) -> Result<DataFrame, String> {
+    let stored_columns: HashSet<&str> = table["columns"]
+        .as_array()
+        .ok_or(format!("{} table has no column list", name))?
+        .iter()
+        .filter_map(|column| column["name"].as_str())
+        .collect();
+    let rows: &Vec<Value> = table["rows"]
+        .as_array()
+        .ok_or(format!("{} table has no records", name))?;
+
+    let mut columns: Vec<Column> = Vec::new();
+    for (column_name, data_type) in schema.iter() {
+        if !stored_columns.contains(column_name.as_str()) {
+            return Err(format!("{} table lacks column {}", name, column_name));
+        }
+
+        let cells = rows.iter().map(|row| &row[column_name.as_str()]);
+        let error = |i: usize| {
+            format!(
+                "{} table has an invalid {} in record {}",
+                name, column_name, i
+            )
+        };
+        let series: Series = match data_type {
+            DataType::Int64 => {
+                let mut values: Vec<Option<i64>> = Vec::new();
+                for (i, cell) in cells.enumerate() {
+                    match cell {
+                        Value::Null => values.push(None),
+                        cell => values.push(Some(cell.as_i64().ok_or_else(|| error(i))?)),
+                    }
+                }
+                Series::new(column_name.clone(), values)
+            }
+            DataType::Float64 => {
+                let mut values: Vec<Option<f64>> = Vec::new();
+                for (i, cell) in cells.enumerate() {
+                    match cell {
+                        Value::Null => values.push(None),
+                        cell => values.push(Some(cell.as_f64().ok_or_else(|| error(i))?)),
+                    }
+                }
+                Series::new(column_name.clone(), values)
+            }
+            DataType::Boolean => {
+                let mut values: Vec<Option<bool>> = Vec::new();
+                for (i, cell) in cells.enumerate() {
+                    match cell {
+                        Value::Null => values.push(None),
+                        cell => values.push(Some(cell.as_bool().ok_or_else(|| error(i))?)),
+                    }
+                }
+                Series::new(column_name.clone(), values)
+            }
+            DataType::Date => {
+                let mut values: Vec<Option<NaiveDate>> = Vec::new();
+                for (i, cell) in cells.enumerate() {
+                    match cell {
+                        Value::Null => values.push(None),
+                        cell => values.push(Some(
+                            cell.as_str()
+                                .and_then(|date| NaiveDate::from_str(date).ok())
+                                .ok_or_else(|| error(i))?,
+                        )),
+                    }
+                }
+                Series::new(column_name.clone(), values)
+            }
+            _ => {
+                let mut values: Vec<Option<String>> = Vec::new();
+                for (i, cell) in cells.enumerate() {
+                    match cell {
+                        Value::Null => values.push(None),
+                        cell => values.push(Some(
+                            cell.as_str().map(String::from).ok_or_else(|| error(i))?,
+                        )),
+                    }
+                }
+                Series::new(column_name.clone(), values)
+            }
+        };
+        columns.push(Column::from(series));
+    }
+
+    DataFrame::new(columns).map_err(|e| format!("Failed to rebuild {} table: {}", name, e))
+}
+
+/// Hex-encoded SHA-256 of the tables and exchange rates of a snapshot.
+fn checksum(payload: &Value) -> String {
+    Sha256::digest(payload.to_string().as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns the values of an integer column.
+fn ids(data_frame: &DataFrame, column_name: &str) -> Vec<Option<i64>> {
+    data_frame
+        .column(column_name)
+        .unwrap()
+        .i64()
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
+/// A ledger read back from a snapshot, validated but not yet written to disk.
+pub(crate) struct Snapshot {
+    pub(crate) database: DataBase,
+    exchange_rates: BTreeMap<String, DataFrame>,
+    created: String,
+    /// Attached files the snapshot refers to that are not on this computer
+    pub(crate) missing_attachments: Vec<String>,
+}
+
+impl DataBase {
+    /// The tables, with the name under which they are stored.
+    fn named_tables(&self) -> [(String, &DataFrame); 13] {
+        [
+            (IncomeTable::name(), &self.incomes_table.data_frame),
+            (ExpensesTable::name(), &self.expenses_table.data_frame),
+            (FundsTable::name(), &self.funds_table.data_frame),
+            (PartyTable::name(), &self.party_table.data_frame),
+            (EntityTable::name(), &self.entity_table.data_frame),
+            (AccountTable::name(), &self.account_table.data_frame),
+            (AttachmentTable::name(), &self.attachment_table.data_frame),
+            (BudgetTable::name(), &self.budget_table.data_frame),
+            (GoalTable::name(), &self.goal_table.data_frame),
+            (AllocationTable::name(), &self.allocation_table.data_frame),
+            (TemplateTable::name(), &self.template_table.data_frame),
+            (
+                TemplateTransactionTable::name(),
+                &self.template_transaction_table.data_frame,
+            ),
+            (DismissalTable::name(), &self.dismissal_table.data_frame),
+        ]
+    }
+
+    /// The whole ledger as a self-describing JSON document: the tables, the exchange rates
+    /// cached on disk, the schema version and a checksum of the content. Attached files are
+    /// referenced but not included, restoring reports the ones missing.
+    pub(crate) fn snapshot(&self) -> String {
+        let mut tables: Map<String, Value> = Map::new();
+        for (name, data_frame) in self.named_tables() {
+            tables.insert(name, data_frame_to_json(data_frame));
+        }
+
+        let mut exchange_rates: Map<String, Value> = Map::new();
+        for key in CurrencyExchange::cache_keys() {
+            if let Ok(data_frame) = CurrencyExchange::read_cache(&key) {
+                exchange_rates.insert(key, data_frame_to_json(&data_frame));
+            }
+        }
+
+        let payload: Value = json!({"tables": tables, "exchange_rates": exchange_rates});
+        let snapshot: Value = json!({
+            "format": SNAPSHOT_FORMAT,
+            "schema_version": SNAPSHOT_SCHEMA_VERSION,
+            "created": Local::now().date_naive().to_string(),
+            "checksum": checksum(&payload),
+            "tables": payload["tables"],
+            "exchange_rates": payload["exchange_rates"],
+        });
+
+        serde_json::to_string_pretty(&snapshot).unwrap()
+    }
+
+    /// Writes a snapshot of the ledger to the data folder and returns the file name.
+    pub(crate) fn export_snapshot(&self) -> Result<String, String> {
+        let file_name: String = format!("data/snapshot_{}.json", Local::now().date_naive());
+        let path: &Path = Path::new(&file_name);
+        if !path.parent().expect("path does not have parent").exists() {
+            let _ = create_dir(path.parent().expect("path does not have parents"));
+        }
+
+        File::create(path)
+            .and_then(|mut file| file.write_all(self.snapshot().as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+
+        Ok(file_name)
+    }
+
+    /// Reads a snapshot and, unless it is a dry run, replaces the ledger with it. Either way,
+    /// returns a summary of what the snapshot contains.
+    pub(crate) fn import_snapshot(&mut self, path: &str, dry_run: bool) -> Result<String, String> {
+        let snapshot: Snapshot = Snapshot::load(path)?;
+        let summary: String = snapshot.to_string();
+
+        if !dry_run {
+            *self = snapshot.restore();
+        }
+
+        Ok(summary)
+    }
+
+    /// Checks that every id is unique and that every reference points to an existing record.
+    fn check_integrity(&self) -> Result<(), String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        let mut id_sets: BTreeMap<String, HashSet<i64>> = BTreeMap::new();
+        for (name, data_frame) in self.named_tables() {
+            let mut id_set: HashSet<i64> = HashSet::new();
+            for id in ids(data_frame, format!("{}_id", name).as_str()) {
+                match id {
+                    Some(id) if !id_set.insert(id) => {
+                        errors.push(format!("{} table has id {} twice", name, id))
+                    }
+                    None => errors.push(format!("{} table has a record without id", name)),
+                    _ => (),
+                }
+            }
+            id_sets.insert(name, id_set);
+        }
+
+        let references = [
+            (
+                IncomeTable::name(),
+                &self.incomes_table.data_frame,
+                PartyTable::name(),
+            ),
+            (
+                IncomeTable::name(),
+                &self.incomes_table.data_frame,
+                EntityTable::name(),
+            ),
+            (
+                ExpensesTable::name(),
+                &self.expenses_table.data_frame,
+                PartyTable::name(),
+            ),
+            (
+                ExpensesTable::name(),
+                &self.expenses_table.data_frame,
+                EntityTable::name(),
+            ),
+            (
+                FundsTable::name(),
+                &self.funds_table.data_frame,
+                PartyTable::name(),
+            ),
+            (
+                FundsTable::name(),
+                &self.funds_table.data_frame,
+                AccountTable::name(),
+            ),
+            (
+                AttachmentTable::name(),
+                &self.attachment_table.data_frame,
+                PartyTable::name(),
+            ),
+            (
+                AllocationTable::name(),
+                &self.allocation_table.data_frame,
+                GoalTable::name(),
+            ),
+            (
+                TemplateTransactionTable::name(),
+                &self.template_transaction_table.data_frame,
+                TemplateTable::name(),
+            ),
+            (
+                TemplateTransactionTable::name(),
+                &self.template_transaction_table.data_frame,
+                EntityTable::name(),
+            ),
+            (
+                TemplateTransactionTable::name(),
+                &self.template_transaction_table.data_frame,
+                AccountTable::name(),
+            ),
+        ];
+        for (name, data_frame, referenced_name) in references {
+            let referenced_ids: &HashSet<i64> = &id_sets[&referenced_name];
+            for id in ids(data_frame, format!("{}_id", referenced_name).as_str())
+                .into_iter()
+                .flatten()
+            {
+                if !referenced_ids.contains(&id) {
+                    errors.push(format!(
+                        "{} table refers to missing {} {}",
+                        name, referenced_name, id
+                    ));
+                }
+            }
+        }
+
+        for (name, data_frame) in [
+            (IncomeTable::name(), &self.incomes_table.data_frame),
+            (ExpensesTable::name(), &self.expenses_table.data_frame),
+            (FundsTable::name(), &self.funds_table.data_frame),
+            (AccountTable::name(), &self.account_table.data_frame),
+            (BudgetTable::name(), &self.budget_table.data_frame),
+            (GoalTable::name(), &self.goal_table.data_frame),
+            (
+                TemplateTransactionTable::name(),
+                &self.template_transaction_table.data_frame,
+            ),
+        ] {
+            for currency in data_frame.column("currency").unwrap().str().unwrap() {
+                if Currency::from_str(currency.unwrap_or_default()).is_err() {
+                    errors.push(format!(
+                        "{} table has unknown currency {}",
+                        name,
+                        currency.unwrap_or_default()
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            errors.dedup();
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+impl Snapshot {
+    /// Parses and validates a snapshot: format, schema version, checksum, table schemas and
+    /// referential integrity.
+    pub(crate) fn parse(snapshot: &str) -> Result<Snapshot, String> {
+        let snapshot: Value =
+            serde_json::from_str(snapshot).map_err(|e| format!("Invalid snapshot: {}", e))?;
+
+        if snapshot["format"].as_str() != Some(SNAPSHOT_FORMAT) {
+            return Err(String::from("Not a snapshot file"));
+        }
+        let schema_version: i64 = match snapshot["schema_version"].as_i64() {
+            Some(version) if (1..=SNAPSHOT_SCHEMA_VERSION).contains(&version) => version,
+            Some(version) => {
+                return Err(format!(
+                    "Snapshot schema version {} is not supported (expected {})",
+                    version, SNAPSHOT_SCHEMA_VERSION
+                ))
+            }
+            None => return Err(String::from("Snapshot has no schema version")),
+        };
+
+        let payload: Value = json!({
+            "tables": snapshot["tables"],
+            "exchange_rates": snapshot["exchange_rates"],
+        });
+        if snapshot["checksum"].as_str() != Some(checksum(&payload).as_str()) {
+            return Err(String::from(
+                "Snapshot checksum does not match, the file was modified or is incomplete",
+            ));
+        }
+
+        let tables: &Value = &snapshot["tables"];
+        let table = |name: String, schema: &Schema| {
+            json_to_data_frame(name.as_str(), &tables[name.as_str()], schema)
+        };
+        let mut expenses_schema: Schema = ExpensesTable::new().data_frame.schema().as_ref().clone();
+        if schema_version < 7 {
+            // Older expenses have no quantity, the table fills it in empty
+            expenses_schema.shift_remove("quantity");
+            expenses_schema.shift_remove("unit");
+        }
+        let database: DataBase = DataBase {
+            incomes_table: *IncomeTable::create(table(
+                IncomeTable::name(),
+                IncomeTable::new().data_frame.schema(),
+            )?),
+            expenses_table: *ExpensesTable::create(table(ExpensesTable::name(), &expenses_schema)?),
+            funds_table: *FundsTable::create(table(
+                FundsTable::name(),
+                FundsTable::new().data_frame.schema(),
+            )?),
+            party_table: *PartyTable::create(table(
+                PartyTable::name(),
+                PartyTable::new().data_frame.schema(),
+            )?),
+            entity_table: *EntityTable::create(table(
+                EntityTable::name(),
+                EntityTable::new().data_frame.schema(),
+            )?),
+            account_table: *AccountTable::create(table(
+                AccountTable::name(),
+                AccountTable::new().data_frame.schema(),
+            )?),
+            attachment_table: if schema_version >= 2 {
+                *AttachmentTable::create(table(
+                    AttachmentTable::name(),
+                    AttachmentTable::new().data_frame.schema(),
+                )?)
+            } else {
+                *AttachmentTable::new()
+            },
+            budget_table: if schema_version >= 3 {
+                *BudgetTable::create(table(
+                    BudgetTable::name(),
+                    BudgetTable::new().data_frame.schema(),
+                )?)
+            } else {
+                *BudgetTable::new()
+            },
+            goal_table: if schema_version >= 4 {
+                *GoalTable::create(table(
+                    GoalTable::name(),
+                    GoalTable::new().data_frame.schema(),
+                )?)
+            } else {
+                *GoalTable::new()
+            },
+            allocation_table: if schema_version >= 4 {
+                *AllocationTable::create(table(
+                    AllocationTable::name(),
+                    AllocationTable::new().data_frame.schema(),
+                )?)
+            } else {
+                *AllocationTable::new()
+            },
+            template_table: if schema_version >= 5 {
+                *TemplateTable::create(table(
+                    TemplateTable::name(),
+                    TemplateTable::new().data_frame.schema(),
+                )?)
+            } else {
+                *TemplateTable::new()
+            },
+            template_transaction_table: if schema_version >= 5 {
+                *TemplateTransactionTable::create(table(
+                    TemplateTransactionTable::name(),
+                    TemplateTransactionTable::new().data_frame.schema(),
+                )?)
+            } else {
+                *TemplateTransactionTable::new()
+            },
+            dismissal_table: if schema_version >= 6 {
+                *DismissalTable::create(table(
+                    DismissalTable::name(),
+                    DismissalTable::new().data_frame.schema(),
+                )?)
+            } else {
+                *DismissalTable::new()
+            },
+            // Settings belong to the installation, not to the ledger
+            settings: Settings::init(),
+        };
+        database.check_integrity()?;
+
+        let exchange_rate_schema: Schema = Schema::from_iter([
+            Field::new("date".into(), DataType::Date),
+            Field::new("value".into(), DataType::Float64),
+        ]);
+        let mut exchange_rates: BTreeMap<String, DataFrame> = BTreeMap::new();
+        if let Some(stored_exchange_rates) = snapshot["exchange_rates"].as_object() {
+            for (key, table) in stored_exchange_rates {
+                if !CurrencyExchange::cache_keys().contains(key) {
+                    return Err(format!("Unknown exchange rate table {}", key));
+                }
+                exchange_rates.insert(
+                    key.clone(),
+                    json_to_data_frame(key.as_str(), table, &exchange_rate_schema)?,
+                );
+            }
+        }
+
+        let missing_attachments: Vec<String> = database.missing_attachments();
+
+        Ok(Snapshot {
+            database,
+            exchange_rates,
+            created: snapshot["created"].as_str().unwrap_or("?").to_string(),
+            missing_attachments,
+        })
+    }
+
+    /// Reads and validates a snapshot file.
+    pub(crate) fn load(path: &str) -> Result<Snapshot, String> {
+        let snapshot: String =
+            read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+        Snapshot::parse(snapshot.as_str())
+    }
+
+    /// Replaces the tables and exchange rates on disk with the ones of the snapshot.
+    pub(crate) fn restore(mut self) -> DataBase {
+        // empty tables are not saved, so their old files must go
+        for (name, _data_frame) in self.database.named_tables() {
+            let _ = remove_file(format!("data/{}_table.csv", name));
+        }
+        self.database.save();
+
+        for (key, data_frame) in self.exchange_rates.iter_mut() {
+            CurrencyExchange::write_cache(key, data_frame);
+        }
+
+        self.database
+    }
+}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let size: DataFrame = self.database.size();
+        let records: Vec<String> = size
+            .column("table")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .zip(size.column("records").unwrap().i64().unwrap())
+            .map(|(table, records)| {
+                format!(
+                    "{} {}",
+                    records.unwrap_or_default(),
+                    table.unwrap_or_default()
+                )
+            })
+            .collect();
+
+        write!(
+            f,
+            "Snapshot of {} with {} records and {} exchange rate tables.",
+            self.created,
+            records.join(", "),
+            self.exchange_rates.len()
+        )?;
+        if !self.missing_attachments.is_empty() {
+            write!(
+                f,
+                " {} attached files are missing and have to be copied into the attachments \
+                folder by hand: {}.",
+                self.missing_attachments.len(),
+                self.missing_attachments.join(", ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,627 @@
+use crate::modules::database::independence::IndependencePlan;
+use crate::modules::database::summaries::{
+    EntityGrouping, FundGrouping, SummaryDimension, TimeUnit, Valuation,
+};
+use crate::modules::database::DataBase;
+use crate::modules::financial::{Currency, TransactionType};
+use crate::modules::price_index::RealTerms;
+use crate::modules::tables::*;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use rust_xlsxwriter::utility::row_col_to_cell;
+use rust_xlsxwriter::{Format, Formula, Workbook, XlsxError};
+use std::fs::{create_dir, File};
+use std::io::Write;
+use std::path::Path;
+
+/// A table to be written in its own worksheet.
+pub(crate) struct Sheet {
+    name: String,
+    data_frame: DataFrame,
+    /// Currency of the amounts, if they are all in the same one
+    currency: Option<Currency>,
+    /// Whether to append a row with the sum of every amount column
+    add_totals: bool,
+}
+
+impl Sheet {
+    pub(crate) fn new(
+        name: &str,
+        data_frame: DataFrame,
+        currency: Option<Currency>,
+        add_totals: bool,
+    ) -> Sheet {
+        Sheet {
+            name: name.to_string(),
+            data_frame,
+            currency,
+            add_totals,
+        }
+    }
+}
+
+/// Number format showing an amount in the given currency.
+fn currency_format(currency: &Currency) -> String {
+    match currency {
+        Currency::EUR => String::from("#,##0.00 [$€-x-euro2];-#,##0.00 [$€-x-euro2]"),
+        Currency::CHF => String::from("#,##0.00 \"CHF\";-#,##0.00 \"CHF\""),
+        Currency::SEK => String::from("#,##0.00 \"kr\";-#,##0.00 \"kr\""),
+    }
+}
+
+/// Writes a table in a new worksheet: a bold header, then one typed cell per value. Dates are
+/// written as dates, and floats as amounts in the currency of the sheet, or as percentages if
+/// their column name starts with %. Rows labelled "Total" are written in bold.
+fn write_sheet(workbook: &mut Workbook, sheet: &Sheet) -> Result<(), XlsxError> {
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet.name.as_str())?;
+
+    let header_format: Format = Format::new().set_bold();
+    let date_format: Format = Format::new().set_num_format("yyyy-mm-dd");
+    let integer_format: Format = Format::new().set_num_format("0");
+    let amount_format: Format = Format::new().set_num_format(
+        sheet
+            .currency
+            .as_ref()
+            .map_or(String::from("#,##0.00"), currency_format),
+    );
+    let percentage_format: Format = Format::new().set_num_format("0.00\"%\"");
+    let total_label_format: Format = Format::new().set_bold();
+
+    let height: usize = sheet.data_frame.height();
+    let total_rows: Vec<bool> = (0..height)
+        .map(|i| {
+            sheet.data_frame.get_columns().iter().any(|column| {
+                matches!(column.get(i), Ok(AnyValue::String("Total")))
+                    | matches!(column.get(i), Ok(AnyValue::StringOwned(ref s)) if s == "Total")
+            })
+        })
+        .collect();
+
+    for (j, column) in sheet.data_frame.get_columns().iter().enumerate() {
+        let col: u16 = j as u16;
+        worksheet.write_string_with_format(0, col, column.name().as_str(), &header_format)?;
+
+        let is_percentage: bool = column.name().starts_with('%');
+        let number_format: &Format = if is_percentage {
+            &percentage_format
+        } else {
+            &amount_format
+        };
+
+        match column.dtype() {
+            DataType::Date => {
+                for (i, date) in column.date().unwrap().as_date_iter().enumerate() {
+                    if let Some(date) = date {
+                        worksheet.write_date_with_format(i as u32 + 1, col, date, &date_format)?;
+                    }
+                }
+            }
+            DataType::Float32 | DataType::Float64 => {
+                let values = column.cast(&DataType::Float64).unwrap();
+                for (i, value) in values.f64().unwrap().into_iter().enumerate() {
+                    if let Some(value) = value {
+                        let format: Format = if total_rows[i] {
+                            number_format.clone().set_bold()
+                        } else {
+                            number_format.clone()
+                        };
+                        worksheet.write_number_with_format(i as u32 + 1, col, value, &format)?;
+                    }
+                }
+            }
+            data_type if data_type.is_integer() => {
+                let values = column.cast(&DataType::Int64).unwrap();
+                for (i, value) in values.i64().unwrap().into_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet.write_number_with_format(
+                            i as u32 + 1,
+                            col,
+                            value as f64,
+                            &integer_format,
+                        )?;
+                    }
+                }
+            }
+            _ => {
+                let values = column.cast(&DataType::String).unwrap();
+                for (i, value) in values.str().unwrap().into_iter().enumerate() {
+                    if let Some(value) = value {
+                        if total_rows[i] {
+                            worksheet.write_string_with_format(
+                                i as u32 + 1,
+                                col,
+                                value,
+                                &total_label_format,
+                            )?;
+                        } else {
+                            worksheet.write_string(i as u32 + 1, col, value)?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if sheet.add_totals & (height > 0) {
+        let row: u32 = height as u32 + 1;
+        worksheet.write_string_with_format(row, 0, "Total", &total_label_format)?;
+        for (j, column) in sheet.data_frame.get_columns().iter().enumerate().skip(1) {
+            if !matches!(column.dtype(), DataType::Float32 | DataType::Float64) {
+                continue;
+            }
+
+            let col: u16 = j as u16;
+            let total: f64 = column
+                .cast(&DataType::Float64)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .sum()
+                .unwrap_or(0.0);
+            let formula: Formula = Formula::new(format!(
+                "=SUM({}:{})",
+                row_col_to_cell(1, col),
+                row_col_to_cell(row - 1, col)
+            ))
+            .set_result(total.to_string());
+            worksheet.write_formula_with_format(
+                row,
+                col,
+                formula,
+                &amount_format.clone().set_bold(),
+            )?;
+        }
+    }
+
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofit();
+
+    Ok(())
+}
+
+/// Returns the content of an xlsx workbook with one worksheet per sheet.
+pub(crate) fn spreadsheet(sheets: &[Sheet]) -> Result<Vec<u8>, String> {
+    let mut workbook: Workbook = Workbook::new();
+    for sheet in sheets {
+        write_sheet(&mut workbook, sheet)
+            .map_err(|e| format!("Failed to write sheet {}: {}", sheet.name, e))?;
+    }
+
+    workbook
+        .save_to_buffer()
+        .map_err(|e| format!("Failed to build spreadsheet: {}", e))
+}
+
+/// Writes every sheet in a workbook at data/{file_stem}.xlsx and returns the file name.
+pub(crate) fn export_spreadsheet(file_stem: &str, sheets: &[Sheet]) -> Result<String, String> {
+    let spreadsheet: Vec<u8> = spreadsheet(sheets)?;
+
+    let file_name: String = format!("data/{}.xlsx", file_stem);
+    let path: &Path = Path::new(&file_name);
+    if !path.parent().expect("path does not have parent").exists() {
+        let _ = create_dir(path.parent().expect("path does not have parents"));
+    }
+
+    File::create(path)
+        .and_then(|mut file| file.write_all(&spreadsheet))
+        .map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+
+    Ok(file_name)
+}
+
+impl DataBase {
+    /// Exports the whole ledger, one sheet per table.
+    pub(crate) fn export_ledger_spreadsheet(&self) -> Result<String, String> {
+        let sheets: Vec<Sheet> = vec![
+            Sheet::new(
+                IncomeTable::name().as_str(),
+                self.incomes_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                ExpensesTable::name().as_str(),
+                self.expenses_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                FundsTable::name().as_str(),
+                self.funds_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                PartyTable::name().as_str(),
+                self.party_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                EntityTable::name().as_str(),
+                self.entity_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                AccountTable::name().as_str(),
+                self.account_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                AttachmentTable::name().as_str(),
+                self.attachment_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                BudgetTable::name().as_str(),
+                self.budget_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                GoalTable::name().as_str(),
+                self.goal_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                AllocationTable::name().as_str(),
+                self.allocation_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                TemplateTable::name().as_str(),
+                self.template_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                TemplateTransactionTable::name().as_str(),
+                self.template_transaction_table.data_frame.clone(),
+                None,
+                false,
+            ),
+            Sheet::new(
+                DismissalTable::name().as_str(),
+                self.dismissal_table.data_frame.clone(),
+                None,
+                false,
+            ),
+        ];
+
+        export_spreadsheet("ledger", &sheets)
+    }
+
+    /// Exports the expenses summary. It already holds its subtotal and totals rows.
+    pub(crate) fn export_expenses_summary(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        dimensions: &[SummaryDimension],
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Expenses summary",
+            self.expenses_summary_data_frame(
+                date_from,
+                date_to,
+                currency_to,
+                dimensions,
+                real_terms,
+            )?,
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("expenses_summary", &[sheet])
+    }
+
+    /// Exports the comparison of the budgets with the expenses of their periods. Every budget
+    /// has its own currency.
+    pub(crate) fn export_budget_vs_actual(&self, date: NaiveDate) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Budget vs actual",
+            self.budget_vs_actual_data_frame(date),
+            None,
+            false,
+        );
+
+        export_spreadsheet("budget_vs_actual", &[sheet])
+    }
+
+    /// Exports the progress of the savings goals and the unallocated funds.
+    pub(crate) fn export_goals_report(
+        &self,
+        currency_to: &Currency,
+        date: NaiveDate,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Savings goals",
+            self.goals_report_data_frame(currency_to, date),
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("savings_goals", &[sheet])
+    }
+
+    /// Exports the recurring payments detected among the expenses, each in its own currency.
+    pub(crate) fn export_subscriptions_report(&self, date: NaiveDate) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Subscriptions",
+            self.subscriptions_report_data_frame(date),
+            None,
+            false,
+        );
+
+        export_spreadsheet("subscriptions", &[sheet])
+    }
+
+    /// Exports the comparison of the expenses of two periods.
+    pub(crate) fn export_expenses_comparison(
+        &self,
+        previous: (NaiveDate, NaiveDate),
+        current: (NaiveDate, NaiveDate),
+        currency_to: &Currency,
+        threshold: f64,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Expenses comparison",
+            self.expenses_comparison_data_frame(previous, current, currency_to, threshold),
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("expenses_comparison", &[sheet])
+    }
+
+    /// Exports the income summary. It already ends with its totals row.
+    pub(crate) fn export_income_summary(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Income summary",
+            self.income_summary_data_frame(date_from, date_to, currency_to, real_terms)?,
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("income_summary", &[sheet])
+    }
+
+    /// Exports the expenses by entity.
+    pub(crate) fn export_entity_spending(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        grouping: &EntityGrouping,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Entity spending",
+            self.entity_spending_data_frame(date_from, date_to, currency_to, grouping),
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("entity_spending", &[sheet])
+    }
+
+    /// Exports the fund stand at a date. Totals are only added when all amounts are converted to
+    /// the same currency.
+    pub(crate) fn export_fund_stand(
+        &self,
+        date: NaiveDate,
+        currency_to: Option<&Currency>,
+        valuation: &Valuation,
+        grouping: &FundGrouping,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Fund stand",
+            self.fund_stand_at_data_frame(date, currency_to, valuation, grouping, real_terms)?,
+            currency_to.map(|currency| currency.clone()),
+            currency_to.is_some(),
+        );
+
+        export_spreadsheet("fund_stand", &[sheet])
+    }
+
+    /// Exports the comparison of the fund stands at two dates.
+    pub(crate) fn export_fund_stand_comparison(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        valuation: &Valuation,
+        grouping: &FundGrouping,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Fund stand comparison",
+            self.fund_stand_comparison_data_frame(
+                date_from,
+                date_to,
+                currency_to,
+                valuation,
+                grouping,
+            ),
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("fund_stand_comparison", &[sheet])
+    }
+
+    /// Exports the daily balance history of the selected accounts, or of all of them if
+    /// account_ids is empty.
+    pub(crate) fn export_balance_history(
+        &self,
+        currency_to: Option<&Currency>,
+        grouping: &FundGrouping,
+        account_ids: &[i64],
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Balance history",
+            self.balance_history_data_frame(currency_to, grouping, account_ids),
+            currency_to.map(|currency| currency.clone()),
+            false,
+        );
+
+        export_spreadsheet("balance_history", &[sheet])
+    }
+
+    /// Exports the evolution of the incomes or expenses, with its periods as dates. Its total row
+    /// is written as a sum of the periods instead.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn export_evolution(
+        &self,
+        transaction_type: &TransactionType,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+        category: Option<&str>,
+        entity_id: Option<i64>,
+        currency: Option<&Currency>,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        let name: String = match transaction_type {
+            TransactionType::Income => String::from("Income evolution"),
+            _ => String::from("Expenses evolution"),
+        };
+        let evolution: DataFrame = self
+            .evolution_data_frame(
+                transaction_type,
+                currency_to,
+                time_unit,
+                category,
+                entity_id,
+                currency,
+                real_terms,
+            )?
+            .lazy()
+            .filter(col(time_unit.to_string()).is_not_null())
+            .collect()
+            .unwrap();
+        let sheet: Sheet = Sheet::new(
+            name.as_str(),
+            evolution,
+            Some(currency_to.clone()),
+            true,
+        );
+
+        export_spreadsheet(name.to_lowercase().replace(" ", "_").as_str(), &[sheet])
+    }
+
+    /// Exports the cash flow table.
+    pub(crate) fn export_cash_flow(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Cash flow",
+            self.cash_flow_data_frame(currency_to, time_unit),
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("cash_flow", &[sheet])
+    }
+
+    /// Exports the personal price index, and the unit prices it comes from.
+    pub(crate) fn export_unit_prices(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> Result<String, String> {
+        let sheets: [Sheet; 2] = [
+            Sheet::new(
+                "Personal price index",
+                self.personal_price_index_data_frame(currency_to, time_unit),
+                None,
+                false,
+            ),
+            Sheet::new(
+                "Unit prices",
+                self.unit_price_history_data_frame(currency_to, time_unit),
+                Some(currency_to.clone()),
+                false,
+            ),
+        ];
+
+        export_spreadsheet("unit_prices", &sheets)
+    }
+
+    /// Exports the financial independence report.
+    pub(crate) fn export_independence_report(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        plan: &IndependencePlan,
+        years: u32,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Financial independence",
+            self.independence_report_data_frame(date, currency_to, plan, years),
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("independence", &[sheet])
+    }
+
+    /// Exports the runway history.
+    pub(crate) fn export_runway_report(
+        &self,
+        date: NaiveDate,
+        currency_to: &Currency,
+        window_months: u32,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Runway",
+            self.runway_report_data_frame(date, currency_to, window_months),
+            Some(currency_to.clone()),
+            false,
+        );
+
+        export_spreadsheet("runway", &[sheet])
+    }
+
+    /// Exports the last n transactions.
+    pub(crate) fn export_last_transactions(&self, n: usize) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Last transactions",
+            self.last_transactions_data_frame(n),
+            None,
+            false,
+        );
+
+        export_spreadsheet("last_transactions", &[sheet])
+    }
+
+    /// Exports the last n fund movements of an account, or of all of them if account_id is
+    /// negative.
+    pub(crate) fn export_last_fund_movements(
+        &self,
+        n: usize,
+        account_id: i64,
+    ) -> Result<String, String> {
+        let sheet: Sheet = Sheet::new(
+            "Last fund movements",
+            self.last_fund_movements_data_frame(n, account_id),
+            None,
+            false,
+        );
+
+        export_spreadsheet("last_fund_movements", &[sheet])
+    }
+}
@@ -0,0 +1,397 @@
+use crate::modules::database::recurring::{Recurrence, Template};
+use crate::modules::database::DataBase;
+use crate::modules::financial::{Currency, Transaction};
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use strum_macros::{EnumIter, EnumString};
+
+/// Minimum number of payments for a series to be considered recurring.
+const MIN_PAYMENTS: usize = 3;
+
+/// Largest change between consecutive payments, relative to the previous one, for their amounts
+/// to be considered similar. Price raises of subscriptions are usually smaller.
+const MAX_AMOUNT_CHANGE: f64 = 0.3;
+
+/// Smallest share of the intervals between payments that must match the cadence, so that a
+/// skipped or delayed payment does not hide a subscription.
+const MIN_REGULAR_INTERVALS: f64 = 0.75;
+
+/// Date, amount and party of a payment.
+type Payment = (NaiveDate, f64, i64);
+
+/// How often a recurring payment happens.
+#[derive(Debug, Hash, PartialEq, Eq, EnumIter, Clone, EnumString)]
+pub(crate) enum Cadence {
+    Weekly,
+    Fortnightly,
+    Monthly,
+    Yearly,
+}
+
+impl Cadence {
+    /// Shortest and longest number of days between two payments of the cadence.
+    fn interval(&self) -> (i64, i64) {
+        match self {
+            Cadence::Weekly => (6, 8),
+            Cadence::Fortnightly => (13, 15),
+            Cadence::Monthly => (26, 35),
+            Cadence::Yearly => (350, 380),
+        }
+    }
+
+    fn from_interval(days: i64) -> Option<Cadence> {
+        [
+            Cadence::Weekly,
+            Cadence::Fortnightly,
+            Cadence::Monthly,
+            Cadence::Yearly,
+        ]
+        .into_iter()
+        .find(|cadence| {
+            let (shortest, longest) = cadence.interval();
+            (shortest..=longest).contains(&days)
+        })
+    }
+
+    pub(crate) fn per_year(&self) -> f64 {
+        match self {
+            Cadence::Weekly => 52.0,
+            Cadence::Fortnightly => 26.0,
+            Cadence::Monthly => 12.0,
+            Cadence::Yearly => 1.0,
+        }
+    }
+
+    /// Recurrence of the payments, aligned with the payment on date.
+    pub(crate) fn recurrence(&self, date: NaiveDate) -> Recurrence {
+        match self {
+            Cadence::Weekly => Recurrence::Weekly { weeks: 1 },
+            Cadence::Fortnightly => Recurrence::Weekly { weeks: 2 },
+            Cadence::Monthly => Recurrence::Monthly { day: date.day() },
+            Cadence::Yearly => Recurrence::Yearly {
+                month: date.month(),
+                day: date.day(),
+            },
+        }
+    }
+}
+
+// Conversion to string
+impl Display for Cadence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Cadence::Weekly => "Weekly".to_string(),
+            Cadence::Fortnightly => "Fortnightly".to_string(),
+            Cadence::Monthly => "Monthly".to_string(),
+            Cadence::Yearly => "Yearly".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Expenses with the same entity, category, subcategory and currency, of similar amounts and at
+/// regular intervals.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Subscription {
+    pub(crate) entity_id: i64,
+    pub(crate) category: String,
+    pub(crate) subcategory: String,
+    pub(crate) currency: Currency,
+    pub(crate) cadence: Cadence,
+    /// Every payment, oldest first
+    pub(crate) payments: Vec<Payment>,
+}
+
+impl Subscription {
+    pub(crate) fn average(&self) -> f64 {
+        self.payments
+            .iter()
+            .map(|(_date, value, _party_id)| value)
+            .sum::<f64>()
+            / self.payments.len() as f64
+    }
+
+    pub(crate) fn last_payment(&self) -> (NaiveDate, f64) {
+        let (date, value, _party_id) = self.payments.last().unwrap();
+        (*date, *value)
+    }
+
+    /// Date and amounts before and after every change of price.
+    pub(crate) fn price_changes(&self) -> Vec<(NaiveDate, f64, f64)> {
+        self.payments
+            .windows(2)
+            .filter(|payments| (payments[1].1 - payments[0].1).abs() >= 0.01)
+            .map(|payments| (payments[1].0, payments[0].1, payments[1].1))
+            .collect()
+    }
+
+    /// What a year of payments costs at the last price.
+    pub(crate) fn annualized_cost(&self) -> f64 {
+        self.last_payment().1 * self.cadence.per_year()
+    }
+
+    /// Whether a payment was due no longer than one and a half intervals before date.
+    pub(crate) fn is_active(&self, date: NaiveDate) -> bool {
+        let (_shortest, longest) = self.cadence.interval();
+        (date - self.last_payment().0).num_days() <= longest * 3 / 2
+    }
+
+    /// Whether one of the templates already enters expenses of this series: to the same
+    /// entity, in the same category, subcategory and currency.
+    pub(crate) fn is_templated(&self, templates: &[Template]) -> bool {
+        templates
+            .iter()
+            .flat_map(|template| template.transactions.iter())
+            .any(|transaction| match transaction {
+                Transaction::Expense {
+                    currency,
+                    category,
+                    subcategory,
+                    entity_id,
+                    ..
+                } => {
+                    *entity_id == self.entity_id
+                        && *category == self.category
+                        && *subcategory == self.subcategory
+                        && *currency == self.currency
+                }
+                _ => false,
+            })
+    }
+}
+
+/// Finds the regular series among the payments of a group, sorted by date. Payments of
+/// dissimilar amounts break the series.
+fn detect_series(payments: &[Payment]) -> Vec<(Cadence, Vec<Payment>)> {
+    let mut series: Vec<Vec<Payment>> = vec![Vec::new()];
+    for payment in payments {
+        let current: &mut Vec<Payment> = series.last_mut().unwrap();
+        match current.last() {
+            Some((_date, value, _party_id))
+                if (payment.1 - value).abs() > MAX_AMOUNT_CHANGE * value.abs() =>
+            {
+                series.push(vec![*payment])
+            }
+            _ => current.push(*payment),
+        }
+    }
+
+    series
+        .into_iter()
+        .filter(|payments| payments.len() >= MIN_PAYMENTS)
+        .filter_map(|payments| {
+            let mut intervals: Vec<i64> = payments
+                .windows(2)
+                .map(|payments| (payments[1].0 - payments[0].0).num_days())
+                .collect();
+            intervals.sort();
+            let cadence: Cadence = Cadence::from_interval(intervals[intervals.len() / 2])?;
+
+            let (shortest, longest) = cadence.interval();
+            let regular_intervals: usize = intervals
+                .iter()
+                .filter(|interval| (shortest..=longest).contains(*interval))
+                .count();
+            match regular_intervals as f64 >= MIN_REGULAR_INTERVALS * intervals.len() as f64 {
+                true => Some((cadence, payments)),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+impl DataBase {
+    /// Finds the recurring payments among the expenses: series of expenses with the same
+    /// entity, category, subcategory and currency, of similar amounts and at a weekly,
+    /// fortnightly, monthly or yearly cadence.
+    pub(crate) fn subscriptions(&self) -> Vec<Subscription> {
+        let expenses: DataFrame = self
+            .expenses_table
+            .data_frame
+            .clone()
+            .lazy()
+            .sort(["date"], Default::default())
+            .collect()
+            .unwrap();
+
+        let text = |name: &str, i: usize| -> String {
+            expenses
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(i)
+                .unwrap()
+                .to_string()
+        };
+        let id = |name: &str, i: usize| -> i64 {
+            expenses
+                .column(name)
+                .unwrap()
+                .i64()
+                .unwrap()
+                .get(i)
+                .unwrap()
+        };
+        let dates: Vec<NaiveDate> = expenses
+            .column("date")
+            .unwrap()
+            .date()
+            .unwrap()
+            .as_date_iter()
+            .map(|date| date.unwrap())
+            .collect();
+
+        let mut groups: BTreeMap<(i64, String, String, String), Vec<Payment>> = BTreeMap::new();
+        for (i, date) in dates.into_iter().enumerate() {
+            groups
+                .entry((
+                    id("entity_id", i),
+                    text("category", i),
+                    text("subcategory", i),
+                    text("currency", i),
+                ))
+                .or_default()
+                .push((
+                    date,
+                    expenses
+                        .column("value")
+                        .unwrap()
+                        .f64()
+                        .unwrap()
+                        .get(i)
+                        .unwrap(),
+                    id("party_id", i),
+                ));
+        }
+
+        groups
+            .into_iter()
+            .flat_map(|((entity_id, category, subcategory, currency), payments)| {
+                detect_series(&payments)
+                    .into_iter()
+                    .map(move |(cadence, payments)| Subscription {
+                        entity_id,
+                        category: category.clone(),
+                        subcategory: subcategory.clone(),
+                        currency: Currency::from_str(currency.as_str())
+                            .expect("Failed to find currency"),
+                        cadence,
+                        payments,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the detected subscriptions, the active ones at date first, each in its own
+    /// currency.
+    pub(crate) fn subscriptions_report_data_frame(&self, date: NaiveDate) -> DataFrame {
+        let subscriptions: Vec<Subscription> = self.subscriptions();
+
+        let price_changes = |subscription: &Subscription| -> String {
+            subscription
+                .price_changes()
+                .iter()
+                .map(|(date, before, after)| format!("{}: {:.2} -> {:.2}", date, before, after))
+                .collect::<Vec<String>>()
+                .join("; ")
+        };
+
+        df!(
+            "Entity" => subscriptions.iter().map(|subscription| self.entity(subscription.entity_id).name()).collect::<Vec<String>>(),
+            "Category" => subscriptions.iter().map(|subscription| subscription.category.clone()).collect::<Vec<String>>(),
+            "Subcategory" => subscriptions.iter().map(|subscription| subscription.subcategory.clone()).collect::<Vec<String>>(),
+            "Currency" => subscriptions.iter().map(|subscription| subscription.currency.to_string()).collect::<Vec<String>>(),
+            "Cadence" => subscriptions.iter().map(|subscription| subscription.cadence.to_string()).collect::<Vec<String>>(),
+            "Payments" => subscriptions.iter().map(|subscription| subscription.payments.len() as i64).collect::<Vec<i64>>(),
+            "First Payment" => subscriptions.iter().map(|subscription| subscription.payments[0].0.to_string()).collect::<Vec<String>>(),
+            "Last Payment" => subscriptions.iter().map(|subscription| subscription.last_payment().0.to_string()).collect::<Vec<String>>(),
+            "Average" => subscriptions.iter().map(|subscription| subscription.average()).collect::<Vec<f64>>(),
+            "Last Amount" => subscriptions.iter().map(|subscription| subscription.last_payment().1).collect::<Vec<f64>>(),
+            "Price Changes" => subscriptions.iter().map(price_changes).collect::<Vec<String>>(),
+            "Annualized Cost" => subscriptions.iter().map(|subscription| subscription.annualized_cost()).collect::<Vec<f64>>(),
+            "Active" => subscriptions.iter().map(|subscription| match subscription.is_active(date) {
+                true => String::from("Yes"),
+                false => String::from("No"),
+            }).collect::<Vec<String>>()
+        )
+        .expect("Failed to report subscriptions")
+        .lazy()
+        .with_columns([
+            col("Average").round(2),
+            col("Last Amount").round(2),
+            col("Annualized Cost").round(2),
+        ])
+        .sort(
+            ["Active", "Entity"],
+            SortMultipleOptions::default().with_order_descending_multi([true, false]),
+        )
+        .collect()
+        .unwrap()
+    }
+
+    /// Recurring party entering the next payments of a subscription at its last price, from
+    /// the account that paid the last one. The first occurrence is the one after the last
+    /// payment. Fails if no debit in the currency of the subscription paid the last one.
+    pub(crate) fn subscription_template(
+        &self,
+        subscription: &Subscription,
+    ) -> Result<Template, String> {
+        let (last_date, last_value) = subscription.last_payment();
+        let (_date, _value, last_party_id) = *subscription.payments.last().unwrap();
+        let entity_name: String = self.entity(subscription.entity_id).name();
+        let account_id: i64 = self
+            .funds_table
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(
+                col("party_id")
+                    .eq(lit(last_party_id))
+                    .and(col("fund_movement_type").eq(lit("Debit")))
+                    .and(col("currency").eq(lit(subscription.currency.to_string()))),
+            )
+            .collect()
+            .unwrap()
+            .column("account_id")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .next()
+            .flatten()
+            .ok_or(format!(
+                "The last payment to {} on {} has no debit in {}",
+                entity_name, last_date, subscription.currency
+            ))?;
+        let recurrence: Recurrence = subscription.cadence.recurrence(last_date);
+
+        Ok(Template {
+            name: format!("{} ({})", entity_name, subscription.subcategory),
+            transactions: vec![
+                Transaction::Expense {
+                    value: last_value,
+                    currency: subscription.currency.clone(),
+                    date: last_date,
+                    category: subscription.category.clone(),
+                    subcategory: subscription.subcategory.clone(),
+                    description: format!("{} subscription", entity_name),
+                    entity_id: subscription.entity_id,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value: last_value,
+                    currency: subscription.currency.clone(),
+                    date: last_date,
+                    account_id,
+                },
+            ],
+            start_date: recurrence.following(last_date),
+            recurrence,
+            end_date: None,
+        })
+    }
+}
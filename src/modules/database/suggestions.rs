@@ -0,0 +1,249 @@
+use crate::modules::database::DataBase;
+use crate::modules::financial::TransactionType;
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::iter::zip;
+
+/// What is known about a transaction while it is still being typed in. Every field is a hint,
+/// the model simply ignores the ones that are not set yet.
+pub(crate) struct SuggestionContext {
+    pub(crate) entity_id: Option<i64>,
+    pub(crate) description: String,
+    pub(crate) value: Option<f64>,
+    pub(crate) date: Option<NaiveDate>,
+}
+
+/// Naive Bayes classifier that predicts the (category, subcategory) pair of an income or an
+/// expense out of its entity, the words in its description, the order of magnitude of its
+/// value and the weekday in which it happened. It is trained on the records already in the
+/// database, so it gets better the more transactions are entered.
+#[derive(Default)]
+pub(crate) struct SuggestionModel {
+    transaction_type: Option<TransactionType>,
+    class_counts: HashMap<(String, String), f64>,
+    feature_counts: HashMap<(String, String), HashMap<String, f64>>,
+    class_feature_totals: HashMap<(String, String), f64>,
+    vocabulary_size: f64,
+    total_count: f64,
+}
+
+/// Splits the context of a transaction into the discrete features used by the model.
+fn features(
+    entity_id: Option<i64>,
+    description: &str,
+    value: Option<f64>,
+    date: Option<NaiveDate>,
+) -> Vec<String> {
+    let mut features: Vec<String> = Vec::new();
+
+    if let Some(entity_id) = entity_id {
+        features.push(format!("entity:{}", entity_id));
+    }
+
+    for word in description
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+    {
+        features.push(format!("word:{}", word));
+    }
+
+    if let Some(value) = value {
+        // half orders of magnitude: 1-3, 3-10, 10-31, 31-100, ...
+        let bucket: i64 = if value > 0.0 {
+            (2.0 * value.log10()).floor() as i64
+        } else {
+            i64::MIN
+        };
+        features.push(format!("amount:{}", bucket));
+    }
+
+    if let Some(date) = date {
+        features.push(format!("weekday:{}", date.weekday()));
+    }
+
+    features
+}
+
+impl SuggestionModel {
+    /// Fits the model on a table with the columns of the income or the expense table.
+    pub(crate) fn train(transaction_type: &TransactionType, data_frame: &DataFrame) -> Self {
+        let mut model: SuggestionModel = SuggestionModel {
+            transaction_type: Some(transaction_type.clone()),
+            ..Default::default()
+        };
+
+        let categories = data_frame.column("category").unwrap().str().unwrap();
+        let subcategories = data_frame.column("subcategory").unwrap().str().unwrap();
+        let descriptions = data_frame.column("description").unwrap().str().unwrap();
+        let entity_ids = data_frame.column("entity_id").unwrap().i64().unwrap();
+        let values = data_frame
+            .column("value")
+            .unwrap()
+            .cast(&DataType::Float64)
+            .unwrap();
+        let values = values.f64().unwrap();
+        let dates = data_frame.column("date").unwrap().date().unwrap();
+
+        let mut vocabulary: HashSet<String> = HashSet::new();
+        let records = zip(
+            zip(
+                zip(categories, subcategories),
+                zip(descriptions, entity_ids),
+            ),
+            zip(values, dates.as_date_iter()),
+        );
+        for (((category, subcategory), (description, entity_id)), (value, date)) in records {
+            let class: (String, String) = (
+                category.unwrap_or_default().to_string(),
+                subcategory.unwrap_or_default().to_string(),
+            );
+
+            *model.class_counts.entry(class.clone()).or_insert(0.0) += 1.0;
+            model.total_count += 1.0;
+
+            for feature in features(entity_id, description.unwrap_or_default(), value, date) {
+                *model
+                    .feature_counts
+                    .entry(class.clone())
+                    .or_default()
+                    .entry(feature.clone())
+                    .or_insert(0.0) += 1.0;
+                *model
+                    .class_feature_totals
+                    .entry(class.clone())
+                    .or_insert(0.0) += 1.0;
+                vocabulary.insert(feature);
+            }
+        }
+        model.vocabulary_size = vocabulary.len() as f64;
+
+        model
+    }
+
+    /// Whether the model has been fitted on the records of the given transaction type.
+    pub(crate) fn is_trained_for(&self, transaction_type: &TransactionType) -> bool {
+        self.transaction_type.as_ref() == Some(transaction_type)
+    }
+
+    /// Returns the posterior probability of every (category, subcategory) pair seen during
+    /// training, given the context of the transaction. Compute them once and rank both the
+    /// categories and the subcategories out of them.
+    pub(crate) fn posteriors(&self, context: &SuggestionContext) -> HashMap<(String, String), f64> {
+        let features: Vec<String> = features(
+            context.entity_id,
+            context.description.as_str(),
+            context.value,
+            context.date,
+        );
+
+        let log_scores: HashMap<(String, String), f64> = self
+            .class_counts
+            .iter()
+            .map(|(class, class_count)| {
+                let class_feature_total: f64 =
+                    *self.class_feature_totals.get(class).unwrap_or(&0.0);
+                let class_feature_counts = self.feature_counts.get(class);
+
+                let mut log_score: f64 = (class_count / self.total_count).ln();
+                for feature in features.iter() {
+                    // Laplace smoothing, so that unseen features do not rule out a class
+                    let feature_count: f64 = class_feature_counts
+                        .and_then(|counts| counts.get(feature))
+                        .cloned()
+                        .unwrap_or(0.0);
+                    log_score += ((feature_count + 1.0)
+                        / (class_feature_total + self.vocabulary_size + 1.0))
+                        .ln();
+                }
+
+                (class.clone(), log_score)
+            })
+            .collect();
+
+        let max_log_score: f64 = log_scores
+            .values()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let normalizer: f64 = log_scores
+            .values()
+            .map(|log_score| (log_score - max_log_score).exp())
+            .sum();
+
+        log_scores
+            .into_iter()
+            .map(|(class, log_score)| (class, (log_score - max_log_score).exp() / normalizer))
+            .collect()
+    }
+
+    /// Returns the most likely (category, subcategory) pair, if the model has seen any record.
+    pub(crate) fn most_likely(&self, context: &SuggestionContext) -> Option<(String, String)> {
+        self.posteriors(context)
+            .into_iter()
+            .max_by(|(class_a, a), (class_b, b)| {
+                a.partial_cmp(b).unwrap().then_with(|| class_b.cmp(class_a))
+            })
+            .map(|(class, _probability)| class)
+    }
+
+    /// Sorts the categories from most to least likely. Ties are broken alphabetically.
+    pub(crate) fn rank_categories(
+        posteriors: &HashMap<(String, String), f64>,
+        categories: Vec<String>,
+    ) -> Vec<String> {
+        let mut probabilities: HashMap<String, f64> = HashMap::new();
+        for ((category, _subcategory), probability) in posteriors {
+            *probabilities.entry(category.clone()).or_insert(0.0) += probability;
+        }
+
+        Self::rank(categories, &probabilities)
+    }
+
+    /// Sorts the subcategories of a category from most to least likely. Ties are broken
+    /// alphabetically.
+    pub(crate) fn rank_subcategories(
+        posteriors: &HashMap<(String, String), f64>,
+        category: &str,
+        subcategories: Vec<String>,
+    ) -> Vec<String> {
+        let probabilities: HashMap<String, f64> = posteriors
+            .iter()
+            .filter(|((class_category, _), _)| class_category == category)
+            .map(|((_, subcategory), probability)| (subcategory.clone(), *probability))
+            .collect();
+
+        Self::rank(subcategories, &probabilities)
+    }
+
+    fn rank(mut items: Vec<String>, probabilities: &HashMap<String, f64>) -> Vec<String> {
+        items.sort_by(|a, b| {
+            let probability_a: f64 = *probabilities.get(a).unwrap_or(&0.0);
+            let probability_b: f64 = *probabilities.get(b).unwrap_or(&0.0);
+            probability_b
+                .partial_cmp(&probability_a)
+                .unwrap()
+                .then_with(|| a.cmp(b))
+        });
+
+        items
+    }
+}
+
+impl DataBase {
+    /// Trains a suggestion model on the incomes or the expenses of the database.
+    pub(crate) fn suggestion_model(&self, transaction_type: &TransactionType) -> SuggestionModel {
+        match transaction_type {
+            TransactionType::Income => {
+                SuggestionModel::train(transaction_type, &self.incomes_table.data_frame)
+            }
+            TransactionType::Expense => {
+                SuggestionModel::train(transaction_type, &self.expenses_table.data_frame)
+            }
+            _ => SuggestionModel {
+                transaction_type: Some(transaction_type.clone()),
+                ..Default::default()
+            },
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use crate::modules::currency_exchange::CurrencyExchange;
+use crate::modules::database::settings::PeriodDefinition;
 use crate::modules::database::{capitalize_every_word, data_frame_to_csv_string, DataBase};
-use crate::modules::financial::Currency;
-use chrono::{Local, NaiveDate};
+use crate::modules::financial::{Currency, TransactionType};
+use crate::modules::price_index::RealTerms;
+use chrono::{Local, Months, NaiveDate};
 use polars::prelude::pivot::pivot_stable;
 use polars::prelude::*;
 use std::fmt::Display;
@@ -46,17 +48,306 @@ impl Default for TimeUnit {
     }
 }
 
+/// Exchange rates at which a fund stand is converted to a single currency.
+#[derive(Debug, Default, Hash, PartialEq, Eq, EnumIter, Clone, EnumString)]
+pub(crate) enum Valuation {
+    /// Rates of the date of the fund stand
+    #[default]
+    Historical,
+    /// Rates of today
+    Current,
+}
+
+// Conversion to string
+impl Display for Valuation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Valuation::Historical => "Historical".to_string(),
+            Valuation::Current => "Current".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Level at which the accounts of a fund stand are aggregated.
+#[derive(Debug, Default, Hash, PartialEq, Eq, EnumIter, Clone, EnumString)]
+pub(crate) enum FundGrouping {
+    #[default]
+    Account,
+    AccountType,
+    Country,
+}
+
+impl FundGrouping {
+    /// Columns of the account table identifying a group.
+    pub(crate) fn columns(&self) -> Vec<&str> {
+        match self {
+            FundGrouping::Account => vec!["name", "country", "account_type"],
+            FundGrouping::AccountType => vec!["account_type"],
+            FundGrouping::Country => vec!["country"],
+        }
+    }
+}
+
+// Conversion to string
+impl Display for FundGrouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            FundGrouping::Account => "Account".to_string(),
+            FundGrouping::AccountType => "Account type".to_string(),
+            FundGrouping::Country => "Country".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Pairs of periods usually compared with each other.
+#[derive(Debug, Default, Hash, PartialEq, Eq, EnumIter, Clone, EnumString)]
+pub(crate) enum ComparisonPeriods {
+    /// From the first of the month until today, against the same days of the previous month
+    #[default]
+    MonthToDate,
+    /// The previous month, against the month before it
+    LastMonth,
+    /// From the first of the year until today, against the same days of the previous year
+    YearToDate,
+}
+
+impl ComparisonPeriods {
+    /// Returns the previous and the current period, as (date_from, date_to), relative to today.
+    /// Months and years start as set in the period definition.
+    pub(crate) fn periods(
+        &self,
+        today: NaiveDate,
+        period_definition: &PeriodDefinition,
+    ) -> ((NaiveDate, NaiveDate), (NaiveDate, NaiveDate)) {
+        let month_start: NaiveDate = period_definition.month_start(today);
+        match self {
+            ComparisonPeriods::MonthToDate => (
+                (month_start - Months::new(1), today - Months::new(1)),
+                (month_start, today),
+            ),
+            ComparisonPeriods::LastMonth => (
+                (
+                    month_start - Months::new(2),
+                    month_start - Months::new(1) - chrono::Duration::days(1),
+                ),
+                (
+                    month_start - Months::new(1),
+                    month_start - chrono::Duration::days(1),
+                ),
+            ),
+            ComparisonPeriods::YearToDate => {
+                let year_start: NaiveDate = period_definition.year_start(today);
+                (
+                    (year_start - Months::new(12), today - Months::new(12)),
+                    (year_start, today),
+                )
+            }
+        }
+    }
+}
+
+// Conversion to string
+impl Display for ComparisonPeriods {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ComparisonPeriods::MonthToDate => "This month vs last month".to_string(),
+            ComparisonPeriods::LastMonth => "Last month vs the month before".to_string(),
+            ComparisonPeriods::YearToDate => "This year vs last year, to date".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Dimension by which the expenses summary can be grouped.
+#[derive(Debug, Hash, PartialEq, Eq, EnumIter, Clone, EnumString)]
+pub(crate) enum SummaryDimension {
+    Category,
+    Subcategory,
+    Entity,
+    EntityType,
+    EntityCountry,
+    Currency,
+    Month,
+    Weekday,
+    FundingAccount,
+}
+
+impl SummaryDimension {
+    /// Value of the dimension for every expense, as a string. Needs the expenses table joined
+    /// with the entity names, types and countries, and with the funding accounts.
+    fn label(&self, period_definition: &PeriodDefinition) -> Expr {
+        match self {
+            SummaryDimension::Category => col("category"),
+            SummaryDimension::Subcategory => col("subcategory"),
+            SummaryDimension::Entity => col("entity_name").fill_null(lit("Unknown")),
+            SummaryDimension::EntityType => col("entity_type").fill_null(lit("Unknown")),
+            SummaryDimension::EntityCountry => col("entity_country").fill_null(lit("Unknown")),
+            SummaryDimension::Currency => col("currency"),
+            SummaryDimension::Month => {
+                // Months are labelled by their first day, unless they are calendar months
+                let offset: i32 = period_definition.month_start_day as i32 - 1;
+                let format: &str = match offset {
+                    0 => "%Y-%m",
+                    _ => "%Y-%m-%d",
+                };
+                let shift = |date: Expr, days: i32| -> Expr {
+                    (date.cast(DataType::Int32) + lit(days)).cast(DataType::Date)
+                };
+                shift(
+                    shift(col("date"), -offset).dt().truncate(lit("1mo")),
+                    offset,
+                )
+                .dt()
+                .strftime(format)
+            }
+            SummaryDimension::Weekday => {
+                let weekday: Expr = col("date").dt().weekday();
+                when(weekday.clone().eq(lit(1)))
+                    .then(lit("Monday"))
+                    .when(weekday.clone().eq(lit(2)))
+                    .then(lit("Tuesday"))
+                    .when(weekday.clone().eq(lit(3)))
+                    .then(lit("Wednesday"))
+                    .when(weekday.clone().eq(lit(4)))
+                    .then(lit("Thursday"))
+                    .when(weekday.clone().eq(lit(5)))
+                    .then(lit("Friday"))
+                    .when(weekday.eq(lit(6)))
+                    .then(lit("Saturday"))
+                    .otherwise(lit("Sunday"))
+            }
+            SummaryDimension::FundingAccount => col("funding_account").fill_null(lit("None")),
+        }
+    }
+
+    /// String by which the values of the dimension are sorted.
+    fn sort_key(&self, period_definition: &PeriodDefinition) -> Expr {
+        match self {
+            SummaryDimension::Weekday => col("date").dt().weekday().cast(DataType::String),
+            _ => self.label(period_definition),
+        }
+    }
+}
+
+// Conversion to string
+impl Display for SummaryDimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            SummaryDimension::Category => "Category".to_string(),
+            SummaryDimension::Subcategory => "Subcategory".to_string(),
+            SummaryDimension::Entity => "Entity".to_string(),
+            SummaryDimension::EntityType => "Entity Type".to_string(),
+            SummaryDimension::EntityCountry => "Entity Country".to_string(),
+            SummaryDimension::Currency => "Currency".to_string(),
+            SummaryDimension::Month => "Month".to_string(),
+            SummaryDimension::Weekday => "Weekday".to_string(),
+            SummaryDimension::FundingAccount => "Funding Account".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Level at which the expenses are aggregated by the entity that received them.
+#[derive(Debug, Default, Hash, PartialEq, Eq, EnumIter, Clone, EnumString)]
+pub(crate) enum EntityGrouping {
+    #[default]
+    Entity,
+    EntityType,
+    EntitySubtype,
+    Country,
+}
+
+impl EntityGrouping {
+    /// Columns of the entity table identifying a group.
+    pub(crate) fn columns(&self) -> Vec<&str> {
+        match self {
+            EntityGrouping::Entity => vec!["entity_id", "name"],
+            EntityGrouping::EntityType => vec!["entity_type"],
+            EntityGrouping::EntitySubtype => vec!["entity_type", "entity_subtype"],
+            EntityGrouping::Country => vec!["country"],
+        }
+    }
+}
+
+// Conversion to string
+impl Display for EntityGrouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            EntityGrouping::Entity => "Entity".to_string(),
+            EntityGrouping::EntityType => "Entity type".to_string(),
+            EntityGrouping::EntitySubtype => "Entity subtype".to_string(),
+            EntityGrouping::Country => "Country".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Multiplies the exchange rate of every row of the data_frame by the deflator of its date, so
+/// that the converted amounts are in real terms, if asked for.
+fn deflate(
+    exchange_rates: Vec<f64>,
+    data_frame: &DataFrame,
+    real_terms: Option<&RealTerms>,
+) -> Result<Vec<f64>, String> {
+    match real_terms {
+        Some(real_terms) => {
+            let dates = data_frame
+                .column("date")
+                .unwrap()
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .map(|date| date.unwrap());
+            Ok(exchange_rates
+                .into_iter()
+                .zip(real_terms.deflators(dates)?)
+                .map(|(exchange_rate, deflator)| exchange_rate * deflator)
+                .collect())
+        }
+        None => Ok(exchange_rates),
+    }
+}
+
+/// Exchange rate from the currency of every row of the data_frame to currency_to at
+/// exchange_date, deflated by the date of the row in real terms, as an exchange_rate column.
+fn exchange_rates_to(
+    currency_to: &Currency,
+    data_frame: &DataFrame,
+    exchange_date: NaiveDate,
+    real_terms: Option<&RealTerms>,
+) -> Result<Series, String> {
+    let currency_exchange: CurrencyExchange = CurrencyExchange::init_for(currency_to, data_frame);
+    let exchange_rates: Vec<f64> = data_frame
+        .column("currency")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .map(|currency| {
+            let currency_from =
+                Currency::from_str(currency.unwrap()).expect("Failed to find currency");
+            currency_exchange.exchange_currency(&currency_from, currency_to, exchange_date)
+        })
+        .collect();
+
+    Ok(Series::new(
+        "exchange_rate".into(),
+        deflate(exchange_rates, data_frame, real_terms)?,
+    ))
+}
+
 impl DataBase {
     /// Calculates the sum of all the incomes earned between date_from to date_to, both included,
-    /// in the currency currency_to.
+    /// in the currency currency_to, and in real terms if asked for.
     fn total_income(
         &self,
         date_from: NaiveDate,
         date_to: NaiveDate,
         currency_to: &Currency,
-    ) -> f64 {
-        let currency_exchange: CurrencyExchange = CurrencyExchange::init();
-
+        real_terms: Option<&RealTerms>,
+    ) -> Result<f64, String> {
         let income_table: DataFrame = self
             .incomes_table
             .data_frame
@@ -66,24 +357,10 @@ impl DataBase {
             .collect()
             .unwrap();
 
-        let mut exchange_rates = Vec::new();
-        let currency_iterator = income_table
-            .column("currency")
-            .unwrap()
-            .str()
-            .unwrap()
-            .into_iter();
-        for currency in currency_iterator {
-            let currency_from =
-                Currency::from_str(currency.unwrap()).expect("Failed to find currency");
-            let exchange_rate: f64 =
-                currency_exchange.exchange_currency(&currency_from, currency_to, date_to);
-            exchange_rates.push(exchange_rate);
-        }
-
-        let exchange_rates: Series = Series::new("exchange_rate".into(), exchange_rates);
+        let exchange_rates: Series =
+            exchange_rates_to(currency_to, &income_table, date_to, real_terms)?;
 
-        income_table
+        Ok(income_table
             .lazy()
             .with_column(exchange_rates.lit())
             .with_column((col("exchange_rate") * col("value")).alias(currency_to.to_string()))
@@ -94,28 +371,37 @@ impl DataBase {
             .f64()
             .unwrap()
             .sum()
-            .unwrap()
+            .unwrap())
     }
 
-    pub(crate) fn current_fund_stand(&self, currency_to: Option<&Currency>) -> String {
-        let currency_exchange: CurrencyExchange = CurrencyExchange::init();
-
-        let initial_balances: DataFrame = self.account_table.data_frame.clone();
+    /// Returns the accounts created by date, both included, with an extra column total_value,
+    /// holding the initial balance plus all the fund movements up to date, in the currency of
+    /// the account.
+    pub(crate) fn account_balances_at(&self, date: NaiveDate) -> DataFrame {
+        let initial_balances: DataFrame = self
+            .account_table
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col("creation_date").lt_eq(lit(date)))
+            .collect()
+            .expect("Failed to filter accounts");
 
         let funds_table: DataFrame = self
             .funds_table
             .data_frame
             .clone()
             .lazy()
+            .filter(col("date").lt_eq(lit(date)))
             .group_by(["account_id", "currency"])
             .agg([col("value").sum()])
             .collect()
             .expect("Failed to aggregate account values");
 
-        let mut summary = initial_balances
+        initial_balances
             .lazy()
             .join(
-                funds_table.clone().lazy(),
+                funds_table.lazy(),
                 [col("account_id"), col("currency")],
                 [col("account_id"), col("currency")],
                 JoinArgs::new(JoinType::Left),
@@ -123,28 +409,59 @@ impl DataBase {
             .with_column(col("value").fill_null(0.0))
             .with_column((col("initial_balance") + col("value")).alias("total_value"))
             .collect()
-            .expect("Failed to join funds");
+            .expect("Failed to join funds")
+    }
+
+    pub(crate) fn fund_stand_at(
+        &self,
+        date: NaiveDate,
+        currency_to: Option<&Currency>,
+        valuation: &Valuation,
+        grouping: &FundGrouping,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        Ok(data_frame_to_csv_string(
+            &mut self.fund_stand_at_data_frame(
+                date,
+                currency_to,
+                valuation,
+                grouping,
+                real_terms,
+            )?,
+        ))
+    }
+
+    /// Returns the value of the accounts at the end of date, aggregated by grouping. Amounts are
+    /// either kept in the currency of each account, or converted to currency_to at the exchange
+    /// rate given by valuation. In real terms, they are deflated to the base year from date.
+    pub(crate) fn fund_stand_at_data_frame(
+        &self,
+        date: NaiveDate,
+        currency_to: Option<&Currency>,
+        valuation: &Valuation,
+        grouping: &FundGrouping,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<DataFrame, String> {
+        let group_columns: Vec<Expr> = grouping.columns().into_iter().map(col).collect();
+
+        let mut summary = self.account_balances_at(date);
+        if let Some(real_terms) = real_terms {
+            let deflator: f64 = real_terms.deflators(std::iter::once(date))?[0];
+            summary = summary
+                .lazy()
+                .with_column(col("total_value") * lit(deflator))
+                .collect()
+                .unwrap();
+        }
 
         if let Some(currency_to) = currency_to {
-            let mut exchange_rates = Vec::new();
-            let currency_iterator = summary
-                .column("currency")
-                .unwrap()
-                .str()
-                .unwrap()
-                .into_iter();
-            for currency in currency_iterator {
-                let currency_from =
-                    Currency::from_str(currency.unwrap()).expect("Failed to find currency");
-                let exchange_rate: f64 = currency_exchange.exchange_currency(
-                    &currency_from,
-                    currency_to,
-                    Local::now().date_naive(),
-                );
-                exchange_rates.push(exchange_rate);
-            }
+            let exchange_date: NaiveDate = match valuation {
+                Valuation::Historical => date,
+                Valuation::Current => Local::now().date_naive(),
+            };
 
-            let exchange_rates: Series = Series::new("exchange_rate".into(), exchange_rates);
+            let exchange_rates: Series =
+                exchange_rates_to(currency_to, &summary, exchange_date, None)?;
 
             summary = summary
                 .lazy()
@@ -152,19 +469,14 @@ impl DataBase {
                 .with_column(
                     (col("exchange_rate") * col("total_value")).alias(currency_to.to_string()),
                 )
-                .group_by(["name", "country", "account_type"])
+                .group_by(group_columns.clone())
                 .agg([col(currency_to.to_string().as_str()).sum()])
                 .sort(
                     [currency_to.to_string()],
                     SortMultipleOptions::default().with_order_descending(true),
                 )
-                .select([
-                    col("name"),
-                    col("country"),
-                    col("account_type"),
-                    col(currency_to.to_string()).round(2),
-                ])
-                .filter(col(currency_to.to_string()).gt_eq(lit(0.01)))
+                .select([group_columns, vec![col(currency_to.to_string()).round(2)]].concat())
+                .filter(col(currency_to.to_string()).abs().gt_eq(lit(0.01)))
                 .select([all().name().map(|name| {
                     Ok(PlSmallStr::from_string(capitalize_every_word(
                         name.replace("_", " "),
@@ -174,19 +486,20 @@ impl DataBase {
                 .unwrap()
         } else {
             summary = summary
-                .clone()
                 .lazy()
+                .group_by([group_columns.clone(), vec![col("currency")]].concat())
+                .agg([col("total_value").sum()])
                 .sort(
                     ["currency", "total_value"],
                     SortMultipleOptions::default().with_order_descending_multi([false, true]),
                 )
-                .select([
-                    col("name"),
-                    col("country"),
-                    col("currency"),
-                    col("account_type"),
-                    col("total_value").round(2),
-                ])
+                .select(
+                    [
+                        group_columns,
+                        vec![col("currency"), col("total_value").round(2)],
+                    ]
+                    .concat(),
+                )
                 .filter(col("total_value").abs().gt_eq(lit(0.01)))
                 .select([all().name().map(|name| {
                     Ok(PlSmallStr::from_string(capitalize_every_word(
@@ -197,7 +510,259 @@ impl DataBase {
                 .unwrap()
         }
 
-        data_frame_to_csv_string(&mut summary)
+        Ok(summary)
+    }
+
+    /// Returns the balance of every account at the end of every day, from the creation of the
+    /// account until today or its last fund movement, as columns date, account_id and balance.
+    /// Balances are either in the currency of each account, or converted to currency_to at the
+    /// exchange rate of each movement, as in funds_evolution. Only the accounts in account_ids
+    /// are included, or all of them if it is empty.
+    pub(crate) fn account_balance_history(
+        &self,
+        currency_to: Option<&Currency>,
+        account_ids: &[i64],
+    ) -> DataFrame {
+        // The initial balance of an account is its first movement.
+        let initial_balances: DataFrame = self
+            .account_table
+            .data_frame
+            .clone()
+            .lazy()
+            .select([
+                col("account_id"),
+                col("initial_balance").alias("value"),
+                col("currency"),
+                col("creation_date").alias("date"),
+            ])
+            .collect()
+            .expect("Failed to select account table");
+
+        let mut movements: DataFrame = self
+            .funds_table
+            .data_frame
+            .clone()
+            .select(["account_id", "value", "currency", "date"])
+            .expect("Failed to select funds table")
+            .vstack(&initial_balances)
+            .expect("Could not append initial balances");
+
+        if !account_ids.is_empty() {
+            let mask: BooleanChunked = movements
+                .column("account_id")
+                .unwrap()
+                .i64()
+                .unwrap()
+                .into_iter()
+                .map(|account_id| account_id.is_some_and(|id| account_ids.contains(&id)))
+                .collect();
+            movements = movements.filter(&mask).expect("Failed to filter accounts");
+        }
+
+        if let Some(currency_to) = currency_to {
+            movements = CurrencyExchange::init_for(currency_to, &movements)
+                .exchange_currencies(currency_to, movements);
+        }
+
+        // Every account is extended until the same last day, with an empty movement.
+        let last_date: NaiveDate = movements
+            .column("date")
+            .unwrap()
+            .date()
+            .unwrap()
+            .as_date_iter()
+            .flatten()
+            .max()
+            .map_or(Local::now().date_naive(), |date| {
+                date.max(Local::now().date_naive())
+            });
+
+        let mut history: DataFrame = DataFrame::empty();
+        let unique_account_ids = movements
+            .column("account_id")
+            .unwrap()
+            .unique_stable()
+            .unwrap();
+        for account_id in unique_account_ids.i64().unwrap().into_no_null_iter() {
+            let last_movement: DataFrame = df!(
+                "date" => [last_date],
+                "value" => [0.0]
+            )
+            .unwrap();
+
+            let account_history: DataFrame = movements
+                .clone()
+                .lazy()
+                .filter(col("account_id").eq(lit(account_id)))
+                .select([col("date"), col("value")])
+                .collect()
+                .expect("Failed to select movements")
+                .vstack(&last_movement)
+                .expect("Could not append last movement")
+                .lazy()
+                .group_by([col("date")])
+                .agg([col("value").sum()])
+                .sort(["date"], Default::default())
+                .collect()
+                .expect("Failed to aggregate by day")
+                .upsample::<[String; 0]>([], "date", Duration::parse("1d"))
+                .expect("Failed to expand date")
+                .fill_null(FillNullStrategy::Zero)
+                .expect("Failed to fill null values")
+                .lazy()
+                .select([
+                    col("date"),
+                    lit(account_id).cast(DataType::Int64).alias("account_id"),
+                    col("value").cum_sum(false).alias("balance"),
+                ])
+                .collect()
+                .expect("Failed to cumsum");
+
+            history = if history.is_empty() {
+                account_history
+            } else {
+                history
+                    .vstack(&account_history)
+                    .expect("Failed to append account history")
+            };
+        }
+
+        history
+    }
+
+    /// Returns one row per day and one column per group of accounts, holding their total
+    /// balance at the end of the day. Without currency_to, groups are also split by currency.
+    pub(crate) fn balance_history_data_frame(
+        &self,
+        currency_to: Option<&Currency>,
+        grouping: &FundGrouping,
+        account_ids: &[i64],
+    ) -> DataFrame {
+        let history: DataFrame = self.account_balance_history(currency_to, account_ids);
+
+        let label: Expr = match grouping {
+            FundGrouping::Account => col("name"),
+            FundGrouping::AccountType => col("account_type"),
+            FundGrouping::Country => col("country"),
+        };
+        let label: Expr = match currency_to {
+            Some(_currency_to) => label,
+            None => label + lit(" (") + col("currency") + lit(")"),
+        };
+
+        let summary: DataFrame = history
+            .lazy()
+            .join(
+                self.account_table.data_frame.clone().lazy().select([
+                    col("account_id"),
+                    col("name"),
+                    col("country"),
+                    col("currency"),
+                    col("account_type"),
+                ]),
+                [col("account_id")],
+                [col("account_id")],
+                JoinArgs::new(JoinType::Left),
+            )
+            .with_column(label.alias("group"))
+            .group_by([col("date"), col("group")])
+            .agg([col("balance").sum().round(2)])
+            .sort(["date", "group"], Default::default())
+            .collect()
+            .expect("Failed to aggregate balances");
+
+        let mut pivoted_summary: DataFrame = pivot_stable(
+            &summary,
+            ["group"],
+            Some(["date"]),
+            Some(["balance"]),
+            true,
+            None,
+            None,
+        )
+        .expect("Failed to pivot balance history")
+        .fill_null(FillNullStrategy::Zero)
+        .expect("Failed to fill null values in balance history");
+
+        pivoted_summary
+            .rename("date", PlSmallStr::from_str("Date"))
+            .expect("Failed to rename column");
+
+        pivoted_summary
+    }
+
+    pub(crate) fn fund_stand_comparison(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        valuation: &Valuation,
+        grouping: &FundGrouping,
+    ) -> String {
+        data_frame_to_csv_string(&mut self.fund_stand_comparison_data_frame(
+            date_from,
+            date_to,
+            currency_to,
+            valuation,
+            grouping,
+        ))
+    }
+
+    /// Puts the fund stands at date_from and at date_to side by side, with the absolute and
+    /// relative change between them. Rows present at only one of the dates count as zero at the
+    /// other one.
+    pub(crate) fn fund_stand_comparison_data_frame(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        valuation: &Valuation,
+        grouping: &FundGrouping,
+    ) -> DataFrame {
+        let group_columns: Vec<Expr> = grouping
+            .columns()
+            .into_iter()
+            .map(|column| col(capitalize_every_word(column.replace("_", " "))))
+            .collect();
+        let column_from: String = format!("{} {}", currency_to, date_from);
+        let column_to: String = format!("{} {}", currency_to, date_to);
+
+        let stand = |date: NaiveDate, column: &str| -> LazyFrame {
+            self.fund_stand_at_data_frame(date, Some(currency_to), valuation, grouping, None)
+                .unwrap()
+                .lazy()
+                .rename([currency_to.to_string()], [column], true)
+        };
+
+        stand(date_from, column_from.as_str())
+            .join(
+                stand(date_to, column_to.as_str()),
+                group_columns.clone(),
+                group_columns,
+                JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+            )
+            .with_columns([
+                col(column_from.as_str()).fill_null(0.0),
+                col(column_to.as_str()).fill_null(0.0),
+            ])
+            .with_column(
+                (col(column_to.as_str()) - col(column_from.as_str()))
+                    .round(2)
+                    .alias("Change"),
+            )
+            .with_column(
+                when(col(column_from.as_str()).neq(lit(0.0)))
+                    .then(col("Change") * lit(100.0) / col(column_from.as_str()).abs())
+                    .otherwise(lit(NULL))
+                    .round(2)
+                    .alias("% Change"),
+            )
+            .sort(
+                [column_to.as_str()],
+                SortMultipleOptions::default().with_order_descending(true),
+            )
+            .collect()
+            .expect("Failed to compare fund stands")
     }
 
     /// Generates a summary table of all expenses between date_from to date_to, expressed in the currency_to
@@ -206,11 +771,42 @@ impl DataBase {
         date_from: NaiveDate,
         date_to: NaiveDate,
         currency_to: &Currency,
-    ) -> String {
-        let currency_exchange: CurrencyExchange = CurrencyExchange::init();
-        let total_income: f64 = self.total_income(date_from, date_to, currency_to);
+        dimensions: &[SummaryDimension],
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        Ok(data_frame_to_csv_string(
+            &mut self.expenses_summary_data_frame(
+                date_from,
+                date_to,
+                currency_to,
+                dimensions,
+                real_terms,
+            )?,
+        ))
+    }
+
+    /// Same as expenses_summary, but as a DataFrame. Expenses are grouped by every dimension in
+    /// turn: each group is followed by its subtotal row, labelled "Total" from the first
+    /// dimension it does not split by, and the last row holds the grand total. In real terms,
+    /// every expense is deflated to the base year from its date.
+    pub(crate) fn expenses_summary_data_frame(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        dimensions: &[SummaryDimension],
+        real_terms: Option<&RealTerms>,
+    ) -> Result<DataFrame, String> {
+        let total_income: f64 = self.total_income(date_from, date_to, currency_to, real_terms)?;
         let num_days: i64 = date_to.signed_duration_since(date_from).num_days();
 
+        let mut unique_dimensions: Vec<&SummaryDimension> = vec![];
+        for dimension in dimensions {
+            if !unique_dimensions.contains(&dimension) {
+                unique_dimensions.push(dimension);
+            }
+        }
+
         let expenses_table: DataFrame = self
             .expenses_table
             .data_frame
@@ -220,44 +816,325 @@ impl DataBase {
             .collect()
             .unwrap();
 
-        let mut exchange_rates = Vec::new();
-        let currency_iterator = expenses_table
-            .column("currency")
+        let exchange_rates: Series =
+            exchange_rates_to(currency_to, &expenses_table, date_to, real_terms)?;
+
+        let entities: LazyFrame = self.entity_table.data_frame.clone().lazy().select([
+            col("entity_id"),
+            col("name").alias("entity_name"),
+            col("entity_type"),
+            col("country").alias("entity_country"),
+        ]);
+
+        // The funding account of a party is the one its largest outflow came from.
+        let funding_accounts: LazyFrame = self
+            .funds_table
+            .data_frame
+            .clone()
+            .lazy()
+            .group_by([col("party_id")])
+            .agg([col("account_id")
+                .sort_by([col("value")], Default::default())
+                .first()])
+            .join(
+                self.account_table
+                    .data_frame
+                    .clone()
+                    .lazy()
+                    .select([col("account_id"), col("name").alias("funding_account")]),
+                [col("account_id")],
+                [col("account_id")],
+                JoinArgs::new(JoinType::Left),
+            )
+            .select([col("party_id"), col("funding_account")]);
+
+        let mut labels: Vec<Expr> = vec![];
+        for (i, dimension) in unique_dimensions.iter().enumerate() {
+            labels.push(
+                dimension
+                    .label(&self.settings.periods)
+                    .alias(format!("dimension_{}", i)),
+            );
+            labels.push(
+                dimension
+                    .sort_key(&self.settings.periods)
+                    .alias(format!("sort_{}", i)),
+            );
+        }
+
+        let expenses: DataFrame = expenses_table
+            .lazy()
+            .with_column(exchange_rates.lit())
+            .with_column((col("exchange_rate") * col("value")).alias("amount"))
+            .join(
+                entities,
+                [col("entity_id")],
+                [col("entity_id")],
+                JoinArgs::new(JoinType::Left),
+            )
+            .join(
+                funding_accounts,
+                [col("party_id")],
+                [col("party_id")],
+                JoinArgs::new(JoinType::Left),
+            )
+            .with_columns(labels)
+            .collect()
+            .unwrap();
+
+        // One set of rows per level: the groups of the first level dimensions, with the rest
+        // of the dimensions labelled as Total.
+        let mut summary: DataFrame = DataFrame::empty();
+        for level in (0..=unique_dimensions.len()).rev() {
+            let mut keys: Vec<Expr> = vec![];
+            for i in 0..level {
+                keys.push(col(format!("dimension_{}", i)));
+                keys.push(col(format!("sort_{}", i)));
+            }
+
+            let mut level_rows: LazyFrame = if level == 0 {
+                expenses.clone().lazy().select([col("amount").sum()])
+            } else {
+                expenses
+                    .clone()
+                    .lazy()
+                    .group_by(keys)
+                    .agg([col("amount").sum()])
+            };
+            for i in 0..unique_dimensions.len() {
+                level_rows = level_rows.with_column(lit(i >= level).alias(format!("total_{}", i)));
+                if i >= level {
+                    level_rows = level_rows.with_columns([
+                        lit("Total").alias(format!("dimension_{}", i)),
+                        lit("").alias(format!("sort_{}", i)),
+                    ]);
+                }
+            }
+
+            let mut columns: Vec<Expr> = vec![];
+            for i in 0..unique_dimensions.len() {
+                columns.push(col(format!("dimension_{}", i)));
+                columns.push(col(format!("sort_{}", i)));
+                columns.push(col(format!("total_{}", i)));
+            }
+            columns.push(col("amount"));
+            let level_rows: DataFrame = level_rows.select(columns).collect().unwrap();
+
+            summary = if summary.is_empty() {
+                level_rows
+            } else {
+                summary.vstack(&level_rows).unwrap()
+            };
+        }
+
+        let mut sort_columns: Vec<String> = vec![];
+        for i in 0..unique_dimensions.len() {
+            sort_columns.push(format!("total_{}", i));
+            sort_columns.push(format!("sort_{}", i));
+        }
+        let total_expenses: f64 = expenses
+            .column("amount")
             .unwrap()
-            .str()
+            .f64()
             .unwrap()
-            .into_iter();
-        for currency in currency_iterator {
-            let currency_from =
-                Currency::from_str(currency.unwrap()).expect("Failed to find currency");
-            let exchange_rate: f64 =
-                currency_exchange.exchange_currency(&currency_from, currency_to, date_to);
-            exchange_rates.push(exchange_rate);
-        }
+            .sum()
+            .unwrap_or(0.0);
 
-        let exchange_rates: Series = Series::new("exchange_rate".into(), exchange_rates);
+        let mut output_columns: Vec<Expr> = unique_dimensions
+            .iter()
+            .enumerate()
+            .map(|(i, dimension)| col(format!("dimension_{}", i)).alias(dimension.to_string()))
+            .collect();
+        output_columns.extend([
+            col("amount").round(2).alias(currency_to.to_string()),
+            (col("amount") / lit(num_days))
+                .round(2)
+                .alias(format!("{} / Day", currency_to).as_str()),
+            (col("amount") * lit(100) / lit(total_expenses))
+                .round(2)
+                .alias("% Total Expenses"),
+            (col("amount") * lit(100) / lit(total_income))
+                .round(2)
+                .alias("% Total Income"),
+        ]);
 
-        let mut summary: DataFrame = expenses_table
+        Ok(summary
+            .lazy()
+            .sort(sort_columns, Default::default())
+            .select(output_columns)
+            .collect()
+            .unwrap())
+    }
+
+    /// Compares the expenses of two periods, each given as (date_from, date_to), per category
+    /// and subcategory, expressed in the currency_to.
+    pub(crate) fn expenses_comparison(
+        &self,
+        previous: (NaiveDate, NaiveDate),
+        current: (NaiveDate, NaiveDate),
+        currency_to: &Currency,
+        threshold: f64,
+    ) -> String {
+        data_frame_to_csv_string(&mut self.expenses_comparison_data_frame(
+            previous,
+            current,
+            currency_to,
+            threshold,
+        ))
+    }
+
+    /// Same as expenses_comparison, but as a DataFrame. Both periods are summarized as in
+    /// expenses_summary, subtotals included, and joined on category and subcategory, so that
+    /// the "Total" rows of a category, and the grand total, line up with each other. Rows whose
+    /// expenses grew by at least threshold percent, or which had no expenses in the previous
+    /// period, are flagged as significant increases.
+    pub(crate) fn expenses_comparison_data_frame(
+        &self,
+        previous: (NaiveDate, NaiveDate),
+        current: (NaiveDate, NaiveDate),
+        currency_to: &Currency,
+        threshold: f64,
+    ) -> DataFrame {
+        let dimensions: [SummaryDimension; 2] =
+            [SummaryDimension::Category, SummaryDimension::Subcategory];
+        let group_columns: Vec<Expr> = dimensions
+            .iter()
+            .map(|dimension| col(dimension.to_string()))
+            .collect();
+        let column_previous: String = format!("{} {} to {}", currency_to, previous.0, previous.1);
+        let column_current: String = format!("{} {} to {}", currency_to, current.0, current.1);
+
+        let summary = |period: (NaiveDate, NaiveDate), column: &str| -> LazyFrame {
+            self.expenses_summary_data_frame(period.0, period.1, currency_to, &dimensions, None)
+                .unwrap()
+                .lazy()
+                .select([
+                    col("Category"),
+                    col("Subcategory"),
+                    col(currency_to.to_string()).alias(column),
+                ])
+        };
+
+        summary(previous, column_previous.as_str())
+            .join(
+                summary(current, column_current.as_str()),
+                group_columns.clone(),
+                group_columns,
+                JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+            )
+            .with_columns([
+                col(column_previous.as_str()).fill_null(0.0),
+                col(column_current.as_str()).fill_null(0.0),
+            ])
+            .with_column(
+                (col(column_current.as_str()) - col(column_previous.as_str()))
+                    .round(2)
+                    .alias("Change"),
+            )
+            .with_column(
+                when(col(column_previous.as_str()).neq(lit(0.0)))
+                    .then(col("Change") * lit(100.0) / col(column_previous.as_str()))
+                    .otherwise(lit(NULL))
+                    .round(2)
+                    .alias("% Change"),
+            )
+            .with_column(
+                when(
+                    col("% Change")
+                        .gt_eq(lit(threshold))
+                        .or(col(column_previous.as_str())
+                            .eq(lit(0.0))
+                            .and(col(column_current.as_str()).gt(lit(0.0)))),
+                )
+                .then(lit("Yes"))
+                .otherwise(lit("No"))
+                .alias("Significant Increase"),
+            )
+            // Same order as in the expenses summary, with the subtotals after their group
+            .sort_by_exprs(
+                [
+                    col("Category").eq(lit("Total")),
+                    col("Category"),
+                    col("Subcategory").eq(lit("Total")),
+                    col("Subcategory"),
+                ],
+                Default::default(),
+            )
+            .collect()
+            .expect("Failed to compare expenses")
+    }
+
+    /// Generates a summary table of all incomes between date_from to date_to, expressed in the
+    /// currency_to
+    pub(crate) fn income_summary(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        Ok(data_frame_to_csv_string(
+            &mut self.income_summary_data_frame(date_from, date_to, currency_to, real_terms)?,
+        ))
+    }
+
+    /// Same as income_summary, but as a DataFrame whose last row holds the totals. Incomes are
+    /// broken down by category, subcategory and paying entity. In real terms, every income is
+    /// deflated to the base year from its date.
+    pub(crate) fn income_summary_data_frame(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<DataFrame, String> {
+        // A single day still divides by one
+        let num_days: i64 = date_to.signed_duration_since(date_from).num_days().max(1);
+
+        let income_table: DataFrame = self
+            .incomes_table
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col("date").is_between(lit(date_from), lit(date_to), ClosedInterval::Both))
+            .collect()
+            .unwrap();
+
+        let exchange_rates: Series =
+            exchange_rates_to(currency_to, &income_table, date_to, real_terms)?;
+
+        let entities: LazyFrame = self
+            .entity_table
+            .data_frame
+            .clone()
+            .lazy()
+            .select([col("entity_id"), col("name").alias("entity")]);
+
+        let summary: DataFrame = income_table
             .lazy()
             .with_column(exchange_rates.lit())
             .with_column((col("exchange_rate") * col("value")).alias(currency_to.to_string()))
-            .group_by([col("category"), col("subcategory")])
+            .join(
+                entities,
+                [col("entity_id")],
+                [col("entity_id")],
+                JoinArgs::new(JoinType::Left),
+            )
+            .with_column(col("entity").fill_null(lit("Unknown")))
+            .group_by([col("category"), col("subcategory"), col("entity")])
             .agg([col(currency_to.to_string()).sum()])
             .with_columns([
                 col(currency_to.to_string()).round(2),
                 (col(currency_to.to_string()) / lit(num_days))
                     .round(2)
-                    .alias(format!("{}_/_day", currency_to.to_string()).as_str()),
+                    .alias(format!("{}_/_day", currency_to).as_str()),
                 (col(currency_to.to_string()) * lit(100) / col(currency_to.to_string()).sum())
-                    .round(2)
-                    .alias("%_total_expenses"),
-                (col(currency_to.to_string()) * lit(100) / lit(total_income))
                     .round(2)
                     .alias("%_total_income"),
             ])
             .sort(
-                ["category", "subcategory"],
-                SortMultipleOptions::default().with_order_descending_multi([false, false]),
+                ["category", "subcategory", "entity"],
+                SortMultipleOptions::default().with_order_descending_multi([false, false, false]),
             )
             .select([all().name().map(|name| {
                 Ok(PlSmallStr::from_string(capitalize_every_word(
@@ -267,77 +1144,254 @@ impl DataBase {
             .collect()
             .unwrap();
 
-        let total_expenses: f64 = summary
+        let total_income: f64 = summary
             .column(currency_to.to_string().as_str())
             .unwrap()
             .f64()
             .unwrap()
             .sum()
-            .unwrap();
+            .unwrap_or(0.0);
 
         let last_row: DataFrame = df!(
         "Category" => ["Total"],
         "Subcategory" => ["Total"],
-        currency_to.to_string().as_str() => [(100.0 * total_expenses).round() / 100.0],
-        format!("{} / Day", currency_to.to_string()).as_str() => [(100.0 * total_expenses / num_days as f64).round() / 100.0],
-        "% Total Expenses" => [100.0],
-        "% Total Income" => [(100.0 * 100.0 * total_expenses / total_income).round() / 100.0]
+        "Entity" => ["Total"],
+        currency_to.to_string().as_str() => [(100.0 * total_income).round() / 100.0],
+        format!("{} / Day", currency_to).as_str() => [(100.0 * total_income / num_days as f64).round() / 100.0],
+        "% Total Income" => [100.0]
         )
         .unwrap();
 
-        summary = summary.vstack(&last_row).unwrap();
+        Ok(summary.vstack(&last_row).unwrap())
+    }
 
-        data_frame_to_csv_string(&mut summary)
+    pub(crate) fn entity_spending(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        grouping: &EntityGrouping,
+    ) -> String {
+        data_frame_to_csv_string(&mut self.entity_spending_data_frame(
+            date_from,
+            date_to,
+            currency_to,
+            grouping,
+        ))
     }
 
-    pub(crate) fn evolution_table(&self, currency_to: &Currency, time_unit: &TimeUnit) -> String {
-        let currency_exchange: CurrencyExchange = CurrencyExchange::init();
-        let duration: &str = time_unit.duration();
+    /// Summarizes the expenses between date_from and date_to, both included, by the entity that
+    /// received them: number of transactions, total and average amount in currency_to, first
+    /// and last purchase, and the change with respect to the previous period of the same length.
+    /// Groups without expenses between date_from and date_to are left out.
+    pub(crate) fn entity_spending_data_frame(
+        &self,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        currency_to: &Currency,
+        grouping: &EntityGrouping,
+    ) -> DataFrame {
+        let group_columns: Vec<Expr> = grouping.columns().into_iter().map(col).collect();
+        let previous_date_from: NaiveDate =
+            date_from - date_to.signed_duration_since(date_from) - chrono::Duration::days(1);
 
-        let expenses_table: DataFrame = self.expenses_table.data_frame.clone();
+        let expenses_table: DataFrame = self
+            .expenses_table
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col("date").is_between(
+                lit(previous_date_from),
+                lit(date_to),
+                ClosedInterval::Both,
+            ))
+            .collect()
+            .unwrap();
 
-        let mut exchange_rates = Vec::new();
-        let currency_iterator = expenses_table
-            .column("currency")
-            .unwrap()
-            .str()
+        let exchange_rates: Series =
+            exchange_rates_to(currency_to, &expenses_table, date_to, None).unwrap();
+
+        let current: Expr = col("date").gt_eq(lit(date_from));
+        let value: Expr = col(currency_to.to_string());
+
+        expenses_table
+            .lazy()
+            .with_column(exchange_rates.lit())
+            .with_column((col("exchange_rate") * col("value")).alias(currency_to.to_string()))
+            .join(
+                self.entity_table.data_frame.clone().lazy(),
+                [col("entity_id")],
+                [col("entity_id")],
+                JoinArgs::new(JoinType::Inner),
+            )
+            .group_by(group_columns)
+            .agg([
+                value
+                    .clone()
+                    .filter(current.clone())
+                    .count()
+                    .alias("transactions"),
+                value.clone().filter(current.clone()).sum().round(2),
+                value
+                    .clone()
+                    .filter(current.clone())
+                    .mean()
+                    .round(2)
+                    .alias("average_ticket"),
+                col("date")
+                    .filter(current.clone())
+                    .min()
+                    .alias("first_purchase"),
+                col("date")
+                    .filter(current.clone())
+                    .max()
+                    .alias("last_purchase"),
+                value
+                    .clone()
+                    .filter(current.not())
+                    .sum()
+                    .round(2)
+                    .alias("previous_period"),
+            ])
+            .filter(col("transactions").gt(lit(0)))
+            .with_column(
+                when(col("previous_period").gt(lit(0.0)))
+                    .then(
+                        (value.clone() - col("previous_period")) * lit(100.0)
+                            / col("previous_period"),
+                    )
+                    .otherwise(lit(NULL))
+                    .round(2)
+                    .alias("%_change"),
+            )
+            .sort(
+                [currency_to.to_string()],
+                SortMultipleOptions::default().with_order_descending(true),
+            )
+            .select([all().name().map(|name| {
+                Ok(PlSmallStr::from_string(capitalize_every_word(
+                    name.replace("_", " "),
+                )))
+            })])
+            .collect()
             .unwrap()
-            .into_iter();
-        for currency in currency_iterator {
-            let currency_from =
-                Currency::from_str(currency.unwrap()).expect("Failed to find currency");
-            let exchange_rate: f64 = currency_exchange.exchange_currency(
-                &currency_from,
-                currency_to,
-                Local::now().date_naive(),
-            );
-            exchange_rates.push(exchange_rate);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn evolution_table(
+        &self,
+        transaction_type: &TransactionType,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+        category: Option<&str>,
+        entity_id: Option<i64>,
+        currency: Option<&Currency>,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<String, String> {
+        let evolution: DataFrame = self.evolution_data_frame(
+            transaction_type,
+            currency_to,
+            time_unit,
+            category,
+            entity_id,
+            currency,
+            real_terms,
+        )?;
+        let time_unit_column: String = time_unit.to_string();
+
+        // The total row is labelled as such in the period column.
+        Ok(data_frame_to_csv_string(
+            &mut evolution
+                .lazy()
+                .with_column(
+                    col(time_unit_column.as_str())
+                        .cast(DataType::String)
+                        .fill_null(lit("Total")),
+                )
+                .collect()
+                .unwrap(),
+        ))
+    }
+
+    /// Returns the incomes or the expenses of every category, in currency_to, aggregated by
+    /// time_unit. If a category is given, its subcategories are shown instead. Only the
+    /// transactions with the given entity and in the given currency are included, if any. The
+    /// last column holds the total of every period, and the last row, without a period, the
+    /// total of every column.
+    /// In real terms, every transaction is deflated to the base year from its date. Fund
+    /// movements have no evolution table.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn evolution_data_frame(
+        &self,
+        transaction_type: &TransactionType,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+        category: Option<&str>,
+        entity_id: Option<i64>,
+        currency: Option<&Currency>,
+        real_terms: Option<&RealTerms>,
+    ) -> Result<DataFrame, String> {
+        let duration: &str = time_unit.duration();
+
+        let transactions_table: &DataFrame = match transaction_type {
+            TransactionType::Income => &self.incomes_table.data_frame,
+            TransactionType::Expense => &self.expenses_table.data_frame,
+            _ => return Err(format!("{} has no evolution table", transaction_type)),
+        };
+
+        let mut filter: Expr = lit(true);
+        let mut pivot_column: &str = "category";
+        if let Some(category) = category {
+            filter = filter.and(col("category").eq(lit(category)));
+            pivot_column = "subcategory";
+        }
+        if let Some(entity_id) = entity_id {
+            filter = filter.and(col("entity_id").eq(lit(entity_id)));
+        }
+        if let Some(currency) = currency {
+            filter = filter.and(col("currency").eq(lit(currency.to_string())));
+        }
+
+        let transactions_table: DataFrame = transactions_table
+            .clone()
+            .lazy()
+            .filter(filter)
+            .collect()
+            .expect("Failed to filter transactions");
+
+        let time_unit_column: PlSmallStr = PlSmallStr::from_string(time_unit.to_string());
+        if transactions_table.height() == 0 {
+            return Ok(DataFrame::new(vec![
+                Column::new_empty(time_unit_column, &DataType::Date),
+                Column::new_empty("Total".into(), &DataType::Float64),
+            ])
+            .unwrap());
         }
 
-        let exchange_rates: Series = Series::new("exchange_rate".into(), exchange_rates);
+        let exchange_rates: Series = exchange_rates_to(
+            currency_to,
+            &transactions_table,
+            Local::now().date_naive(),
+            real_terms,
+        )?;
 
-        let summary: DataFrame = expenses_table
+        let summary: DataFrame = transactions_table
             .lazy()
             .with_column(exchange_rates.lit())
             .with_column((col("exchange_rate") * col("value")).alias(currency_to.to_string()))
             .sort(["date"], Default::default())
             .group_by_dynamic(
                 col("date"),
-                [col("category")],
-                DynamicGroupOptions {
-                    every: Duration::parse(duration),
-                    period: Duration::parse(duration),
-                    offset: Duration::parse("0"),
-                    ..Default::default()
-                },
+                [col(pivot_column)],
+                self.settings.periods.group_options(time_unit),
             )
             .agg([col(currency_to.to_string()).sum().round(2)])
             .collect()
             .expect("Failed to aggregate by time period");
 
-        let mut pivoted_summary: DataFrame = pivot_stable(
+        let pivoted_summary: DataFrame = pivot_stable(
             &summary,
-            ["category"],
+            [pivot_column],
             Some(["date"]),
             Some([currency_to.to_string()]),
             true,
@@ -354,10 +1408,139 @@ impl DataBase {
         .fill_null(FillNullStrategy::Zero)
         .expect("Failed to fill null values in pivoted evolution table");
 
-        pivoted_summary
-            .rename("date", PlSmallStr::from_string(time_unit.to_string()))
+        // Sum of the columns of every period, all of them already without nulls.
+        let period_total: Expr = pivoted_summary
+            .get_column_names()
+            .into_iter()
+            .filter(|name| name.as_str() != "date")
+            .map(|name| col(name.clone()))
+            .reduce(|total, column| total + column)
+            .unwrap_or(lit(0.0));
+
+        let pivoted_summary: DataFrame = pivoted_summary
+            .lazy()
+            .with_column(period_total.round(2).alias("Total"))
+            .collect()
+            .expect("Failed to add totals column");
+
+        let totals: DataFrame = pivoted_summary
+            .clone()
+            .lazy()
+            .select([
+                lit(NULL).cast(DataType::Date).alias("date"),
+                all().exclude(["date"]).sum().round(2),
+            ])
+            .collect()
+            .expect("Failed to add totals row");
+
+        let evolution: DataFrame = pivoted_summary
+            .vstack(&totals)
+            .expect("Failed to add totals row")
+            .lazy()
+            .rename(["date"], [time_unit_column], true)
+            .collect()
+            .expect("Failed to rename column");
+
+        Ok(evolution)
+    }
+
+    /// Returns a csv in String format with the cash flow of every period of length time_unit,
+    /// in currency_to.
+    pub(crate) fn cash_flow(&self, currency_to: &Currency, time_unit: &TimeUnit) -> String {
+        data_frame_to_csv_string(&mut self.cash_flow_data_frame(currency_to, time_unit))
+    }
+
+    /// Returns, for every period of length time_unit, the total income, the total expenses, the
+    /// net savings and the savings rate, next to the change in the balance of all accounts. The
+    /// last column is the part of that change the incomes and expenses do not explain, which
+    /// should be zero unless some transaction was entered wrongly. Amounts are converted to
+    /// currency_to at the exchange rate of their date.
+    pub(crate) fn cash_flow_data_frame(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> DataFrame {
+        let duration: &str = time_unit.duration();
+
+        let mut flows: DataFrame = DataFrame::empty();
+        for (flow, data_frame) in [
+            ("income", &self.incomes_table.data_frame),
+            ("expenses", &self.expenses_table.data_frame),
+            ("funds", &self.funds_table.data_frame),
+        ] {
+            let flow_data_frame: DataFrame = data_frame
+                .clone()
+                .lazy()
+                .select([
+                    col("date"),
+                    col("currency"),
+                    col("value"),
+                    lit(flow).alias("flow"),
+                ])
+                .collect()
+                .expect("Failed to select flows");
+
+            flows = if flows.is_empty() {
+                flow_data_frame
+            } else {
+                flows
+                    .vstack(&flow_data_frame)
+                    .expect("Failed to append flows")
+            };
+        }
+
+        let flow_sum = |flow: &str| -> Expr {
+            col("value")
+                .filter(col("flow").eq(lit(flow)))
+                .sum()
+                .alias(flow)
+        };
+
+        let mut summary: DataFrame = CurrencyExchange::init_for(currency_to, &flows)
+            .exchange_currencies(currency_to, flows)
+            .lazy()
+            .sort(["date"], Default::default())
+            .group_by_dynamic(
+                col("date"),
+                [],
+                self.settings.periods.group_options(time_unit),
+            )
+            .agg([flow_sum("income"), flow_sum("expenses"), flow_sum("funds")])
+            .collect()
+            .expect("Failed to aggregate by time period")
+            .upsample::<[String; 0]>([], "date", Duration::parse(duration))
+            .expect("Failed to expand date on cash flow table")
+            .fill_null(FillNullStrategy::Zero)
+            .expect("Failed to fill null values in cash flow table")
+            .lazy()
+            .with_column((col("income") - col("expenses")).alias("net_savings"))
+            .select([
+                col("date"),
+                col("income").round(2),
+                col("expenses").round(2),
+                col("net_savings").round(2),
+                when(col("income").gt(lit(0.0)))
+                    .then(col("net_savings") * lit(100.0) / col("income"))
+                    .otherwise(lit(NULL))
+                    .round(2)
+                    .alias("%_savings_rate"),
+                col("funds").round(2).alias("funds_change"),
+                (col("funds") - col("net_savings"))
+                    .round(2)
+                    .alias("unexplained_difference"),
+            ])
+            .select([all().name().map(|name| {
+                Ok(PlSmallStr::from_string(capitalize_every_word(
+                    name.replace("_", " "),
+                )))
+            })])
+            .collect()
+            .expect("Failed to compute cash flow table");
+
+        summary
+            .rename("Date", PlSmallStr::from_string(time_unit.to_string()))
             .expect("Failed to rename column");
 
-        data_frame_to_csv_string(&mut pivoted_summary)
+        summary
     }
 }
@@ -0,0 +1,226 @@
+use crate::modules::currency_exchange::CurrencyExchange;
+use crate::modules::database::summaries::TimeUnit;
+use crate::modules::database::{data_frame_to_csv_string, DataBase};
+use crate::modules::financial::Currency;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// What is bought: category, subcategory, entity and unit. Unit prices are only compared within
+/// the same item.
+type Item = (String, String, i64, String);
+
+/// Amount spent on an item in a period, and the quantity bought.
+type Spending = (f64, f64);
+
+/// Point of the personal price index: period, index, items compared and amount spent.
+type IndexPoint = (NaiveDate, f64, usize, f64);
+
+impl DataBase {
+    /// Spending on every item bought with a quantity, in every period of time_unit, converted
+    /// to currency_to at the exchange rate of the date of each expense.
+    fn item_spending(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> BTreeMap<NaiveDate, BTreeMap<Item, Spending>> {
+        let expenses: DataFrame = self
+            .expenses_table
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col("quantity").gt(lit(0.0)).and(col("unit").is_not_null()))
+            .collect()
+            .unwrap();
+        let currency_exchange: CurrencyExchange =
+            CurrencyExchange::init_for(currency_to, &expenses);
+
+        let text = |name: &str, i: usize| -> String {
+            expenses
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(i)
+                .unwrap()
+                .to_string()
+        };
+        let number = |name: &str, i: usize| -> f64 {
+            expenses
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .get(i)
+                .unwrap()
+        };
+        let dates: Vec<NaiveDate> = expenses
+            .column("date")
+            .unwrap()
+            .date()
+            .unwrap()
+            .as_date_iter()
+            .map(|date| date.unwrap())
+            .collect();
+
+        let mut spending: BTreeMap<NaiveDate, BTreeMap<Item, Spending>> = BTreeMap::new();
+        for (i, date) in dates.into_iter().enumerate() {
+            let currency_from: Currency =
+                Currency::from_str(text("currency", i).as_str()).expect("Failed to find currency");
+            let value: f64 = number("value", i)
+                * currency_exchange.exchange_currency(&currency_from, currency_to, date);
+            let item: Item = (
+                text("category", i),
+                text("subcategory", i),
+                expenses
+                    .column("entity_id")
+                    .unwrap()
+                    .i64()
+                    .unwrap()
+                    .get(i)
+                    .unwrap(),
+                text("unit", i),
+            );
+            let (period, _end) = self.settings.periods.period(time_unit, date);
+            let item_spending = spending.entry(period).or_default().entry(item).or_default();
+            item_spending.0 += value;
+            item_spending.1 += number("quantity", i);
+        }
+
+        spending
+    }
+
+    /// Returns a csv in String format with the unit price of every item bought with a quantity.
+    pub(crate) fn unit_price_history(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> String {
+        data_frame_to_csv_string(&mut self.unit_price_history_data_frame(currency_to, time_unit))
+    }
+
+    /// Returns the average price per unit of every item in every period of time_unit, in
+    /// currency_to, with its change since the last period the item was bought in.
+    pub(crate) fn unit_price_history_data_frame(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> DataFrame {
+        let mut rows: Vec<(Item, NaiveDate, Spending, Option<f64>)> = Vec::new();
+        let mut last_prices: BTreeMap<Item, f64> = BTreeMap::new();
+        for (period, items) in self.item_spending(currency_to, time_unit) {
+            for (item, (spent, quantity)) in items {
+                let unit_price: f64 = spent / quantity;
+                let change: Option<f64> = last_prices
+                    .insert(item.clone(), unit_price)
+                    .map(|last_price| 100.0 * (unit_price / last_price - 1.0));
+                rows.push((item, period, (spent, quantity), change));
+            }
+        }
+
+        df!(
+            "Category" => rows.iter().map(|(item, ..)| item.0.clone()).collect::<Vec<String>>(),
+            "Subcategory" => rows.iter().map(|(item, ..)| item.1.clone()).collect::<Vec<String>>(),
+            "Entity" => rows.iter().map(|(item, ..)| self.entity(item.2).name()).collect::<Vec<String>>(),
+            "Unit" => rows.iter().map(|(item, ..)| item.3.clone()).collect::<Vec<String>>(),
+            "Period" => rows.iter().map(|(_item, period, ..)| *period).collect::<Vec<NaiveDate>>(),
+            "Quantity" => rows.iter().map(|(_item, _period, (_spent, quantity), _change)| *quantity).collect::<Vec<f64>>(),
+            "Spent" => rows.iter().map(|(_item, _period, (spent, _quantity), _change)| *spent).collect::<Vec<f64>>(),
+            "Unit Price" => rows.iter().map(|(_item, _period, (spent, quantity), _change)| spent / quantity).collect::<Vec<f64>>(),
+            "% Change" => rows.iter().map(|(.., change)| *change).collect::<Vec<Option<f64>>>()
+        )
+        .expect("Failed to report unit prices")
+        .lazy()
+        .with_columns([
+            col("Spent").round(2),
+            col("Unit Price").round(2),
+            col("% Change").round(2),
+        ])
+        .sort(
+            ["Category", "Subcategory", "Entity", "Unit", "Period"],
+            Default::default(),
+        )
+        .collect()
+        .unwrap()
+    }
+
+    /// Personal price index of the expenses bought with a quantity, by period of time_unit. It
+    /// starts at 100 and, from one period to the next, moves by the average change in the unit
+    /// prices of the items bought in the period that had been bought before, weighted by what
+    /// was spent on them in the period. Periods without such items keep the index unchanged.
+    pub(crate) fn personal_price_index_points(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> Vec<IndexPoint> {
+        let mut points: Vec<IndexPoint> = Vec::new();
+        let mut index: f64 = 100.0;
+        let mut last_prices: BTreeMap<Item, f64> = BTreeMap::new();
+        for (period, items) in self.item_spending(currency_to, time_unit) {
+            let mut weighted_change: f64 = 0.0;
+            let mut compared_spending: f64 = 0.0;
+            let mut compared: usize = 0;
+            for (item, (spent, quantity)) in items.iter() {
+                if let Some(last_price) = last_prices.get(item) {
+                    weighted_change += spent * (spent / quantity) / last_price;
+                    compared_spending += spent;
+                    compared += 1;
+                }
+            }
+            if compared_spending > 0.0 {
+                index *= weighted_change / compared_spending;
+            }
+            for (item, (spent, quantity)) in items.iter() {
+                last_prices.insert(item.clone(), spent / quantity);
+            }
+
+            let spent: f64 = items.values().map(|(spent, _quantity)| spent).sum();
+            points.push((period, index, compared, spent));
+        }
+
+        points
+    }
+
+    /// Returns a csv in String format with the personal price index.
+    pub(crate) fn personal_price_index(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> String {
+        data_frame_to_csv_string(&mut self.personal_price_index_data_frame(currency_to, time_unit))
+    }
+
+    /// Same as personal_price_index_points, but as a DataFrame, with the change of every
+    /// period.
+    pub(crate) fn personal_price_index_data_frame(
+        &self,
+        currency_to: &Currency,
+        time_unit: &TimeUnit,
+    ) -> DataFrame {
+        let points: Vec<IndexPoint> = self.personal_price_index_points(currency_to, time_unit);
+        let changes: Vec<Option<f64>> = (0..points.len())
+            .map(|i| match i {
+                0 => None,
+                i => Some(100.0 * (points[i].1 / points[i - 1].1 - 1.0)),
+            })
+            .collect();
+
+        df!(
+            "Period" => points.iter().map(|point| point.0).collect::<Vec<NaiveDate>>(),
+            "Index" => points.iter().map(|point| point.1).collect::<Vec<f64>>(),
+            "% Change" => changes,
+            "Items Compared" => points.iter().map(|point| point.2 as i64).collect::<Vec<i64>>(),
+            format!("Spent {}", currency_to) => points.iter().map(|point| point.3).collect::<Vec<f64>>()
+        )
+        .expect("Failed to report personal price index")
+        .lazy()
+        .with_columns([
+            col("Index").round(2),
+            col("% Change").round(2),
+            col(format!("Spent {}", currency_to)).round(2),
+        ])
+        .collect()
+        .unwrap()
+    }
+}
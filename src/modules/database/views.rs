@@ -1,9 +1,15 @@
 use crate::modules::database::{capitalize_every_word, data_frame_to_csv_string, DataBase};
+use chrono::NaiveDate;
 use polars::prelude::*;
 
 impl DataBase {
     /// Returns a csv in String format with the last n transactions.
     pub(crate) fn last_transactions(&self, n: usize) -> String {
+        data_frame_to_csv_string(&mut self.last_transactions_data_frame(n))
+    }
+
+    /// Returns the last n incomes and expenses, most recent first.
+    pub(crate) fn last_transactions_data_frame(&self, n: usize) -> DataFrame {
         let incomes_table: DataFrame = self
             .incomes_table
             .data_frame
@@ -18,7 +24,7 @@ impl DataBase {
             .data_frame
             .clone()
             .lazy()
-            .select([all().exclude(["expense_id"])])
+            .select([all().exclude(["expense_id", "quantity", "unit"])])
             .with_column(lit("Expense").alias("type"))
             .collect()
             .unwrap();
@@ -63,11 +69,17 @@ impl DataBase {
             .collect()
             .unwrap();
 
-        data_frame_to_csv_string(&mut transactions_table.head(Some(n)))
+        transactions_table.head(Some(n))
     }
 
     /// Returns a csv in String format with the last n fund movements.
     pub(crate) fn last_fund_movements(&self, n: usize, account_id: i64) -> String {
+        data_frame_to_csv_string(&mut self.last_fund_movements_data_frame(n, account_id))
+    }
+
+    /// Returns the last n fund movements, most recent first. A negative account_id means any
+    /// account.
+    pub(crate) fn last_fund_movements_data_frame(&self, n: usize, account_id: i64) -> DataFrame {
         let mut funds_table: DataFrame = self
             .funds_table
             .data_frame
@@ -95,7 +107,7 @@ impl DataBase {
             .collect()
             .unwrap();
 
-        let mut last_fund_movements = funds_table
+        funds_table
             .inner_join(&accounts_table, ["account_id"], ["account_id"])
             .unwrap()
             .select([
@@ -119,8 +131,54 @@ impl DataBase {
             })])
             .collect()
             .unwrap()
-            .head(Some(n));
+            .head(Some(n))
+    }
+
+    /// Returns a csv in String format with the expenses paid to an entity.
+    pub(crate) fn entity_transactions(
+        &self,
+        entity_id: i64,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+    ) -> String {
+        data_frame_to_csv_string(
+            &mut self.entity_transactions_data_frame(entity_id, date_from, date_to),
+        )
+    }
 
-        data_frame_to_csv_string(&mut last_fund_movements)
+    /// Returns the expenses paid to an entity between date_from and date_to, both included, most
+    /// recent first.
+    pub(crate) fn entity_transactions_data_frame(
+        &self,
+        entity_id: i64,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+    ) -> DataFrame {
+        self.expenses_table
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col("entity_id").eq(lit(entity_id)))
+            .filter(col("date").is_between(lit(date_from), lit(date_to), ClosedInterval::Both))
+            .select([
+                col("date"),
+                col("value"),
+                col("currency"),
+                col("category"),
+                col("subcategory"),
+                col("description"),
+                col("party_id"),
+            ])
+            .sort(
+                ["date", "party_id"],
+                SortMultipleOptions::default().with_order_descending_multi([true, true]),
+            )
+            .select([all().name().map(|name| {
+                Ok(PlSmallStr::from_string(capitalize_every_word(
+                    name.replace("_", " "),
+                )))
+            })])
+            .collect()
+            .unwrap()
     }
 }
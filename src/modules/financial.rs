@@ -142,6 +142,7 @@ pub enum Transaction {
         subcategory: String, // train, bus, hairdresser
         description: String,
         entity_id: i64,
+        quantity: Option<Quantity>, // 42.3 litres, 1 month
     },
     Credit {
         value: f64,
@@ -209,6 +210,20 @@ impl Transaction {
     }
 }
 
+/// Amount of a good or service bought in an expense, in its own unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub amount: f64,
+    pub unit: String,
+}
+
+// Conversion to string
+impl Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.amount, self.unit)
+    }
+}
+
 /// Conversion to string
 impl Display for Transaction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
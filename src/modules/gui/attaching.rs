@@ -0,0 +1,97 @@
+use crate::modules::database::attachments::Attachment;
+use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use eframe::egui;
+use egui::Color32;
+use std::process::Command;
+
+/// Opens a file with the default program of the system.
+fn open_file(path: &str) -> Result<(), String> {
+    let result = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", "start", "", path]).spawn()
+    } else if cfg!(target_os = "macos") {
+        Command::new("open").arg(path).spawn()
+    } else {
+        Command::new("xdg-open").arg(path).spawn()
+    };
+
+    result
+        .map(|_child| ())
+        .map_err(|e| format!("Failed to open {}: {}", path, e))
+}
+
+impl AppState {
+    pub fn handle_show_party_attachments_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("party_attachments_window"),
+            egui::ViewportBuilder::default()
+                .with_title(format!(
+                    "Attachments of party {}",
+                    self.attachments_party_id
+                ))
+                .with_inner_size([WINDOW_WIDTH / 1.5, WINDOW_HEIGHT / 1.5]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    egui::Grid::new("attach_file")
+                        .num_columns(3)
+                        .spacing([15.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("File:").on_hover_text(
+                                "Path to a receipt, invoice or warranty to attach to the party.",
+                            );
+                            ui.text_edit_singleline(&mut self.attachment_path);
+                            if ui.button("Attach").clicked() {
+                                match Attachment::read(self.attachment_path.as_str()).and_then(
+                                    |attachment| {
+                                        self.database.attach(self.attachments_party_id, &attachment)
+                                    },
+                                ) {
+                                    Ok(_attachment_id) => {
+                                        self.database.save();
+                                        self.attachment_path.clear();
+                                        self.attachment_message.clear();
+                                    }
+                                    Err(e) => self.attachment_message = e,
+                                }
+                            }
+                            ui.end_row();
+                        });
+
+                    if !self.attachment_message.is_empty() {
+                        ui.colored_label(
+                            Color32::from_rgb(255, 0, 0),
+                            self.attachment_message.as_str(),
+                        );
+                    }
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("party_attachments")
+                            .num_columns(2)
+                            .spacing([15.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (_attachment_id, file_name, path) in
+                                    self.database.attachments(self.attachments_party_id)
+                                {
+                                    ui.label(file_name.as_str());
+                                    if ui.button("Open").on_hover_text(path.as_str()).clicked() {
+                                        if let Err(e) = open_file(path.as_str()) {
+                                            self.attachment_message = e;
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_party_attachments_window = false;
+                }
+            },
+        )
+    }
+}
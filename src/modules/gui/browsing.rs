@@ -1,3 +1,4 @@
+use crate::modules::database::suggestions::SuggestionModel;
 use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
 use eframe::egui::ComboBox;
 use egui::{Align, Color32, Layout};
@@ -72,6 +73,14 @@ impl AppState {
                                             self.last_transactions_n = self.last_transactions_n_temptative.parse::<usize>().expect("Failed to parse the number of last transactions.");
                                             self.last_transactions_csv = self.database.last_transactions(self.last_transactions_n);
                                         }
+                                        if ui.button("Export").on_hover_text("Writes the records to a spreadsheet in the data folder.").clicked() {
+                                            self.last_transactions_n = self.last_transactions_n_temptative.parse::<usize>().expect("Failed to parse the number of last transactions.");
+                                            self.export_message = match self.database.export_last_transactions(self.last_transactions_n) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.label(self.export_message.as_str());
                                     }
                                 });
                                 ui.separator();
@@ -100,12 +109,21 @@ impl AppState {
                                                             let party_id: i64 =
                                                                 element.parse().unwrap();
                                                             self.party = self.database.party(party_id);
+                                                            self.party_attachments = self.database.load_attachments(party_id);
                                                             self.database.delete_party(party_id);
                                                             self.database.save();
+                                                            self.runway_date = None;
+                                                            self.budget_progress_date = None;
+                                                            self.transaction_suggestion_model = SuggestionModel::default();
 
                                                             self.show_input_party_window = true;
                                                             self.show_browse_last_transactions_window = false;
                                                         }
+                                                        if ui.button("Attachments").on_hover_text("Shows the files attached to the party").clicked() {
+                                                            self.attachments_party_id = element.parse().unwrap();
+                                                            self.attachment_message.clear();
+                                                            self.show_party_attachments_window = true;
+                                                        }
                                                     } else {
                                                         ui.label(element);
                                                     }
@@ -217,6 +235,17 @@ impl AppState {
                                                 .expect("Failed to parse the number of last fund_movements.");
                                             self.last_fund_movements_csv = self.database.last_fund_movements(self.last_fund_movements_n, self.browse_account_id);
                                         }
+                                        if ui.button("Export").on_hover_text("Writes the records to a spreadsheet in the data folder.").clicked() {
+                                            self.last_fund_movements_n = self
+                                                .last_fund_movements_n_temptative
+                                                .parse::<usize>()
+                                                .expect("Failed to parse the number of last fund_movements.");
+                                            self.export_message = match self.database.export_last_fund_movements(self.last_fund_movements_n, self.browse_account_id) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.label(self.export_message.as_str());
                                     }
                                 });
                                 ui.separator();
@@ -245,12 +274,21 @@ impl AppState {
                                                             let party_id: i64 =
                                                                 element.parse().unwrap();
                                                             self.party = self.database.party(party_id);
+                                                            self.party_attachments = self.database.load_attachments(party_id);
                                                             self.database.delete_party(party_id);
                                                             self.database.save();
+                                                            self.runway_date = None;
+                                                            self.budget_progress_date = None;
+                                                            self.transaction_suggestion_model = SuggestionModel::default();
 
                                                             self.show_input_party_window = true;
                                                             self.show_browse_last_fund_movements_window = false;
                                                         }
+                                                        if ui.button("Attachments").on_hover_text("Shows the files attached to the party").clicked() {
+                                                            self.attachments_party_id = element.parse().unwrap();
+                                                            self.attachment_message.clear();
+                                                            self.show_party_attachments_window = true;
+                                                        }
                                                     } else {
                                                         ui.label(element);
                                                     }
@@ -271,4 +309,54 @@ impl AppState {
             },
             );
     }
+
+    pub fn handle_show_entity_transactions_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("entity_transactions_window"),
+            egui::ViewportBuilder::default()
+                .with_title(self.entity_transactions_title.as_str())
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let entity_transactions_csv = self.entity_transactions_csv.clone();
+                    let header_line: String =
+                        entity_transactions_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> =
+                        entity_transactions_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+
+                    TableBuilder::new(ui)
+                        .columns(Column::auto().resizable(true), column_count)
+                        .striped(true)
+                        .cell_layout(Layout::right_to_left(Align::Center))
+                        .header(20.0, |mut header| {
+                            for column_name in header_line.split(",") {
+                                header.col(|ui| {
+                                    ui.strong(column_name).on_hover_text(column_name);
+                                });
+                            }
+                        })
+                        .body(|mut body| {
+                            for row_line in row_lines {
+                                body.row(30.0, |mut row_ui| {
+                                    for element in row_line.split(",") {
+                                        row_ui.col(|ui| {
+                                            ui.label(element);
+                                        });
+                                    }
+                                });
+                            }
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_entity_transactions_window = false;
+                }
+            },
+        );
+    }
 }
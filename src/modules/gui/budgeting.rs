@@ -0,0 +1,294 @@
+use crate::modules::database::budgets::Budget;
+use crate::modules::database::summaries::TimeUnit;
+use crate::modules::financial::*;
+use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use chrono::{Local, NaiveDate};
+use eframe::egui;
+use egui::{Align, Color32, ComboBox, Layout};
+use egui_extras::*;
+use strum::IntoEnumIterator;
+
+impl AppState {
+    /// Recomputes the progress of the monthly budgets shown in the main window, at most once a
+    /// day unless the budgets or the transactions change.
+    pub(crate) fn refresh_budget_progress(&mut self) -> () {
+        let today: NaiveDate = Local::now().date_naive();
+        if self.budget_progress_date != Some(today) {
+            self.budget_progress = self.database.budget_progress(today, &Currency::default());
+            self.budget_progress_date = Some(today);
+        }
+    }
+
+    pub fn handle_show_budgets_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("budgets_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Budgets window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let value: Option<f64> = self.budget_value_tentative.parse::<f64>().ok();
+
+                    egui::Grid::new("budget")
+                        .num_columns(2)
+                        .spacing([45.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Category:")
+                                .on_hover_text("Expense category to budget.");
+                            ComboBox::from_id_salt("Budget category")
+                                .selected_text(self.budget_category.as_str())
+                                .show_ui(ui, |ui| {
+                                    for category in self
+                                        .database
+                                        .transaction_categories(&TransactionType::Expense)
+                                    {
+                                        if ui
+                                            .selectable_value(
+                                                &mut self.budget_category,
+                                                category.clone(),
+                                                category.as_str(),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.budget_subcategory = None;
+                                        }
+                                    }
+                                });
+                            ui.end_row();
+
+                            ui.label("Subcategory:").on_hover_text(
+                                "Subcategory to budget, or all of the category's expenses.",
+                            );
+                            ComboBox::from_id_salt("Budget subcategory")
+                                .selected_text(self.budget_subcategory.as_deref().unwrap_or("All"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.budget_subcategory, None, "All");
+                                    for subcategory in self.database.transaction_subcategories(
+                                        &TransactionType::Expense,
+                                        self.budget_category.clone(),
+                                    ) {
+                                        ui.selectable_value(
+                                            &mut self.budget_subcategory,
+                                            Some(subcategory.clone()),
+                                            subcategory.as_str(),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+
+                            ui.label("Period:")
+                                .on_hover_text("Period over which the amount may be spent.");
+                            ComboBox::from_id_salt("Budget period")
+                                .selected_text(format!("{}", self.budget_period))
+                                .show_ui(ui, |ui| {
+                                    for period in TimeUnit::iter() {
+                                        ui.selectable_value(
+                                            &mut self.budget_period,
+                                            period.clone(),
+                                            format!("{period}"),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+
+                            ui.label("Amount:");
+                            ui.horizontal(|ui| {
+                                ui.text_edit_singleline(&mut self.budget_value_tentative);
+                                ComboBox::from_id_salt("Budget currency")
+                                    .selected_text(format!("{}", self.budget_currency))
+                                    .show_ui(ui, |ui| {
+                                        for currency in Currency::iter() {
+                                            ui.selectable_value(
+                                                &mut self.budget_currency,
+                                                currency.clone(),
+                                                format!("{currency}"),
+                                            );
+                                        }
+                                    });
+                                if value.is_none() {
+                                    ui.colored_label(Color32::from_rgb(255, 0, 0), "Invalid amount!");
+                                }
+                            });
+                            ui.end_row();
+
+                            ui.label("Rollover:").on_hover_text(
+                                "Carry over what is left of, or overspent on, a period to the next one.",
+                            );
+                            ui.checkbox(&mut self.budget_rollover, "");
+                            ui.end_row();
+
+                            ui.label("");
+                            if let Some(value) = value {
+                                if !self.budget_category.is_empty()
+                                    && ui
+                                        .button("Set budget")
+                                        .on_hover_text(
+                                            "Replaces the budget of the same category and period, if any.",
+                                        )
+                                        .clicked()
+                                {
+                                    self.database.set_budget(&Budget {
+                                        category: self.budget_category.clone(),
+                                        subcategory: self.budget_subcategory.clone(),
+                                        period: self.budget_period.clone(),
+                                        value,
+                                        currency: self.budget_currency.clone(),
+                                        rollover: self.budget_rollover,
+                                    });
+                                    self.database.save();
+                                    self.budget_progress_date = None;
+                                }
+                            }
+                            ui.end_row();
+                        });
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("budgets")
+                            .num_columns(6)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (budget_id, budget, _creation_date) in self.database.budgets()
+                                {
+                                    ui.label(budget.category.as_str());
+                                    ui.label(budget.subcategory.as_deref().unwrap_or("All"));
+                                    ui.label(format!("{}", budget.period));
+                                    ui.label(format!("{:.2} {}", budget.value, budget.currency));
+                                    ui.label(match budget.rollover {
+                                        true => "Rollover",
+                                        false => "",
+                                    });
+                                    if ui.button("Remove").clicked() {
+                                        self.database.delete_budget(budget_id);
+                                        self.database.save();
+                                        self.budget_progress_date = None;
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_budgets_window = false;
+                }
+            },
+        )
+    }
+
+    pub fn handle_show_budget_vs_actual_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("budget_vs_actual_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Budget vs actual window")
+                .with_inner_size([WINDOW_WIDTH * 1.5, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let budget_vs_actual_csv = self.budget_vs_actual_csv.clone();
+                    let header_line: String =
+                        budget_vs_actual_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> =
+                        budget_vs_actual_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("budget_vs_actual")
+                                    .num_columns(2)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Date:").on_hover_text(
+                                            "Compare the budgets with the expenses of their periods containing this date.",
+                                        );
+                                        ui.add(DatePickerButton::new(&mut self.budget_vs_actual_date));
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.horizontal(|ui| {
+                                            if ui.button("Generate!").clicked() {
+                                                self.budget_vs_actual_csv = self
+                                                    .database
+                                                    .budget_vs_actual(self.budget_vs_actual_date);
+                                            }
+                                            if ui
+                                                .button("Export")
+                                                .on_hover_text(
+                                                    "Writes the comparison to a spreadsheet in the data folder.",
+                                                )
+                                                .clicked()
+                                            {
+                                                self.export_message = match self
+                                                    .database
+                                                    .export_budget_vs_actual(self.budget_vs_actual_date)
+                                                {
+                                                    Ok(file_name) => format!("Exported to {}", file_name),
+                                                    Err(e) => e,
+                                                };
+                                            }
+                                        });
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                TableBuilder::new(ui)
+                                    .columns(Column::auto().resizable(true), column_count)
+                                    .striped(true)
+                                    .cell_layout(Layout::right_to_left(Align::Center))
+                                    .header(20.0, |mut header| {
+                                        for column_name in header_line.split(",") {
+                                            header.col(|ui| {
+                                                ui.strong(column_name).on_hover_text(column_name);
+                                            });
+                                        }
+                                    })
+                                    .body(|mut body| {
+                                        for row_line in row_lines {
+                                            body.row(30.0, |mut row_ui| {
+                                                for element in row_line.split(",") {
+                                                    row_ui.col(|ui| match element {
+                                                        "Over budget" => {
+                                                            ui.colored_label(
+                                                                Color32::from_rgb(255, 0, 0),
+                                                                element,
+                                                            );
+                                                        }
+                                                        "Projected over" => {
+                                                            ui.colored_label(
+                                                                Color32::from_rgb(255, 165, 0),
+                                                                element,
+                                                            );
+                                                        }
+                                                        _ => {
+                                                            ui.label(element);
+                                                        }
+                                                    });
+                                                }
+                                            });
+                                        }
+                                    });
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_budget_vs_actual_window = false;
+                }
+            },
+        )
+    }
+}
@@ -0,0 +1,149 @@
+use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use chrono::{Month, Weekday};
+use eframe::egui;
+use egui::ComboBox;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Name of the month, from 1 to 12.
+fn month_name(month: u32) -> &'static str {
+    Month::try_from(month as u8)
+        .map(|month| month.name())
+        .unwrap_or("")
+}
+
+impl AppState {
+    pub fn handle_show_settings_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("settings_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Settings window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT / 1.5]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let periods = &mut self.settings.periods;
+                    egui::Grid::new("settings")
+                        .num_columns(2)
+                        .spacing([45.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Months start on day:").on_hover_text(
+                                "Day on which monthly periods start, e.g. the pay day.",
+                            );
+                            ComboBox::from_id_salt("Month start day")
+                                .selected_text(periods.month_start_day.to_string())
+                                .show_ui(ui, |ui| {
+                                    for day in 1..=28 {
+                                        ui.selectable_value(
+                                            &mut periods.month_start_day,
+                                            day,
+                                            day.to_string(),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+
+                            ui.label("Weeks start on:")
+                                .on_hover_text("Day on which weekly periods start.");
+                            ComboBox::from_id_salt("Week start")
+                                .selected_text(periods.week_start.to_string())
+                                .show_ui(ui, |ui| {
+                                    for weekday in WEEKDAYS {
+                                        ui.selectable_value(
+                                            &mut periods.week_start,
+                                            weekday,
+                                            weekday.to_string(),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+
+                            ui.label("Years start on:").on_hover_text(
+                                "Month and day on which yearly periods start, e.g. 6 April for UK tax years.",
+                            );
+                            ui.horizontal(|ui| {
+                                ComboBox::from_id_salt("Year start day")
+                                    .selected_text(periods.year_start_day.to_string())
+                                    .show_ui(ui, |ui| {
+                                        for day in 1..=28 {
+                                            ui.selectable_value(
+                                                &mut periods.year_start_day,
+                                                day,
+                                                day.to_string(),
+                                            );
+                                        }
+                                    });
+                                ComboBox::from_id_salt("Year start month")
+                                    .selected_text(month_name(periods.year_start_month))
+                                    .show_ui(ui, |ui| {
+                                        for month in 1..=12 {
+                                            ui.selectable_value(
+                                                &mut periods.year_start_month,
+                                                month,
+                                                month_name(month),
+                                            );
+                                        }
+                                    });
+                            });
+                            ui.end_row();
+
+                            ui.label("Minimum runway:").on_hover_text(
+                                "Months of average expenses the deposit and cash accounts should cover. Below it, a warning is shown on the main window.",
+                            );
+                            ComboBox::from_id_salt("Minimum runway")
+                                .selected_text(match self.settings.min_runway_months {
+                                    0 => String::from("None"),
+                                    months => format!("{} months", months),
+                                })
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(
+                                        &mut self.settings.min_runway_months,
+                                        0,
+                                        "None",
+                                    );
+                                    for months in 1..=24 {
+                                        ui.selectable_value(
+                                            &mut self.settings.min_runway_months,
+                                            months,
+                                            format!("{} months", months),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+                        });
+                    ui.separator();
+
+                    ui.vertical_centered_justified(|ui| {
+                        if ui
+                            .button("Save")
+                            .on_hover_text("Use these settings in every report from now on.")
+                            .clicked()
+                        {
+                            self.settings_message =
+                                match self.database.set_settings(self.settings.clone()) {
+                                    Ok(()) => String::from("Settings saved."),
+                                    Err(e) => e,
+                                };
+                            self.runway_date = None;
+                        }
+                        ui.label(self.settings_message.as_str());
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_settings_window = false;
+                }
+            },
+        )
+    }
+}
@@ -0,0 +1,238 @@
+use crate::modules::database::journal::*;
+use crate::modules::database::suggestions::SuggestionModel;
+use crate::modules::financial::*;
+use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use eframe::egui;
+use egui::{Color32, ComboBox};
+use strum::IntoEnumIterator;
+
+impl AppState {
+    /// Reads the journal file and proposes a mapping for its accounts.
+    fn analyze_journal(&mut self) -> () {
+        self.import_journal_transactions.clear();
+        self.import_journal_refused.clear();
+        self.import_journal_message.clear();
+
+        match load_journal(self.import_journal_path.as_str()) {
+            Ok(results) => {
+                for result in results {
+                    match result {
+                        Ok(journal_transaction) => {
+                            self.import_journal_transactions.push(journal_transaction)
+                        }
+                        Err(e) => self.import_journal_refused.push(e),
+                    }
+                }
+                self.import_journal_mapping = JournalMapping::guess(
+                    &self.import_journal_transactions,
+                    self.import_journal_country.clone(),
+                );
+                self.import_journal_message = format!(
+                    "Found {} transactions, {} cannot be imported.",
+                    self.import_journal_transactions.len(),
+                    self.import_journal_refused.len()
+                );
+            }
+            Err(e) => {
+                self.import_journal_mapping = JournalMapping::default();
+                self.import_journal_message = e;
+            }
+        }
+    }
+
+    pub fn handle_show_import_journal_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("import_journal_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Import journal window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    egui::Grid::new("import_journal")
+                        .num_columns(3)
+                        .spacing([45.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Journal file:")
+                                .on_hover_text("Path to a beancount or hledger journal to import.");
+                            ui.text_edit_singleline(&mut self.import_journal_path);
+                            ui.end_row();
+
+                            ui.label("Country:").on_hover_text(
+                                "Country given to the accounts and entities created by the import.",
+                            );
+                            ui.text_edit_singleline(&mut self.import_journal_country);
+                            ui.end_row();
+
+                            ui.label("");
+                            if ui.button("Analyze").clicked() {
+                                self.analyze_journal();
+                            }
+                            ui.end_row();
+                        });
+
+                    if !self.import_journal_message.is_empty() {
+                        ui.label(self.import_journal_message.as_str());
+                    }
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("import_journal_mapping")
+                            .num_columns(5)
+                            .spacing([15.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (i, account_mapping) in
+                                    self.import_journal_mapping.accounts.iter_mut().enumerate()
+                                {
+                                    ui.label(account_mapping.journal_account.as_str());
+
+                                    ComboBox::from_id_salt(format!("Journal account role {}", i))
+                                        .selected_text(format!("{}", account_mapping.role))
+                                        .show_ui(ui, |ui| {
+                                            for possible_role in JournalAccountRole::iter() {
+                                                ui.selectable_value(
+                                                    &mut account_mapping.role,
+                                                    possible_role.clone(),
+                                                    format!("{possible_role}"),
+                                                );
+                                            }
+                                        });
+
+                                    match account_mapping.role {
+                                        JournalAccountRole::Account => {
+                                            ComboBox::from_id_salt(format!(
+                                                "Journal account type {}",
+                                                i
+                                            ))
+                                            .selected_text(format!(
+                                                "{}",
+                                                account_mapping.account_type
+                                            ))
+                                            .show_ui(
+                                                ui,
+                                                |ui| {
+                                                    for possible_account_type in AccountType::iter()
+                                                    {
+                                                        ui.selectable_value(
+                                                            &mut account_mapping.account_type,
+                                                            possible_account_type.clone(),
+                                                            format!("{possible_account_type}"),
+                                                        );
+                                                    }
+                                                },
+                                            );
+                                            ui.label("");
+                                            ui.label("");
+                                        }
+                                        JournalAccountRole::Income
+                                        | JournalAccountRole::Expense => {
+                                            ui.label("");
+                                            ui.text_edit_singleline(&mut account_mapping.category)
+                                                .on_hover_text("Category");
+                                            ui.text_edit_singleline(
+                                                &mut account_mapping.subcategory,
+                                            )
+                                            .on_hover_text("Subcategory");
+                                        }
+                                        JournalAccountRole::OpeningBalance => {
+                                            ui.label("");
+                                            ui.label("");
+                                            ui.label("");
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+
+                        for refused in self.import_journal_refused.iter() {
+                            ui.colored_label(Color32::from_rgb(255, 0, 0), refused.as_str());
+                        }
+                    });
+
+                    ui.separator();
+                    ui.vertical_centered_justified(|ui| {
+                        if !self.import_journal_transactions.is_empty()
+                            && ui.button("Import!").clicked()
+                        {
+                            self.import_journal_mapping.country =
+                                self.import_journal_country.clone();
+                            let report: JournalImportReport = self.database.import_journal(
+                                &self.import_journal_transactions,
+                                &self.import_journal_mapping,
+                            );
+                            self.database.save();
+                            self.runway_date = None;
+                            self.budget_progress_date = None;
+                            self.transaction_suggestion_model = SuggestionModel::default();
+
+                            self.import_journal_message = report.to_string();
+                            self.import_journal_refused.extend(report.refused);
+                            self.import_journal_transactions.clear();
+                        }
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_import_journal_window = false;
+                }
+            },
+        )
+    }
+
+    pub fn handle_show_import_snapshot_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("import_snapshot_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Import snapshot window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    egui::Grid::new("import_snapshot")
+                        .num_columns(3)
+                        .spacing([45.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Snapshot file:").on_hover_text(
+                                "Path to a JSON snapshot. Importing it replaces the whole ledger.",
+                            );
+                            ui.text_edit_singleline(&mut self.import_snapshot_path);
+                            ui.end_row();
+
+                            ui.label("");
+                            if ui.button("Dry run").clicked() {
+                                self.import_snapshot_message = match self
+                                    .database
+                                    .import_snapshot(self.import_snapshot_path.as_str(), true)
+                                {
+                                    Ok(summary) => summary,
+                                    Err(e) => e,
+                                };
+                            }
+                            if ui.button("Import!").clicked() {
+                                self.import_snapshot_message = match self
+                                    .database
+                                    .import_snapshot(self.import_snapshot_path.as_str(), false)
+                                {
+                                    Ok(summary) => format!("Imported. {}", summary),
+                                    Err(e) => e,
+                                };
+                            }
+                            ui.end_row();
+                        });
+
+                    ui.separator();
+                    ui.label(self.import_snapshot_message.as_str());
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_import_snapshot_window = false;
+                }
+            },
+        )
+    }
+}
@@ -1,3 +1,5 @@
+use crate::modules::database::attachments::Attachment;
+use crate::modules::database::suggestions::{SuggestionContext, SuggestionModel};
 use crate::modules::financial::*;
 use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
 use eframe::egui;
@@ -8,20 +10,40 @@ use egui_extras::*;
 use strum::IntoEnumIterator;
 
 impl AppState {
-    fn clear_fields(&mut self) -> () {
+    pub(crate) fn clear_fields(&mut self) -> () {
+        // Due recurring parties are only looked for on startup, keep the ones not handled yet
+        let due_occurrences = std::mem::take(&mut self.due_occurrences);
+        let due_occurrence_values_tentative =
+            std::mem::take(&mut self.due_occurrence_values_tentative);
+        let show_due_occurrences_window: bool = self.show_due_occurrences_window;
+        let anomalies = std::mem::take(&mut self.anomalies);
+        let show_anomalies_window: bool = self.show_anomalies_window;
+
         *self = AppState::default();
+
+        self.due_occurrences_checked = true;
+        self.due_occurrences = due_occurrences;
+        self.due_occurrence_values_tentative = due_occurrence_values_tentative;
+        self.show_due_occurrences_window = show_due_occurrences_window;
+        self.anomalies = anomalies;
+        self.show_anomalies_window = show_anomalies_window;
     }
 
     fn clear_transaction_fields(&mut self) -> () {
         self.transaction_category = String::default();
         self.transaction_subcategory = String::default();
         self.transaction_description = String::default();
+        self.transaction_quantity_tentative = String::default();
+        self.transaction_unit = String::default();
         self.transaction_entity_id = i64::default();
         self.transaction_entity_string = String::default();
         self.transaction_account_id = i64::default();
         self.transaction_account_string = String::default();
         self.transaction_type = TransactionType::default();
         self.transaction_filter = String::default();
+        self.transaction_suggestion_entity_id = i64::default();
+        self.transaction_suggested_category = String::default();
+        self.transaction_suggested_subcategory = String::default();
     }
 
     fn clear_entity_fields(&mut self) -> () {
@@ -74,10 +96,74 @@ impl AppState {
                 .currency()
     }
 
+    /// The quantity is optional, but needs a positive amount and a unit if given.
+    fn is_valid_transaction_quantity(&self) -> bool {
+        (self.transaction_quantity_tentative.is_empty() & self.transaction_unit.is_empty())
+            | self.transaction_quantity().is_some()
+    }
+
+    fn transaction_quantity(&self) -> Option<Quantity> {
+        match self.transaction_quantity_tentative.parse::<f64>() {
+            Ok(amount) if (amount > 0.0) & !self.transaction_unit.is_empty() => Some(Quantity {
+                amount,
+                unit: self.transaction_unit.clone(),
+            }),
+            _ => None,
+        }
+    }
+
     fn are_valid_transaction_fields(&self) -> bool {
         ((self.transaction_category.len() > 0)
             | (self.transaction_type.is_fund_change() & self.is_valid_transaction_currency()))
             & self.is_valid_transaction_value()
+            & self.is_valid_transaction_quantity()
+    }
+
+    fn transaction_suggestion_context(&self) -> SuggestionContext {
+        SuggestionContext {
+            entity_id: Some(self.transaction_entity_id),
+            description: self.transaction_description.clone(),
+            value: self.transaction_value_tentative.parse::<f64>().ok(),
+            date: Some(self.transaction_date),
+        }
+    }
+
+    /// Keeps the suggestion model in sync with the transaction type and, whenever a new entity
+    /// is picked, pre-selects the most likely category and subcategory. Categories typed in by
+    /// hand are never overwritten.
+    fn update_transaction_suggestions(&mut self) -> () {
+        if self.transaction_type.is_fund_change() {
+            return;
+        }
+
+        if !self
+            .transaction_suggestion_model
+            .is_trained_for(&self.transaction_type)
+        {
+            self.transaction_suggestion_model =
+                self.database.suggestion_model(&self.transaction_type);
+            self.transaction_suggestion_entity_id = self.transaction_entity_id;
+        }
+
+        if self.transaction_entity_id == self.transaction_suggestion_entity_id {
+            return;
+        }
+        self.transaction_suggestion_entity_id = self.transaction_entity_id;
+
+        let is_untouched: bool = self.transaction_category.is_empty()
+            | ((self.transaction_category == self.transaction_suggested_category)
+                & (self.transaction_subcategory == self.transaction_suggested_subcategory));
+        if is_untouched {
+            if let Some((category, subcategory)) = self
+                .transaction_suggestion_model
+                .most_likely(&self.transaction_suggestion_context())
+            {
+                self.transaction_category = category.clone();
+                self.transaction_subcategory = subcategory.clone();
+                self.transaction_suggested_category = category;
+                self.transaction_suggested_subcategory = subcategory;
+            }
+        }
     }
 
     pub fn handle_show_input_entity_window(&mut self, ctx: &egui::Context) -> () {
@@ -339,6 +425,7 @@ impl AppState {
                     StripBuilder::new(ui)
                         .size(Size::exact(40.0))
                         .size(Size::remainder().at_least(100.0))
+                        .size(Size::exact(60.0))
                         .size(Size::exact(40.0))
                         .vertical(|mut strip| {
                             strip.cell(|ui| {
@@ -454,6 +541,7 @@ impl AppState {
                                                     subcategory,
                                                     description,
                                                     entity_id,
+                                                    quantity,
                                                 } => {
                                                     self.transaction_type =
                                                         TransactionType::Expense;
@@ -466,6 +554,11 @@ impl AppState {
                                                     self.transaction_subcategory = subcategory;
                                                     self.transaction_description = description;
                                                     self.transaction_entity_id = entity_id;
+                                                    if let Some(quantity) = quantity {
+                                                        self.transaction_quantity_tentative =
+                                                            quantity.amount.to_string();
+                                                        self.transaction_unit = quantity.unit;
+                                                    }
                                                 }
                                                 Transaction::Credit {
                                                     value,
@@ -501,16 +594,72 @@ impl AppState {
                                         }
                                     });
                             });
+                            strip.cell(|ui| {
+                                ui.separator();
+                                ui.horizontal(|ui| {
+                                    ui.label("File:").on_hover_text(
+                                        "Path to a receipt, invoice or warranty to attach to the party.",
+                                    );
+                                    ui.text_edit_singleline(&mut self.attachment_path);
+                                    if ui.button("Attach file").clicked() {
+                                        match Attachment::read(self.attachment_path.as_str()) {
+                                            Ok(attachment) => {
+                                                self.party_attachments.push(attachment);
+                                                self.attachment_path.clear();
+                                                self.attachment_message.clear();
+                                            }
+                                            Err(e) => self.attachment_message = e,
+                                        }
+                                    }
+                                });
+                                if self.attachment_message.is_empty() {
+                                    let file_names: Vec<&str> = self
+                                        .party_attachments
+                                        .iter()
+                                        .map(|attachment| attachment.file_name.as_str())
+                                        .collect();
+                                    ui.label(format!("Attachments: {}", file_names.join(", ")));
+                                } else {
+                                    ui.colored_label(
+                                        Color32::from_rgb(255, 0, 0),
+                                        self.attachment_message.as_str(),
+                                    );
+                                }
+                            });
                             strip.cell(|ui| {
                                 ui.separator();
                                 ui.vertical_centered_justified(|ui| {
                                     if self.party.is_valid() {
                                         if ui.button("Add party").clicked() {
-                                            self.database.insert_party(&mut self.party);
+                                            let party_id: i64 =
+                                                self.database.insert_party(&mut self.party);
+                                            let attachment_errors: Vec<String> = self
+                                                .party_attachments
+                                                .iter()
+                                                .filter_map(|attachment| {
+                                                    self.database.attach(party_id, attachment).err()
+                                                })
+                                                .collect();
                                             self.database.save();
                                             self.clear_fields();
-
-                                            self.show_input_party_window = false;
+                                            self.runway_date = None;
+                                            self.budget_progress_date = None;
+                                            self.transaction_suggestion_model =
+                                                SuggestionModel::default();
+                                            self.check_anomalies(Some(party_id));
+
+                                            // The party is saved either way, stay open to show
+                                            // the files that could not be attached
+                                            self.attachment_message = attachment_errors.join("\n");
+                                            self.show_input_party_window =
+                                                !attachment_errors.is_empty();
+                                        }
+                                        if ui
+                                            .button("Save as recurring party")
+                                            .on_hover_text("Propose this party again on a schedule, e.g. the rent.")
+                                            .clicked()
+                                        {
+                                            self.show_template_window = true;
                                         }
                                     }
                                 });
@@ -525,6 +674,7 @@ impl AppState {
         );
     }
     pub fn handle_show_input_transaction_window(&mut self, ctx: &egui::Context) -> () {
+        self.update_transaction_suggestions();
         self.transaction_entity_string =
             self.database.entity(self.transaction_entity_id).to_string();
         self.transaction_account_string = self
@@ -645,6 +795,9 @@ impl AppState {
 
                                 ui.label("");
                                 ui.end_row();
+
+                                ui.label("");
+                                ui.end_row();
                             } else {
                                 // it is not fund change
                                 ui.label("Transaction entity:")
@@ -683,13 +836,20 @@ impl AppState {
                                 }
                                 ui.end_row();
 
+                                let suggestion_posteriors = self
+                                    .transaction_suggestion_model
+                                    .posteriors(&self.transaction_suggestion_context());
+
                                 ui.label("Transaction category:")
-                                    .on_hover_text("Category of the transaction.");
+                                    .on_hover_text("Category of the transaction. Suggestions are sorted from most to least likely.");
                                 ui.add(
                                     AutoCompleteTextEdit::new(
                                         &mut self.transaction_category,
-                                        self.database
-                                            .transaction_categories(&self.transaction_type),
+                                        SuggestionModel::rank_categories(
+                                            &suggestion_posteriors,
+                                            self.database
+                                                .transaction_categories(&self.transaction_type),
+                                        ),
                                     )
                                     .max_suggestions(10)
                                     .highlight_matches(true),
@@ -709,13 +869,17 @@ impl AppState {
                                 ui.end_row();
 
                                 ui.label("Transaction subcategory:")
-                                    .on_hover_text("Subcategory of the transaction.");
+                                    .on_hover_text("Subcategory of the transaction. Suggestions are sorted from most to least likely.");
                                 ui.add(
                                     AutoCompleteTextEdit::new(
                                         &mut self.transaction_subcategory,
-                                        self.database.transaction_subcategories(
-                                            &self.transaction_type,
-                                            self.transaction_category.clone(),
+                                        SuggestionModel::rank_subcategories(
+                                            &suggestion_posteriors,
+                                            self.transaction_category.as_str(),
+                                            self.database.transaction_subcategories(
+                                                &self.transaction_type,
+                                                self.transaction_category.clone(),
+                                            ),
                                         ),
                                     )
                                     .max_suggestions(10)
@@ -727,6 +891,38 @@ impl AppState {
                                     .on_hover_text("Text description of the transaction.");
                                 ui.text_edit_singleline(&mut self.transaction_description);
                                 ui.end_row();
+
+                                if self.transaction_type == TransactionType::Expense {
+                                    ui.label("Transaction quantity:").on_hover_text(
+                                        "Optional quantity bought and its unit, such as 42.3 litres or 1 month, to follow unit prices.",
+                                    );
+                                    ui.horizontal(|ui| {
+                                        ui.add(
+                                            egui::TextEdit::singleline(
+                                                &mut self.transaction_quantity_tentative,
+                                            )
+                                            .hint_text("Quantity")
+                                            .desired_width(80.0),
+                                        );
+                                        ui.add(
+                                            AutoCompleteTextEdit::new(
+                                                &mut self.transaction_unit,
+                                                self.database.units(),
+                                            )
+                                            .max_suggestions(10)
+                                            .highlight_matches(true),
+                                        );
+                                    });
+                                    if !self.is_valid_transaction_quantity() {
+                                        ui.colored_label(
+                                            Color32::from_rgb(255, 0, 0),
+                                            "Please enter a positive quantity and its unit, or neither!",
+                                        );
+                                    }
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
                             }
                         });
 
@@ -756,6 +952,7 @@ impl AppState {
                                     subcategory: self.transaction_subcategory.clone(),
                                     description: self.transaction_description.clone(),
                                     entity_id: self.transaction_entity_id,
+                                    quantity: self.transaction_quantity(),
                                 },
                                 TransactionType::Credit => Transaction::Credit {
                                     value: self.transaction_value,
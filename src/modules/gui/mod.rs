@@ -1,17 +1,36 @@
+pub mod attaching;
 pub mod browsing;
+pub mod budgeting;
+pub mod configuring;
+pub mod importing;
 pub mod inputting;
 pub mod plotting;
+pub mod reviewing;
+pub mod saving;
+pub mod scheduling;
 pub mod summarizing;
 
-use super::database::summaries::TimeUnit;
+use super::database::summaries::{
+    EntityGrouping, FundGrouping, SummaryDimension, TimeUnit, Valuation,
+};
+use crate::modules::database::anomalies::Anomaly;
+use crate::modules::database::attachments::Attachment;
+use crate::modules::database::journal::{JournalFormat, JournalMapping, JournalTransaction};
+use crate::modules::database::liquidity::{Runway, RUNWAY_WINDOW_MONTHS};
 use crate::modules::database::plotter::BarplotType;
+use crate::modules::database::recurring::{Occurrence, Recurrence};
+use crate::modules::database::settings::Settings;
+use crate::modules::database::subscriptions::Subscription;
+use crate::modules::database::suggestions::SuggestionModel;
 use crate::modules::database::*;
 use crate::modules::financial::*;
+use crate::modules::price_index::RealTerms;
 use chrono::{Local, NaiveDate};
 use derivative::*;
 use eframe::egui;
 use egui::PopupCloseBehavior;
 use egui_extras::{Size, StripBuilder};
+use strum::IntoEnumIterator;
 
 const WINDOW_HEIGHT: f32 = 400.0;
 const WINDOW_WIDTH: f32 = 600.0;
@@ -24,12 +43,35 @@ pub struct AppState {
     show_input_party_window: bool,
     show_input_transaction_window: bool,
     show_expense_summary_window: bool,
+    show_income_summary_window: bool,
+    show_expenses_comparison_window: bool,
+    show_entity_spending_window: bool,
+    show_entity_transactions_window: bool,
     show_fund_stand_window: bool,
     show_browse_last_transactions_window: bool,
     show_browse_last_fund_movements_window: bool,
     show_fund_evolution_plot_window: bool,
     show_expense_category_plot_window: bool,
     show_expenses_evolution_window: bool,
+    show_cash_flow_window: bool,
+    show_unit_prices_window: bool,
+    show_runway_window: bool,
+    show_cash_flow_plot_window: bool,
+    show_balance_history_plot_window: bool,
+    show_independence_plot_window: bool,
+    show_import_journal_window: bool,
+    show_import_snapshot_window: bool,
+    show_party_attachments_window: bool,
+    show_settings_window: bool,
+    show_budgets_window: bool,
+    show_budget_vs_actual_window: bool,
+    show_goals_window: bool,
+    show_goals_report_window: bool,
+    show_template_window: bool,
+    show_templates_window: bool,
+    show_due_occurrences_window: bool,
+    show_subscriptions_window: bool,
+    show_anomalies_window: bool,
 
     database: DataBase,
 
@@ -46,6 +88,7 @@ pub struct AppState {
     account_initial_balance_tentative: String,
 
     party: Party,
+    party_attachments: Vec<Attachment>,
 
     transaction_value: f64,
     transaction_value_tentative: String,
@@ -55,6 +98,8 @@ pub struct AppState {
     transaction_category: String,
     transaction_subcategory: String,
     transaction_description: String,
+    transaction_quantity_tentative: String,
+    transaction_unit: String,
     transaction_entity_id: i64,
     transaction_entity_string: String,
     transaction_account_id: i64,
@@ -63,6 +108,10 @@ pub struct AppState {
     transaction_filter: String,
     #[derivative(Default(value = "PopupCloseBehavior::IgnoreClicks"))]
     transaction_entity_popup: PopupCloseBehavior,
+    transaction_suggestion_model: SuggestionModel,
+    transaction_suggestion_entity_id: i64,
+    transaction_suggested_category: String,
+    transaction_suggested_subcategory: String,
 
     expense_summary_csv: String,
     #[derivative(Default(value = "Local::now().date_naive()"))]
@@ -70,13 +119,80 @@ pub struct AppState {
     #[derivative(Default(value = "Local::now().date_naive()"))]
     expense_summary_date_to: NaiveDate,
     expense_summary_currency: Currency,
+    #[derivative(Default(
+        value = "vec![SummaryDimension::Category, SummaryDimension::Subcategory]"
+    ))]
+    expense_summary_dimensions: Vec<SummaryDimension>,
+
+    expenses_comparison_csv: String,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    expenses_comparison_previous_from: NaiveDate,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    expenses_comparison_previous_to: NaiveDate,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    expenses_comparison_current_from: NaiveDate,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    expenses_comparison_current_to: NaiveDate,
+    expenses_comparison_currency: Currency,
+    #[derivative(Default(value = "String::from(\"20\")"))]
+    expenses_comparison_threshold_tentative: String,
+
+    income_summary_csv: String,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    income_summary_date_from: NaiveDate,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    income_summary_date_to: NaiveDate,
+    income_summary_currency: Currency,
+
+    entity_spending_csv: String,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    entity_spending_date_from: NaiveDate,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    entity_spending_date_to: NaiveDate,
+    entity_spending_currency: Currency,
+    entity_spending_grouping: EntityGrouping,
+    entity_spending_csv_grouping: EntityGrouping,
+
+    entity_transactions_csv: String,
+    entity_transactions_title: String,
 
     fund_stand_csv: String,
     fund_stand_currency: Option<Currency>,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    fund_stand_date: NaiveDate,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    fund_stand_compare_date: NaiveDate,
+    fund_stand_valuation: Valuation,
+    fund_stand_grouping: FundGrouping,
+    fund_stand_comparing: bool,
 
     expenses_evolution_csv: String,
     expenses_evolution_currency: Currency,
     expenses_evolution_time_unit: TimeUnit,
+    #[derivative(Default(value = "TransactionType::Expense"))]
+    expenses_evolution_transaction_type: TransactionType,
+    expenses_evolution_category: Option<String>,
+    expenses_evolution_entity_id: Option<i64>,
+    expenses_evolution_currency_filter: Option<Currency>,
+
+    real_terms: Option<RealTerms>,
+
+    cash_flow_csv: String,
+    cash_flow_currency: Currency,
+    cash_flow_time_unit: TimeUnit,
+
+    unit_prices_index_csv: String,
+    unit_prices_history_csv: String,
+    unit_prices_currency: Currency,
+    unit_prices_time_unit: TimeUnit,
+
+    runway_report_csv: String,
+    runway_currency: Currency,
+    #[derivative(Default(value = "RUNWAY_WINDOW_MONTHS"))]
+    runway_window_months: u32,
+    /// Runway of the main window warning, and the day it was computed on
+    runway: Option<Runway>,
+    runway_date: Option<NaiveDate>,
 
     last_transactions_csv: String,
     last_transactions_n: usize,
@@ -91,15 +207,107 @@ pub struct AppState {
     browse_account_string: String,
 
     fund_evolution_plot_currency: Currency,
+    #[derivative(Default(value = "6"))]
+    fund_evolution_forecast_months: u32,
+    fund_evolution_forecast_warnings: Vec<String>,
 
     expense_category_plot_currency: Currency,
     expense_category_plot_type: BarplotType,
+
+    cash_flow_plot_currency: Currency,
+    cash_flow_plot_time_unit: TimeUnit,
+
+    balance_history_plot_currency: Currency,
+    balance_history_plot_grouping: FundGrouping,
+    balance_history_plot_account_ids: Vec<i64>,
+
+    independence_currency: Currency,
+    #[derivative(Default(value = "12"))]
+    independence_window_months: u32,
+    #[derivative(Default(value = "30"))]
+    independence_years: u32,
+    #[derivative(Default(value = "String::from(\"1\")"))]
+    independence_deposit_return_tentative: String,
+    #[derivative(Default(value = "String::from(\"5\")"))]
+    independence_investment_return_tentative: String,
+    #[derivative(Default(value = "String::from(\"0\")"))]
+    independence_cash_return_tentative: String,
+    #[derivative(Default(value = "String::from(\"4\")"))]
+    independence_withdrawal_rate_tentative: String,
+    #[derivative(Default(value = "String::from(\"0\")"))]
+    independence_savings_change_tentative: String,
+    #[derivative(Default(value = "String::from(\"0\")"))]
+    independence_return_change_tentative: String,
+    independence_report_csv: String,
+
+    export_message: String,
+
+    import_journal_path: String,
+    import_journal_country: String,
+    import_journal_transactions: Vec<JournalTransaction>,
+    import_journal_refused: Vec<String>,
+    import_journal_mapping: JournalMapping,
+    import_journal_message: String,
+
+    import_snapshot_path: String,
+    import_snapshot_message: String,
+
+    attachment_path: String,
+    attachment_message: String,
+    attachments_party_id: i64,
+
+    settings: Settings,
+    settings_message: String,
+
+    budget_category: String,
+    budget_subcategory: Option<String>,
+    budget_period: TimeUnit,
+    budget_value_tentative: String,
+    budget_currency: Currency,
+    budget_rollover: bool,
+
+    budget_vs_actual_csv: String,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    budget_vs_actual_date: NaiveDate,
+
+    /// Spent and available amounts of the monthly budgets, and the day they were computed on
+    budget_progress: Option<(f64, f64)>,
+    budget_progress_date: Option<NaiveDate>,
+
+    goal_name: String,
+    goal_target_value_tentative: String,
+    goal_currency: Currency,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    goal_target_date: NaiveDate,
+    goal_allocation_id: Option<i64>,
+    goal_allocation_tentative: String,
+
+    goals_report_csv: String,
+    goals_report_currency: Currency,
+
+    template_name: String,
+    template_recurrence: Recurrence,
+    #[derivative(Default(value = "Local::now().date_naive()"))]
+    template_start_date: NaiveDate,
+    template_end_date: Option<NaiveDate>,
+
+    due_occurrences_checked: bool,
+    due_occurrences: Vec<Occurrence>,
+    due_occurrence_values_tentative: Vec<String>,
+
+    subscriptions: Vec<Subscription>,
+
+    anomalies: Vec<Anomaly>,
 }
 
 impl eframe::App for AppState {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) -> () {
         egui_extras::install_image_loaders(ctx);
 
+        if !self.due_occurrences_checked {
+            self.check_due_occurrences();
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             StripBuilder::new(ui)
                 .size(Size::exact(20.0))
@@ -116,12 +324,30 @@ impl eframe::App for AppState {
                                 if ui.button("Expenses by Category").clicked() {
                                     self.show_expense_summary_window = true;
                                 }
+                                if ui.button("Expenses Comparison").clicked() {
+                                    self.show_expenses_comparison_window = true;
+                                }
+                                if ui.button("Income by Category").clicked() {
+                                    self.show_income_summary_window = true;
+                                }
+                                if ui.button("Expenses by Entity").clicked() {
+                                    self.show_entity_spending_window = true;
+                                }
                                 if ui.button("Funds by Account").clicked() {
                                     self.show_fund_stand_window = true;
                                 }
                                 if ui.button("Expenses Evolution").clicked() {
                                     self.show_expenses_evolution_window = true;
                                 }
+                                if ui.button("Cash Flow").clicked() {
+                                    self.show_cash_flow_window = true;
+                                }
+                                if ui.button("Unit Prices and Personal Inflation").clicked() {
+                                    self.show_unit_prices_window = true;
+                                }
+                                if ui.button("Liquidity Runway").clicked() {
+                                    self.show_runway_window = true;
+                                }
                             });
                             ui.end_row();
 
@@ -132,6 +358,31 @@ impl eframe::App for AppState {
                                 if ui.button("Expenses by Category and Month").clicked() {
                                     self.show_expense_category_plot_window = true;
                                 }
+                                if ui.button("Cash Flow and Savings Rate").clicked() {
+                                    self.show_cash_flow_plot_window = true;
+                                }
+                                if ui.button("Balance History by Account").clicked() {
+                                    self.show_balance_history_plot_window = true;
+                                }
+                                if ui.button("Financial Independence").clicked() {
+                                    self.show_independence_plot_window = true;
+                                }
+                            });
+                            ui.end_row();
+
+                            ui.menu_button("Budgets and Goals", |ui| {
+                                if ui.button("Edit Budgets").clicked() {
+                                    self.show_budgets_window = true;
+                                }
+                                if ui.button("Budget vs Actual").clicked() {
+                                    self.show_budget_vs_actual_window = true;
+                                }
+                                if ui.button("Savings Goals").clicked() {
+                                    self.show_goals_window = true;
+                                }
+                                if ui.button("Savings Goals Report").clicked() {
+                                    self.show_goals_report_window = true;
+                                }
                             });
                             ui.end_row();
 
@@ -142,8 +393,107 @@ impl eframe::App for AppState {
                                 if ui.button("Last fund movements").clicked() {
                                     self.show_browse_last_fund_movements_window = true;
                                 }
+                                if ui.button("Recurring parties").clicked() {
+                                    self.show_templates_window = true;
+                                }
+                                if ui.button("Subscriptions").clicked() {
+                                    self.show_subscriptions_window = true;
+                                }
+                                if ui.button("Unusual expenses").clicked() {
+                                    self.check_anomalies(None);
+                                }
                             });
                             ui.end_row();
+
+                            ui.menu_button("Exporting", |ui| {
+                                for journal_format in JournalFormat::iter() {
+                                    if ui
+                                        .button(format!("{} journal", journal_format))
+                                        .clicked()
+                                    {
+                                        self.export_message =
+                                            match self.database.export_journal(&journal_format) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                    }
+                                }
+                                if ui.button("Spreadsheet").clicked() {
+                                    self.export_message =
+                                        match self.database.export_ledger_spreadsheet() {
+                                            Ok(file_name) => format!("Exported to {}", file_name),
+                                            Err(e) => e,
+                                        };
+                                }
+                                if ui.button("JSON snapshot").clicked() {
+                                    self.export_message = match self.database.export_snapshot() {
+                                        Ok(file_name) => format!("Exported to {}", file_name),
+                                        Err(e) => e,
+                                    };
+                                }
+                            });
+                            ui.end_row();
+
+                            ui.menu_button("Importing", |ui| {
+                                if ui.button("Beancount or hledger journal").clicked() {
+                                    self.show_import_journal_window = true;
+                                }
+                                if ui.button("JSON snapshot").clicked() {
+                                    self.show_import_snapshot_window = true;
+                                }
+                            });
+                            ui.end_row();
+
+                            if ui.button("Settings").clicked() {
+                                self.settings = self.database.settings().clone();
+                                self.settings_message.clear();
+                                self.show_settings_window = true;
+                            }
+                            ui.end_row();
+
+                            self.refresh_budget_progress();
+                            if let Some((actual, available)) = self.budget_progress {
+                                let progress: f32 = match available > 0.0 {
+                                    true => (actual / available) as f32,
+                                    false => 1.0,
+                                };
+                                let mut progress_bar = egui::ProgressBar::new(progress.min(1.0))
+                                    .text(format!(
+                                        "Monthly budgets: {:.2} of {:.2} {}",
+                                        actual,
+                                        available,
+                                        Currency::default()
+                                    ));
+                                if progress > 1.0 {
+                                    progress_bar =
+                                        progress_bar.fill(egui::Color32::from_rgb(255, 0, 0));
+                                }
+                                ui.add(progress_bar);
+                                ui.end_row();
+                            }
+
+                            self.refresh_runway();
+                            let min_runway_months: u32 =
+                                self.database.settings().min_runway_months;
+                            if let Some(months) =
+                                self.runway.as_ref().and_then(|runway| runway.months())
+                            {
+                                if min_runway_months > 0 && months < min_runway_months as f64 {
+                                    ui.colored_label(
+                                        egui::Color32::from_rgb(255, 0, 0),
+                                        format!(
+                                            "Liquid funds cover {:.1} months of expenses, below the minimum of {}",
+                                            months, min_runway_months
+                                        ),
+                                    );
+                                    ui.end_row();
+                                }
+                            }
+
+                            if !self.export_message.is_empty() {
+                                ui.label(self.export_message.as_str());
+                                ui.end_row();
+                            }
                         });
                     });
                 });
@@ -169,6 +519,22 @@ impl eframe::App for AppState {
             self.handle_show_expense_summary_window(ctx)
         }
 
+        if self.show_expenses_comparison_window {
+            self.handle_show_expenses_comparison_window(ctx)
+        }
+
+        if self.show_income_summary_window {
+            self.handle_show_income_summary_window(ctx)
+        }
+
+        if self.show_entity_spending_window {
+            self.handle_show_entity_spending_window(ctx)
+        }
+
+        if self.show_entity_transactions_window {
+            self.handle_show_entity_transactions_window(ctx)
+        }
+
         if self.show_fund_stand_window {
             self.handle_show_fund_stand_window(ctx)
         }
@@ -177,6 +543,18 @@ impl eframe::App for AppState {
             self.handle_show_expenses_evolution_window(ctx)
         }
 
+        if self.show_cash_flow_window {
+            self.handle_show_cash_flow_window(ctx)
+        }
+
+        if self.show_unit_prices_window {
+            self.handle_show_unit_prices_window(ctx)
+        }
+
+        if self.show_runway_window {
+            self.handle_show_runway_window(ctx)
+        }
+
         if self.show_browse_last_transactions_window {
             self.handle_show_browse_last_transactions_window(ctx)
         }
@@ -192,5 +570,69 @@ impl eframe::App for AppState {
         if self.show_expense_category_plot_window {
             self.handle_show_expense_category_plot(ctx);
         }
+
+        if self.show_cash_flow_plot_window {
+            self.handle_show_cash_flow_plot(ctx);
+        }
+
+        if self.show_balance_history_plot_window {
+            self.handle_show_balance_history_plot(ctx);
+        }
+
+        if self.show_independence_plot_window {
+            self.handle_show_independence_plot(ctx);
+        }
+
+        if self.show_import_journal_window {
+            self.handle_show_import_journal_window(ctx);
+        }
+
+        if self.show_import_snapshot_window {
+            self.handle_show_import_snapshot_window(ctx);
+        }
+
+        if self.show_party_attachments_window {
+            self.handle_show_party_attachments_window(ctx);
+        }
+
+        if self.show_settings_window {
+            self.handle_show_settings_window(ctx);
+        }
+
+        if self.show_budgets_window {
+            self.handle_show_budgets_window(ctx);
+        }
+
+        if self.show_budget_vs_actual_window {
+            self.handle_show_budget_vs_actual_window(ctx);
+        }
+
+        if self.show_goals_window {
+            self.handle_show_goals_window(ctx);
+        }
+
+        if self.show_goals_report_window {
+            self.handle_show_goals_report_window(ctx);
+        }
+
+        if self.show_template_window {
+            self.handle_show_template_window(ctx);
+        }
+
+        if self.show_templates_window {
+            self.handle_show_templates_window(ctx);
+        }
+
+        if self.show_due_occurrences_window {
+            self.handle_show_due_occurrences_window(ctx);
+        }
+
+        if self.show_subscriptions_window {
+            self.handle_show_subscriptions_window(ctx);
+        }
+
+        if self.show_anomalies_window {
+            self.handle_show_anomalies_window(ctx);
+        }
     }
 }
@@ -1,8 +1,11 @@
+use crate::modules::database::independence::IndependencePlan;
 use crate::modules::database::plotter::BarplotType;
+use crate::modules::database::summaries::{FundGrouping, TimeUnit};
 use crate::modules::financial::*;
 use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use chrono::Local;
 use eframe::egui;
-use eframe::egui::ComboBox;
+use eframe::egui::{Align, Color32, ComboBox, Layout};
 use egui_extras::*;
 use strum::IntoEnumIterator;
 
@@ -50,16 +53,63 @@ impl AppState {
                                             });
                                         ui.end_row();
 
+                                        ui.label("Forecast:").on_hover_text(
+                                            "Months to forecast from today, from the recurring parties and the average incomes and expenses.",
+                                        );
+                                        ComboBox::from_id_salt("Fund evolution forecast months")
+                                            .selected_text(match self.fund_evolution_forecast_months {
+                                                0 => String::from("None"),
+                                                months => format!("{} months", months),
+                                            })
+                                            .show_ui(ui, |ui| {
+                                                ui.selectable_value(
+                                                    &mut self.fund_evolution_forecast_months,
+                                                    0,
+                                                    "None",
+                                                );
+                                                for months in 3..=12 {
+                                                    ui.selectable_value(
+                                                        &mut self.fund_evolution_forecast_months,
+                                                        months,
+                                                        format!("{} months", months),
+                                                    );
+                                                }
+                                            });
+                                        ui.end_row();
+
                                         ui.label("");
                                         if ui.button("Generate!").clicked() {
                                             self.database.funds_evolution(
                                                 &self.fund_evolution_plot_currency,
+                                                self.fund_evolution_forecast_months,
                                             );
+                                            self.fund_evolution_forecast_warnings = self
+                                                .database
+                                                .forecast_warnings(
+                                                    Local::now().date_naive(),
+                                                    self.fund_evolution_forecast_months,
+                                                )
+                                                .into_iter()
+                                                .map(|(account_id, date, lowest)| {
+                                                    let account: Account =
+                                                        self.database.account(account_id);
+                                                    format!(
+                                                        "{} is projected to go negative on {}, down to {:.2} {}",
+                                                        account.name(),
+                                                        date,
+                                                        lowest,
+                                                        account.currency()
+                                                    )
+                                                })
+                                                .collect();
 
                                             // forget the old one
                                             ui.ctx().forget_all_images();
                                         }
                                     });
+                                for warning in self.fund_evolution_forecast_warnings.iter() {
+                                    ui.colored_label(Color32::from_rgb(255, 0, 0), warning);
+                                }
                                 ui.separator();
                             });
                             strip.cell(|ui| {
@@ -163,4 +213,478 @@ impl AppState {
             },
         );
     }
+
+    pub fn handle_show_cash_flow_plot(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("cash_flow_plot_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Cash flow plot window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("cash_flow_plot")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Currency:").on_hover_text(
+                                            "Currency in which to express the ammounts.",
+                                        );
+                                        ComboBox::from_id_salt("Cash flow plot currency")
+                                            .selected_text(format!(
+                                                "{}",
+                                                self.cash_flow_plot_currency
+                                            ))
+                                            .show_ui(ui, |ui| {
+                                                for possible_cash_flow_plot_currency in
+                                                    Currency::iter()
+                                                {
+                                                    ui.selectable_value(
+                                            &mut self.cash_flow_plot_currency,
+                                            possible_cash_flow_plot_currency.clone(),
+                                            format!("{possible_cash_flow_plot_currency}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Time unit:").on_hover_text(
+                                            "Time unit to aggregate incomes, expenses and fund movements.",
+                                        );
+                                        ComboBox::from_id_salt("Cash flow plot time unit")
+                                            .selected_text(format!(
+                                                "{}",
+                                                self.cash_flow_plot_time_unit
+                                            ))
+                                            .show_ui(ui, |ui| {
+                                                for possible_cash_flow_plot_time_unit in
+                                                    TimeUnit::iter()
+                                                {
+                                                    ui.selectable_value(
+                                            &mut self.cash_flow_plot_time_unit,
+                                            possible_cash_flow_plot_time_unit.clone(),
+                                            format!("{possible_cash_flow_plot_time_unit}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if ui.button("Generate!").clicked() {
+                                            self.database.cash_flow_plot(
+                                                &self.cash_flow_plot_currency,
+                                                &self.cash_flow_plot_time_unit,
+                                                "figures/cash_flow.svg",
+                                            );
+
+                                            ui.ctx().forget_all_images();
+                                        }
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                ui.image("file://figures/cash_flow.svg");
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_cash_flow_plot_window = false;
+                }
+            },
+        );
+    }
+    pub fn handle_show_balance_history_plot(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("balance_history_plot_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Balance history plot window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("balance_history_plot")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Currency:").on_hover_text(
+                                            "Currency in which to express the ammounts.",
+                                        );
+                                        ComboBox::from_id_salt("Balance history plot currency")
+                                            .selected_text(format!(
+                                                "{}",
+                                                self.balance_history_plot_currency
+                                            ))
+                                            .show_ui(ui, |ui| {
+                                                for possible_balance_history_plot_currency in
+                                                    Currency::iter()
+                                                {
+                                                    ui.selectable_value(
+                                            &mut self.balance_history_plot_currency,
+                                            possible_balance_history_plot_currency.clone(),
+                                            format!("{possible_balance_history_plot_currency}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Group by:").on_hover_text(
+                                            "Draw one layer per account, per account type or per country.",
+                                        );
+                                        ComboBox::from_id_salt("Balance history plot grouping")
+                                            .selected_text(format!(
+                                                "{}",
+                                                self.balance_history_plot_grouping
+                                            ))
+                                            .show_ui(ui, |ui| {
+                                                for possible_balance_history_plot_grouping in
+                                                    FundGrouping::iter()
+                                                {
+                                                    ui.selectable_value(
+                                            &mut self.balance_history_plot_grouping,
+                                            possible_balance_history_plot_grouping.clone(),
+                                            format!("{possible_balance_history_plot_grouping}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Accounts:").on_hover_text(
+                                            "Accounts to include. Select none to include all of them.",
+                                        );
+                                        ui.menu_button(
+                                            format!(
+                                                "{} selected",
+                                                self.balance_history_plot_account_ids.len()
+                                            ),
+                                            |ui| {
+                                                for account_id in self.database.iter_account_ids() {
+                                                    let mut selected: bool = self
+                                                        .balance_history_plot_account_ids
+                                                        .contains(&account_id);
+                                                    if ui
+                                                        .checkbox(
+                                                            &mut selected,
+                                                            self.database
+                                                                .account(account_id)
+                                                                .to_string(),
+                                                        )
+                                                        .changed()
+                                                    {
+                                                        if selected {
+                                                            self.balance_history_plot_account_ids
+                                                                .push(account_id);
+                                                        } else {
+                                                            self.balance_history_plot_account_ids
+                                                                .retain(|id| *id != account_id);
+                                                        }
+                                                    }
+                                                }
+                                            },
+                                        );
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if ui.button("Generate!").clicked() {
+                                            self.database.balance_history_plot(
+                                                &self.balance_history_plot_currency,
+                                                &self.balance_history_plot_grouping,
+                                                &self.balance_history_plot_account_ids,
+                                            );
+
+                                            ui.ctx().forget_all_images();
+                                        }
+                                        if ui
+                                            .button("Export")
+                                            .on_hover_text(
+                                                "Writes the daily balances to a spreadsheet in the data folder.",
+                                            )
+                                            .clicked()
+                                        {
+                                            self.export_message =
+                                                match self.database.export_balance_history(
+                                                    Some(&self.balance_history_plot_currency),
+                                                    &self.balance_history_plot_grouping,
+                                                    &self.balance_history_plot_account_ids,
+                                                ) {
+                                                    Ok(file_name) => {
+                                                        format!("Exported to {}", file_name)
+                                                    }
+                                                    Err(e) => e,
+                                                };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                ui.image("file://figures/balance_history.svg");
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_balance_history_plot_window = false;
+                }
+            },
+        );
+    }
+
+    /// Plan of the financial independence window, or None if any of its numbers is invalid.
+    fn independence_plan(&self) -> Option<IndependencePlan> {
+        let plan: IndependencePlan = IndependencePlan {
+            window_months: self.independence_window_months,
+            deposit_return: self.independence_deposit_return_tentative.parse::<f64>().ok()?,
+            investment_return: self
+                .independence_investment_return_tentative
+                .parse::<f64>()
+                .ok()?,
+            cash_return: self.independence_cash_return_tentative.parse::<f64>().ok()?,
+            withdrawal_rate: self
+                .independence_withdrawal_rate_tentative
+                .parse::<f64>()
+                .ok()?,
+            savings_change: self.independence_savings_change_tentative.parse::<f64>().ok()?,
+            return_change: self.independence_return_change_tentative.parse::<f64>().ok()?,
+        };
+
+        Some(plan).filter(IndependencePlan::is_valid)
+    }
+
+    pub fn handle_show_independence_plot(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("independence_plot_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Financial independence plot window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let report_csv = self.independence_report_csv.clone();
+                    let header_line: String = report_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = report_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+                    let plan: Option<IndependencePlan> = self.independence_plan();
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::initial(240.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("independence_plot")
+                                    .num_columns(4)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Currency:").on_hover_text(
+                                            "Currency in which to express the ammounts.",
+                                        );
+                                        ComboBox::from_id_salt("Independence plot currency")
+                                            .selected_text(format!(
+                                                "{}",
+                                                self.independence_currency
+                                            ))
+                                            .show_ui(ui, |ui| {
+                                                for possible_independence_currency in
+                                                    Currency::iter()
+                                                {
+                                                    ui.selectable_value(
+                                            &mut self.independence_currency,
+                                            possible_independence_currency.clone(),
+                                            format!("{possible_independence_currency}"),
+                                        );
+                                                }
+                                            });
+
+                                        ui.label("Years:").on_hover_text(
+                                            "Years to project from today.",
+                                        );
+                                        ComboBox::from_id_salt("Independence plot years")
+                                            .selected_text(format!(
+                                                "{} years",
+                                                self.independence_years
+                                            ))
+                                            .show_ui(ui, |ui| {
+                                                for years in (10..=50).step_by(10) {
+                                                    ui.selectable_value(
+                                                        &mut self.independence_years,
+                                                        years,
+                                                        format!("{} years", years),
+                                                    );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("History:").on_hover_text(
+                                            "Months of incomes and expenses to average the savings and the expenses over.",
+                                        );
+                                        ComboBox::from_id_salt("Independence plot window months")
+                                            .selected_text(format!(
+                                                "{} months",
+                                                self.independence_window_months
+                                            ))
+                                            .show_ui(ui, |ui| {
+                                                for months in [3, 6, 12, 24, 36] {
+                                                    ui.selectable_value(
+                                                        &mut self.independence_window_months,
+                                                        months,
+                                                        format!("{} months", months),
+                                                    );
+                                                }
+                                            });
+
+                                        ui.label("Withdrawal rate (%):").on_hover_text(
+                                            "Share of the net worth that can be withdrawn every year once independent.",
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut self.independence_withdrawal_rate_tentative,
+                                        );
+                                        ui.end_row();
+
+                                        ui.label("Deposit return (%):").on_hover_text(
+                                            "Expected yearly return of the deposit accounts.",
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut self.independence_deposit_return_tentative,
+                                        );
+
+                                        ui.label("Investment return (%):").on_hover_text(
+                                            "Expected yearly return of the investment accounts.",
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut self.independence_investment_return_tentative,
+                                        );
+                                        ui.end_row();
+
+                                        ui.label("Cash return (%):").on_hover_text(
+                                            "Expected yearly return of the cash accounts.",
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut self.independence_cash_return_tentative,
+                                        );
+                                        ui.end_row();
+
+                                        ui.label("What if savings change (%):").on_hover_text(
+                                            "Change of the monthly savings, in percent of the average ones.",
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut self.independence_savings_change_tentative,
+                                        );
+
+                                        ui.label("What if returns change (pp):").on_hover_text(
+                                            "Change of every expected return, in percentage points.",
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut self.independence_return_change_tentative,
+                                        );
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        match plan {
+                                            Some(plan) => {
+                                                if ui.button("Generate!").clicked() {
+                                                    self.database.independence_plot(
+                                                        Local::now().date_naive(),
+                                                        &self.independence_currency,
+                                                        &plan,
+                                                        self.independence_years,
+                                                    );
+                                                    self.independence_report_csv =
+                                                        self.database.independence_report(
+                                                            Local::now().date_naive(),
+                                                            &self.independence_currency,
+                                                            &plan,
+                                                            self.independence_years,
+                                                        );
+
+                                                    // forget the old one
+                                                    ui.ctx().forget_all_images();
+                                                }
+                                                if ui.button("Export").on_hover_text("Writes the financial independence report to a spreadsheet in the data folder.").clicked() {
+                                                    self.export_message = match self.database.export_independence_report(
+                                                        Local::now().date_naive(),
+                                                        &self.independence_currency,
+                                                        &plan,
+                                                        self.independence_years,
+                                                    ) {
+                                                        Ok(file_name) => format!("Exported to {}", file_name),
+                                                        Err(e) => e,
+                                                    };
+                                                }
+                                            }
+                                            None => {
+                                                ui.colored_label(
+                                                    Color32::from_rgb(255, 0, 0),
+                                                    "Invalid rates",
+                                                );
+                                            }
+                                        }
+                                        ui.label(self.export_message.as_str());
+                                        ui.end_row();
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    for element in row_line.split(",") {
+                                                        row_ui.col(|ui| {
+                                                                ui.label(element);
+                                                        });
+                                                    }
+                                                });
+                                            }
+                                        });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                ui.image("file://figures/independence.svg");
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_independence_plot_window = false;
+                }
+            },
+        );
+    }
 }
@@ -0,0 +1,88 @@
+use crate::modules::database::anomalies::Anomaly;
+use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use chrono::Local;
+use eframe::egui;
+
+impl AppState {
+    /// Looks for anomalies in the expenses. If a party was just entered, the review window is
+    /// only shown when the party is involved in one of them.
+    pub(crate) fn check_anomalies(&mut self, party_id: Option<i64>) -> () {
+        self.anomalies = self.database.anomalies();
+        self.show_anomalies_window = match party_id {
+            Some(party_id) => self
+                .anomalies
+                .iter()
+                .any(|anomaly| anomaly.party_ids.contains(&party_id)),
+            None => true,
+        };
+    }
+
+    pub fn handle_show_anomalies_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("anomalies_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Anomalies window")
+                .with_inner_size([WINDOW_WIDTH * 1.5, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{} unusual expenses to review. Dismissed ones are not flagged again.",
+                            self.anomalies.len()
+                        ));
+                        if ui.button("Check again").clicked() {
+                            self.anomalies = self.database.anomalies();
+                        }
+                    });
+                    ui.separator();
+
+                    let mut dismissed: Option<Anomaly> = None;
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("anomalies")
+                            .num_columns(5)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for anomaly in self.anomalies.iter() {
+                                    ui.label(anomaly.date.to_string());
+                                    ui.label(anomaly.kind.to_string());
+                                    ui.label(format!("{:.2} {}", anomaly.value, anomaly.currency))
+                                        .on_hover_text(format!(
+                                            "Parties {}",
+                                            anomaly
+                                                .party_ids
+                                                .iter()
+                                                .map(|party_id| party_id.to_string())
+                                                .collect::<Vec<String>>()
+                                                .join(", ")
+                                        ));
+                                    ui.label(anomaly.description.as_str());
+                                    if ui
+                                        .button("Dismiss")
+                                        .on_hover_text("The expenses are fine as they are.")
+                                        .clicked()
+                                    {
+                                        dismissed = Some(anomaly.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                    if let Some(anomaly) = dismissed {
+                        self.database
+                            .dismiss_anomaly(&anomaly, Local::now().date_naive());
+                        self.database.save();
+                        self.anomalies.retain(|other| other.key != anomaly.key);
+                    }
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_anomalies_window = false;
+                }
+            },
+        )
+    }
+}
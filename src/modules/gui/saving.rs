@@ -0,0 +1,277 @@
+use crate::modules::database::goals::Goal;
+use crate::modules::financial::*;
+use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use chrono::{Local, NaiveDate};
+use eframe::egui;
+use egui::{Align, Color32, ComboBox, Layout};
+use egui_extras::*;
+use strum::IntoEnumIterator;
+
+impl AppState {
+    pub fn handle_show_goals_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("goals_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Savings goals window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let today: NaiveDate = Local::now().date_naive();
+                    let target_value: Option<f64> =
+                        self.goal_target_value_tentative.parse::<f64>().ok();
+                    let allocation: Option<f64> =
+                        self.goal_allocation_tentative.parse::<f64>().ok();
+                    let goals: Vec<(i64, Goal)> = self.database.goals();
+
+                    egui::Grid::new("goal")
+                        .num_columns(2)
+                        .spacing([45.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Name:")
+                                .on_hover_text("What the savings are for, e.g. a new car.");
+                            ui.text_edit_singleline(&mut self.goal_name);
+                            ui.end_row();
+
+                            ui.label("Target:");
+                            ui.horizontal(|ui| {
+                                ui.text_edit_singleline(&mut self.goal_target_value_tentative);
+                                ComboBox::from_id_salt("Goal currency")
+                                    .selected_text(format!("{}", self.goal_currency))
+                                    .show_ui(ui, |ui| {
+                                        for currency in Currency::iter() {
+                                            ui.selectable_value(
+                                                &mut self.goal_currency,
+                                                currency.clone(),
+                                                format!("{currency}"),
+                                            );
+                                        }
+                                    });
+                                if target_value.is_none() {
+                                    ui.colored_label(
+                                        Color32::from_rgb(255, 0, 0),
+                                        "Invalid amount!",
+                                    );
+                                }
+                            });
+                            ui.end_row();
+
+                            ui.label("Target date:")
+                                .on_hover_text("Date by which the target should be reached.");
+                            ui.add(
+                                DatePickerButton::new(&mut self.goal_target_date)
+                                    .id_salt("goal_target_date"),
+                            );
+                            ui.end_row();
+
+                            ui.label("");
+                            if let Some(target_value) = target_value {
+                                if !self.goal_name.is_empty() && ui.button("Add goal").clicked() {
+                                    self.database.insert_goal(&Goal {
+                                        name: self.goal_name.clone(),
+                                        target_value,
+                                        currency: self.goal_currency.clone(),
+                                        target_date: self.goal_target_date,
+                                    });
+                                    self.database.save();
+                                    self.goal_name.clear();
+                                }
+                            }
+                            ui.end_row();
+                        });
+                    ui.separator();
+
+                    egui::Grid::new("allocation")
+                        .num_columns(2)
+                        .spacing([45.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Allocate:").on_hover_text(
+                                "Earmark part of the funds for a goal, in the currency of the goal. A negative amount releases it.",
+                            );
+                            ui.horizontal(|ui| {
+                                let selected_goal: String = goals
+                                    .iter()
+                                    .find(|(goal_id, _goal)| Some(*goal_id) == self.goal_allocation_id)
+                                    .map(|(_goal_id, goal)| goal.name.clone())
+                                    .unwrap_or_default();
+                                ComboBox::from_id_salt("Allocation goal")
+                                    .selected_text(selected_goal)
+                                    .show_ui(ui, |ui| {
+                                        for (goal_id, goal) in goals.iter() {
+                                            ui.selectable_value(
+                                                &mut self.goal_allocation_id,
+                                                Some(*goal_id),
+                                                goal.name.as_str(),
+                                            );
+                                        }
+                                    });
+                                ui.text_edit_singleline(&mut self.goal_allocation_tentative);
+                                if let (Some(goal_id), Some(allocation)) =
+                                    (self.goal_allocation_id, allocation)
+                                {
+                                    if ui.button("Allocate").clicked() {
+                                        self.database.allocate(goal_id, allocation, today);
+                                        self.database.save();
+                                        self.goal_allocation_tentative.clear();
+                                    }
+                                }
+                            });
+                            ui.end_row();
+                        });
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("goals")
+                            .num_columns(4)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (goal_id, goal) in goals.iter() {
+                                    ui.label(goal.name.as_str());
+                                    ui.label(format!(
+                                        "{:.2} of {:.2} {}",
+                                        self.database.allocated(*goal_id, today),
+                                        goal.target_value,
+                                        goal.currency
+                                    ));
+                                    ui.label(format!("by {}", goal.target_date));
+                                    if ui
+                                        .button("Remove")
+                                        .on_hover_text("Deletes the goal and its allocations.")
+                                        .clicked()
+                                    {
+                                        self.database.delete_goal(*goal_id);
+                                        self.database.save();
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_goals_window = false;
+                }
+            },
+        )
+    }
+
+    pub fn handle_show_goals_report_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("goals_report_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Savings goals report window")
+                .with_inner_size([WINDOW_WIDTH * 1.5, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let goals_report_csv = self.goals_report_csv.clone();
+                    let header_line: String =
+                        goals_report_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> =
+                        goals_report_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("goals_report")
+                                    .num_columns(2)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Currency:").on_hover_text(
+                                            "Currency in which to express the ammounts.",
+                                        );
+                                        ComboBox::from_id_salt("Goals report currency")
+                                            .selected_text(format!("{}", self.goals_report_currency))
+                                            .show_ui(ui, |ui| {
+                                                for currency in Currency::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.goals_report_currency,
+                                                        currency.clone(),
+                                                        format!("{currency}"),
+                                                    );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.horizontal(|ui| {
+                                            let today: NaiveDate = Local::now().date_naive();
+                                            if ui.button("Generate!").clicked() {
+                                                self.goals_report_csv = self
+                                                    .database
+                                                    .goals_report(&self.goals_report_currency, today);
+                                            }
+                                            if ui
+                                                .button("Export")
+                                                .on_hover_text(
+                                                    "Writes the report to a spreadsheet in the data folder.",
+                                                )
+                                                .clicked()
+                                            {
+                                                self.export_message = match self
+                                                    .database
+                                                    .export_goals_report(&self.goals_report_currency, today)
+                                                {
+                                                    Ok(file_name) => format!("Exported to {}", file_name),
+                                                    Err(e) => e,
+                                                };
+                                            }
+                                        });
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                TableBuilder::new(ui)
+                                    .columns(Column::auto().resizable(true), column_count)
+                                    .striped(true)
+                                    .cell_layout(Layout::right_to_left(Align::Center))
+                                    .header(20.0, |mut header| {
+                                        for column_name in header_line.split(",") {
+                                            header.col(|ui| {
+                                                ui.strong(column_name).on_hover_text(column_name);
+                                            });
+                                        }
+                                    })
+                                    .body(|mut body| {
+                                        for row_line in row_lines {
+                                            body.row(30.0, |mut row_ui| {
+                                                // Unallocated and total funds rows
+                                                let is_total_row: bool =
+                                                    row_line.starts_with("Unallocated,")
+                                                        || row_line.starts_with("Total Funds,");
+                                                for element in row_line.split(",") {
+                                                    row_ui.col(|ui| {
+                                                        if is_total_row {
+                                                            ui.strong(element);
+                                                        } else {
+                                                            ui.label(element);
+                                                        }
+                                                    });
+                                                }
+                                            });
+                                        }
+                                    });
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_goals_report_window = false;
+                }
+            },
+        )
+    }
+}
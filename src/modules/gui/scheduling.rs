@@ -0,0 +1,421 @@
+use crate::modules::database::recurring::{Recurrence, Template};
+use crate::modules::database::subscriptions::Subscription;
+use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use chrono::{Datelike, Local, NaiveDate};
+use eframe::egui;
+use egui::{Color32, ComboBox};
+use egui_extras::*;
+
+impl AppState {
+    /// Looks for the occurrences of the templates due until today, and shows them if there are
+    /// any. Meant to run once, on startup.
+    pub(crate) fn check_due_occurrences(&mut self) -> () {
+        self.due_occurrences_checked = true;
+        self.due_occurrences = self.database.due_occurrences(Local::now().date_naive());
+        self.due_occurrence_values_tentative = self
+            .due_occurrences
+            .iter()
+            .map(|occurrence| format!("{:.2}", occurrence.value()))
+            .collect();
+        self.show_due_occurrences_window = !self.due_occurrences.is_empty();
+    }
+
+    pub fn handle_show_template_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("template_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Recurring party window")
+                .with_inner_size([WINDOW_WIDTH / 1.5, WINDOW_HEIGHT / 1.5]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let start_date = self.template_start_date;
+                    let recurrences: [Recurrence; 3] = [
+                        Recurrence::Monthly {
+                            day: start_date.day(),
+                        },
+                        Recurrence::Yearly {
+                            month: start_date.month(),
+                            day: start_date.day(),
+                        },
+                        Recurrence::Weekly { weeks: 1 },
+                    ];
+
+                    egui::Grid::new("template")
+                        .num_columns(2)
+                        .spacing([45.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Name:")
+                                .on_hover_text("Name of the recurring party, e.g. Rent.");
+                            ui.text_edit_singleline(&mut self.template_name);
+                            ui.end_row();
+
+                            ui.label("Starts on:")
+                                .on_hover_text("No occurrence is due before this date.");
+                            ui.add(
+                                DatePickerButton::new(&mut self.template_start_date)
+                                    .id_salt("template_start_date"),
+                            );
+                            ui.end_row();
+
+                            ui.label("Repeats:");
+                            ui.horizontal(|ui| {
+                                ComboBox::from_id_salt("Template recurrence")
+                                    .selected_text(format!("{}", self.template_recurrence))
+                                    .show_ui(ui, |ui| {
+                                        for recurrence in recurrences {
+                                            ui.selectable_value(
+                                                &mut self.template_recurrence,
+                                                recurrence.clone(),
+                                                format!("{recurrence}"),
+                                            );
+                                        }
+                                    });
+                                match &mut self.template_recurrence {
+                                    Recurrence::Monthly { day } => {
+                                        ComboBox::from_id_salt("Template day")
+                                            .selected_text(format!("Day {}", day))
+                                            .show_ui(ui, |ui| {
+                                                for possible_day in 1..=31 {
+                                                    ui.selectable_value(
+                                                        day,
+                                                        possible_day,
+                                                        format!("Day {}", possible_day),
+                                                    );
+                                                }
+                                            });
+                                    }
+                                    Recurrence::Weekly { weeks } => {
+                                        ComboBox::from_id_salt("Template weeks")
+                                            .selected_text(format!("Every {} weeks", weeks))
+                                            .show_ui(ui, |ui| {
+                                                for possible_weeks in 1..=52 {
+                                                    ui.selectable_value(
+                                                        weeks,
+                                                        possible_weeks,
+                                                        format!("Every {} weeks", possible_weeks),
+                                                    );
+                                                }
+                                            });
+                                    }
+                                    Recurrence::Yearly { .. } => (),
+                                }
+                            });
+                            ui.end_row();
+
+                            ui.label("Ends on:")
+                                .on_hover_text("No occurrence is due after this date.");
+                            ui.horizontal(|ui| {
+                                let mut has_end_date: bool = self.template_end_date.is_some();
+                                if ui.checkbox(&mut has_end_date, "").changed() {
+                                    self.template_end_date = match has_end_date {
+                                        true => Some(start_date),
+                                        false => None,
+                                    };
+                                }
+                                if let Some(end_date) = self.template_end_date.as_mut() {
+                                    ui.add(
+                                        DatePickerButton::new(end_date)
+                                            .id_salt("template_end_date"),
+                                    );
+                                }
+                            });
+                            ui.end_row();
+                        });
+                    ui.separator();
+
+                    ui.vertical_centered_justified(|ui| {
+                        if self.template_name.is_empty() {
+                            ui.colored_label(Color32::from_rgb(255, 0, 0), "Name the party!");
+                        } else if ui
+                            .button("Save recurring party")
+                            .on_hover_text("Its occurrences will be proposed on startup once due.")
+                            .clicked()
+                        {
+                            self.database.insert_template(&Template {
+                                name: self.template_name.clone(),
+                                transactions: self.party.transactions.clone(),
+                                recurrence: self.template_recurrence.clone(),
+                                start_date: self.template_start_date,
+                                end_date: self.template_end_date,
+                            });
+                            self.database.save();
+                            self.clear_fields();
+                        }
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_template_window = false;
+                }
+            },
+        )
+    }
+
+    pub fn handle_show_templates_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("templates_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Recurring parties window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.label("Recurring parties are created from the party window.");
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("templates")
+                            .num_columns(5)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (template_id, template, next_date) in self.database.templates()
+                                {
+                                    ui.label(template.name.as_str()).on_hover_text(
+                                        template
+                                            .transactions
+                                            .iter()
+                                            .map(|transaction| transaction.to_string())
+                                            .collect::<Vec<String>>()
+                                            .join("\n"),
+                                    );
+                                    ui.label(format!("{}", template.recurrence));
+                                    ui.label(match template.end_date {
+                                        Some(end_date) => {
+                                            format!("from {} to {}", template.start_date, end_date)
+                                        }
+                                        None => format!("from {}", template.start_date),
+                                    });
+                                    ui.label(format!("next on {}", next_date));
+                                    if ui
+                                        .button("Remove")
+                                        .on_hover_text("The parties already entered are kept.")
+                                        .clicked()
+                                    {
+                                        self.database.delete_template(template_id);
+                                        self.database.save();
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_templates_window = false;
+                }
+            },
+        )
+    }
+
+    pub fn handle_show_due_occurrences_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("due_occurrences_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Due recurring parties window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.label(
+                        "These recurring parties are due. Confirm them, adjusting the amount if needed, or skip them.",
+                    );
+                    ui.separator();
+
+                    let mut handled: Option<usize> = None;
+                    let mut confirmed_party_id: Option<i64> = None;
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("due_occurrences")
+                            .num_columns(5)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (i, occurrence) in self.due_occurrences.iter().enumerate() {
+                                    ui.label(occurrence.name.as_str());
+                                    ui.label(occurrence.date.to_string());
+                                    ui.text_edit_singleline(
+                                        &mut self.due_occurrence_values_tentative[i],
+                                    )
+                                    .on_hover_text(
+                                        "Amount of the party. Every transaction is scaled to match it.",
+                                    );
+                                    match self.due_occurrence_values_tentative[i].parse::<f64>() {
+                                        Ok(value) if value > 0.0 => {
+                                            if ui.button("Confirm").clicked() {
+                                                if let Ok(party_id) = self
+                                                    .database
+                                                    .confirm_occurrence(occurrence, value)
+                                                {
+                                                    confirmed_party_id = Some(party_id);
+                                                    handled = Some(i);
+                                                }
+                                            }
+                                        }
+                                        _ => {
+                                            ui.colored_label(
+                                                Color32::from_rgb(255, 0, 0),
+                                                "Invalid amount!",
+                                            );
+                                        }
+                                    }
+                                    if ui.button("Skip").clicked() {
+                                        self.database.skip_occurrence(occurrence);
+                                        handled = Some(i);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                    if let Some(i) = handled {
+                        self.database.save();
+                        self.budget_progress_date = None;
+                        self.runway_date = None;
+                        self.due_occurrences.remove(i);
+                        self.due_occurrence_values_tentative.remove(i);
+                        if self.due_occurrences.is_empty() {
+                            self.show_due_occurrences_window = false;
+                        }
+                    }
+                    if let Some(party_id) = confirmed_party_id {
+                        self.check_anomalies(Some(party_id));
+                    }
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_due_occurrences_window = false;
+                }
+            },
+        )
+    }
+
+    pub fn handle_show_subscriptions_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("subscriptions_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Subscriptions window")
+                .with_inner_size([WINDOW_WIDTH * 1.5, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let today: NaiveDate = Local::now().date_naive();
+                    ui.label(
+                        "Expenses to the same entity, of similar amounts and at regular intervals.",
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Detect!").clicked() {
+                            self.subscriptions = self.database.subscriptions();
+                            self.subscriptions
+                                .sort_by_key(|subscription| !subscription.is_active(today));
+                        }
+                        if ui
+                            .button("Export")
+                            .on_hover_text("Writes the report to a spreadsheet in the data folder.")
+                            .clicked()
+                        {
+                            self.export_message =
+                                match self.database.export_subscriptions_report(today) {
+                                    Ok(file_name) => format!("Exported to {}", file_name),
+                                    Err(e) => e,
+                                };
+                        }
+                        ui.label(self.export_message.as_str());
+                    });
+                    ui.separator();
+
+                    let mut made_recurring: Option<Subscription> = None;
+                    let templates: Vec<Template> = self
+                        .database
+                        .templates()
+                        .into_iter()
+                        .map(|(_template_id, template, _next_date)| template)
+                        .collect();
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("subscriptions")
+                            .num_columns(7)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for subscription in self.subscriptions.iter() {
+                                    let (last_date, last_value) = subscription.last_payment();
+                                    ui.label(
+                                        self.database.entity(subscription.entity_id).name(),
+                                    )
+                                    .on_hover_text(format!(
+                                        "{}, {}",
+                                        subscription.category, subscription.subcategory
+                                    ));
+                                    ui.label(format!(
+                                        "{}, {} payments",
+                                        subscription.cadence,
+                                        subscription.payments.len()
+                                    ));
+                                    ui.label(format!(
+                                        "last {:.2} {} on {}",
+                                        last_value, subscription.currency, last_date
+                                    ))
+                                    .on_hover_text(
+                                        subscription
+                                            .price_changes()
+                                            .iter()
+                                            .map(|(date, before, after)| {
+                                                format!("{}: {:.2} -> {:.2}", date, before, after)
+                                            })
+                                            .collect::<Vec<String>>()
+                                            .join("\n"),
+                                    );
+                                    ui.label(format!("average {:.2}", subscription.average()));
+                                    ui.label(format!(
+                                        "{:.2} {} a year",
+                                        subscription.annualized_cost(),
+                                        subscription.currency
+                                    ));
+                                    ui.label(match subscription.is_active(today) {
+                                        true => "Active",
+                                        false => "Stopped",
+                                    });
+                                    let recurrable: bool = subscription.is_active(today)
+                                        && !subscription.is_templated(&templates);
+                                    if ui
+                                        .add_enabled(recurrable, egui::Button::new("Make recurring"))
+                                        .on_hover_text(
+                                            "Saves a recurring party for the next payments at the last price.",
+                                        )
+                                        .on_disabled_hover_text(
+                                            "The series has stopped or is already entered by a recurring party.",
+                                        )
+                                        .clicked()
+                                    {
+                                        made_recurring = Some(subscription.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                    if let Some(subscription) = made_recurring {
+                        self.export_message =
+                            match self.database.subscription_template(&subscription) {
+                                Ok(template) => {
+                                    self.database.insert_template(&template);
+                                    self.database.save();
+                                    format!("Saved recurring party {}", template.name)
+                                }
+                                Err(e) => e,
+                            };
+                    }
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_subscriptions_window = false;
+                }
+            },
+        )
+    }
+}
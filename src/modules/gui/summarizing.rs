@@ -1,10 +1,15 @@
+use crate::modules::database::liquidity::RUNWAY_WINDOW_MONTHS;
+use crate::modules::database::summaries::{
+    ComparisonPeriods, EntityGrouping, FundGrouping, SummaryDimension, TimeUnit, Valuation,
+};
 use crate::modules::financial::*;
 use crate::modules::gui::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::modules::price_index::{PriceIndex, RealTerms};
+use chrono::{Local, NaiveDate};
 use eframe::egui;
-use egui::{Align, ComboBox, Layout};
+use egui::{Align, Color32, ComboBox, Layout};
 use egui_extras::*;
 use strum::IntoEnumIterator;
-use crate::modules::database::summaries::TimeUnit;
 
 impl AppState {
     pub fn handle_show_expense_summary_window(&mut self, ctx: &egui::Context) -> () {
@@ -56,20 +61,875 @@ impl AppState {
                                 });
                                         ui.end_row();
 
+                                        ui.label("Group by:").on_hover_text("Dimensions by which to group the expenses, from the outermost to the innermost. Each group gets a subtotal row.");
+                                        ui.horizontal(|ui| {
+                                            let mut removed_dimension: Option<usize> = None;
+                                            for (i, dimension) in self.expense_summary_dimensions.iter().enumerate() {
+                                                if ui.button(format!("{} ✖", dimension)).on_hover_text("Stop grouping by this dimension.").clicked() {
+                                                    removed_dimension = Some(i);
+                                                }
+                                            }
+                                            if let Some(i) = removed_dimension {
+                                                self.expense_summary_dimensions.remove(i);
+                                            }
+                                            ComboBox::from_id_salt("Expense summary dimension")
+                                                .selected_text("Add")
+                                                .show_ui(ui, |ui| {
+                                                    for possible_dimension in SummaryDimension::iter() {
+                                                        if !self.expense_summary_dimensions.contains(&possible_dimension)
+                                                            && ui.selectable_label(false, format!("{possible_dimension}")).clicked() {
+                                                            self.expense_summary_dimensions.push(possible_dimension);
+                                                        }
+                                                    }
+                                                });
+                                        });
+                                        ui.end_row();
+
+                                        self.real_terms_row(ui);
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if ui.button("Generate!").clicked() {
+                                            match self.database.expenses_summary(
+                                                self.expense_summary_date_from,
+                                                self.expense_summary_date_to,
+                                                &self.expense_summary_currency,
+                                                &self.expense_summary_dimensions,
+                                                self.real_terms.as_ref(),
+                                            ) {
+                                                Ok(expense_summary_csv) => self.expense_summary_csv = expense_summary_csv,
+                                                Err(e) => self.export_message = e,
+                                            };
+                                        }
+                                        if ui.button("Export").on_hover_text("Writes the summary to a spreadsheet in the data folder.").clicked() {
+                                            self.export_message = match self.database.export_expenses_summary(
+                                                self.expense_summary_date_from,
+                                                self.expense_summary_date_to,
+                                                &self.expense_summary_currency,
+                                                &self.expense_summary_dimensions,
+                                                self.real_terms.as_ref(),
+                                            ) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    // Subtotal and total rows
+                                                    let is_total_row: bool = row_line.split(",").any(|element| element == "Total");
+                                                    for element in row_line.split(",") {
+                                                        row_ui.col(|ui| {
+                                                            if is_total_row {
+                                                                ui.strong(element);
+                                                            } else {
+                                                                ui.label(element);
+                                                            }
+                                                        });
+                                                    }
+                                                });
+                                            }                         
+                                        });
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_expense_summary_window = false;
+                }
+            },
+        )
+    }
+    pub fn handle_show_income_summary_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("income_summary_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Income summary window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let income_summary_csv = self.income_summary_csv.clone();
+                    let header_line: String = income_summary_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = income_summary_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("income_summary")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Start date:").on_hover_text("Include incomes in the summary starting on the specified date, included.");
+                                        ui.add(DatePickerButton::new(&mut self.income_summary_date_from).id_salt("date_from"));
+                                        ui.end_row();
+
+                                        ui.label("End date:").on_hover_text("Include incomes in the summary until the specified date, included.");
+                                        ui.add(DatePickerButton::new(&mut self.income_summary_date_to).id_salt("date_to"));
+                                        ui.end_row();
+
+                                        ui.label("Currency:").on_hover_text("Currency in which to express the ammounts.");
+                                        ComboBox::from_id_salt("Income summary currency")
+                                .selected_text(format!("{}", self.income_summary_currency))
+                                .show_ui(ui, |ui| {
+                                    for possible_income_summary_currency in Currency::iter() {
+                                        ui.selectable_value(
+                                            &mut self.income_summary_currency,
+                                            possible_income_summary_currency.clone(),
+                                            format!("{possible_income_summary_currency}"),
+                                        );
+                                    }
+                                });
+                                        ui.end_row();
+
+                                        self.real_terms_row(ui);
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if ui.button("Generate!").clicked() {
+                                            match self.database.income_summary(
+                                                self.income_summary_date_from,
+                                                self.income_summary_date_to,
+                                                &self.income_summary_currency,
+                                                self.real_terms.as_ref(),
+                                            ) {
+                                                Ok(income_summary_csv) => self.income_summary_csv = income_summary_csv,
+                                                Err(e) => self.export_message = e,
+                                            };
+                                        }
+                                        if ui.button("Export").on_hover_text("Writes the summary to a spreadsheet in the data folder.").clicked() {
+                                            self.export_message = match self.database.export_income_summary(
+                                                self.income_summary_date_from,
+                                                self.income_summary_date_to,
+                                                &self.income_summary_currency,
+                                                self.real_terms.as_ref(),
+                                            ) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    let mut is_last_row: bool = false;
+                                                    for element in row_line.split(",") {
+                                                        if element == "Total" {
+                                                            is_last_row = true;
+                                                        }
+                                                        row_ui.col(|ui| {
+                                                            if is_last_row {
+                                                                ui.strong(element);
+                                                            } else {
+                                                                ui.label(element);
+                                                            }
+                                                        });
+                                                    }
+                                                });
+                                            }                         
+                                        });
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_income_summary_window = false;
+                }
+            },
+        )
+    }
+    pub fn handle_show_expenses_comparison_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("expenses_comparison_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Expenses comparison window")
+                .with_inner_size([WINDOW_WIDTH * 1.5, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let expenses_comparison_csv = self.expenses_comparison_csv.clone();
+                    let header_line: String = expenses_comparison_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = expenses_comparison_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+                    let threshold: Option<f64> = self.expenses_comparison_threshold_tentative.parse::<f64>().ok();
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("expenses_comparison")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Periods:").on_hover_text("Fill in the dates of two usually compared periods.");
+                                        ui.horizontal(|ui| {
+                                            for comparison_periods in ComparisonPeriods::iter() {
+                                                if ui.button(format!("{comparison_periods}")).clicked() {
+                                                    let (previous, current) = comparison_periods.periods(Local::now().date_naive(), &self.database.settings().periods);
+                                                    (self.expenses_comparison_previous_from, self.expenses_comparison_previous_to) = previous;
+                                                    (self.expenses_comparison_current_from, self.expenses_comparison_current_to) = current;
+                                                }
+                                            }
+                                        });
+                                        ui.end_row();
+
+                                        ui.label("Previous period:").on_hover_text("Start and end dates, both included, of the period to compare against.");
+                                        ui.horizontal(|ui| {
+                                            ui.add(DatePickerButton::new(&mut self.expenses_comparison_previous_from).id_salt("previous_from"));
+                                            ui.add(DatePickerButton::new(&mut self.expenses_comparison_previous_to).id_salt("previous_to"));
+                                        });
+                                        ui.end_row();
+
+                                        ui.label("Current period:").on_hover_text("Start and end dates, both included, of the period to review.");
+                                        ui.horizontal(|ui| {
+                                            ui.add(DatePickerButton::new(&mut self.expenses_comparison_current_from).id_salt("current_from"));
+                                            ui.add(DatePickerButton::new(&mut self.expenses_comparison_current_to).id_salt("current_to"));
+                                        });
+                                        ui.end_row();
+
+                                        ui.label("Currency:").on_hover_text("Currency in which to express the ammounts.");
+                                        ComboBox::from_id_salt("Expenses comparison currency")
+                                .selected_text(format!("{}", self.expenses_comparison_currency))
+                                .show_ui(ui, |ui| {
+                                    for possible_expenses_comparison_currency in Currency::iter() {
+                                        ui.selectable_value(
+                                            &mut self.expenses_comparison_currency,
+                                            possible_expenses_comparison_currency.clone(),
+                                            format!("{possible_expenses_comparison_currency}"),
+                                        );
+                                    }
+                                });
+                                        ui.end_row();
+
+                                        ui.label("Threshold (%):").on_hover_text("Minimum increase, in percent, for an expense to be flagged as significant.");
+                                        ui.text_edit_singleline(&mut self.expenses_comparison_threshold_tentative);
+                                        if threshold.is_none() {
+                                            ui.colored_label(Color32::from_rgb(255, 0, 0), "Invalid threshold!");
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if let Some(threshold) = threshold {
+                                            if ui.button("Generate!").clicked() {
+                                                self.expenses_comparison_csv = self.database.expenses_comparison(
+                                                    (self.expenses_comparison_previous_from, self.expenses_comparison_previous_to),
+                                                    (self.expenses_comparison_current_from, self.expenses_comparison_current_to),
+                                                    &self.expenses_comparison_currency,
+                                                    threshold
+                                                );
+                                            }
+                                            if ui.button("Export").on_hover_text("Writes the comparison to a spreadsheet in the data folder.").clicked() {
+                                                self.export_message = match self.database.export_expenses_comparison(
+                                                    (self.expenses_comparison_previous_from, self.expenses_comparison_previous_to),
+                                                    (self.expenses_comparison_current_from, self.expenses_comparison_current_to),
+                                                    &self.expenses_comparison_currency,
+                                                    threshold
+                                                ) {
+                                                    Ok(file_name) => format!("Exported to {}", file_name),
+                                                    Err(e) => e,
+                                                };
+                                            }
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    // Subtotal and total rows
+                                                    let is_total_row: bool = row_line.split(",").any(|element| element == "Total");
+                                                    for element in row_line.split(",") {
+                                                        row_ui.col(|ui| {
+                                                            if element == "Yes" {
+                                                                ui.colored_label(Color32::from_rgb(255, 0, 0), element);
+                                                            } else if is_total_row {
+                                                                ui.strong(element);
+                                                            } else {
+                                                                ui.label(element);
+                                                            }
+                                                        });
+                                                    }
+                                                });
+                                            }
+                                        });
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_expenses_comparison_window = false;
+                }
+            },
+        )
+    }
+    pub fn handle_show_entity_spending_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("entity_spending_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Entity spending window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let entity_spending_csv = self.entity_spending_csv.clone();
+                    let header_line: String = entity_spending_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = entity_spending_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("entity_spending")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Start date:").on_hover_text("Include expenses in the summary starting on the specified date, included.");
+                                        ui.add(DatePickerButton::new(&mut self.entity_spending_date_from).id_salt("date_from"));
+                                        ui.end_row();
+
+                                        ui.label("End date:").on_hover_text("Include expenses in the summary until the specified date, included.");
+                                        ui.add(DatePickerButton::new(&mut self.entity_spending_date_to).id_salt("date_to"));
+                                        ui.end_row();
+
+                                        ui.label("Currency:").on_hover_text("Currency in which to express the ammounts.");
+                                        ComboBox::from_id_salt("Entity spending currency")
+                                .selected_text(format!("{}", self.entity_spending_currency))
+                                .show_ui(ui, |ui| {
+                                    for possible_entity_spending_currency in Currency::iter() {
+                                        ui.selectable_value(
+                                            &mut self.entity_spending_currency,
+                                            possible_entity_spending_currency.clone(),
+                                            format!("{possible_entity_spending_currency}"),
+                                        );
+                                    }
+                                });
+                                        ui.end_row();
+
+                                        ui.label("Group by:").on_hover_text("Aggregate the expenses by entity, by entity type, by entity subtype or by country.");
+                                        ComboBox::from_id_salt("Entity spending grouping")
+                                .selected_text(format!("{}", self.entity_spending_grouping))
+                                .show_ui(ui, |ui| {
+                                    for possible_entity_spending_grouping in EntityGrouping::iter() {
+                                        ui.selectable_value(
+                                            &mut self.entity_spending_grouping,
+                                            possible_entity_spending_grouping.clone(),
+                                            format!("{possible_entity_spending_grouping}"),
+                                        );
+                                    }
+                                });
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if ui.button("Generate!").clicked() {
+                                            self.entity_spending_csv = self.database.entity_spending(
+                                                self.entity_spending_date_from,
+                                                self.entity_spending_date_to,
+                                                &self.entity_spending_currency,
+                                                &self.entity_spending_grouping
+                                            );
+                                            self.entity_spending_csv_grouping = self.entity_spending_grouping.clone();
+                                        }
+                                        if ui.button("Export").on_hover_text("Writes the summary to a spreadsheet in the data folder.").clicked() {
+                                            self.export_message = match self.database.export_entity_spending(
+                                                self.entity_spending_date_from,
+                                                self.entity_spending_date_to,
+                                                &self.entity_spending_currency,
+                                                &self.entity_spending_grouping
+                                            ) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    for (i, element) in row_line.split(",").enumerate() {
+                                                        row_ui.col(|ui| {
+                                                            if (i == 0) & (self.entity_spending_csv_grouping == EntityGrouping::Entity) {
+                                                                // index of the entity id column
+                                                                if ui.button(element).on_hover_text("Shows the expenses paid to the entity").clicked() {
+                                                                    let entity_id: i64 = element.parse().unwrap();
+                                                                    self.entity_transactions_csv = self.database.entity_transactions(
+                                                                        entity_id,
+                                                                        self.entity_spending_date_from,
+                                                                        self.entity_spending_date_to,
+                                                                    );
+                                                                    self.entity_transactions_title = format!("Expenses paid to {}", self.database.entity(entity_id).name());
+                                                                    self.show_entity_transactions_window = true;
+                                                                }
+                                                            } else {
+                                                                ui.label(element);
+                                                            }
+                                                        });
+                                                    }
+                                                });
+                                            }
+                                        });
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_entity_spending_window = false;
+                }
+            },
+        )
+    }
+    pub fn handle_show_fund_stand_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("fund_stand_summary_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Fund stand summary window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let fund_stand_csv = self.fund_stand_csv.clone();
+                    let header_line: String = fund_stand_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = fund_stand_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+                    let currency_label: String = self.fund_stand_currency.clone().map_or("None".to_string(), |currency| currency.to_string());
+                    let valuation_label: String = self.fund_stand_valuation.to_string();
+                    let grouping_label: String = self.fund_stand_grouping.to_string();
+                    
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("fund_stand")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Date:").on_hover_text("Include the accounts created and the fund movements made until the specified date, included.");
+                                        ui.add(DatePickerButton::new(&mut self.fund_stand_date).id_salt("fund_stand_date"));
+                                        ui.end_row();
+
+                                        ui.label("Compare with:").on_hover_text("Earlier date to compare the fund stand with.");
+                                        ui.add(DatePickerButton::new(&mut self.fund_stand_compare_date).id_salt("fund_stand_compare_date"));
+                                        ui.end_row();
+
+                                        ui.label("Currency:").on_hover_text("Currency to which convert all amounts. Select None to avoid converting to a single currency.");
+                                        ComboBox::from_id_salt("Fund stand currency")
+                                            .selected_text(currency_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_fund_stand_currency in Currency::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.fund_stand_currency,
+                                        Some(possible_fund_stand_currency.clone()),
+                                        format!("{possible_fund_stand_currency}"),
+                                        );
+                                                }
+                                                ui.selectable_value(
+                                                    &mut self.fund_stand_currency,
+                                                    None,
+                                                    String::from("None")
+                                                    );
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Valuation:").on_hover_text("Historical: convert the amounts at the exchange rates of the date. Current: convert them at today's exchange rates.");
+                                        ComboBox::from_id_salt("Fund stand valuation")
+                                            .selected_text(valuation_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_fund_stand_valuation in Valuation::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.fund_stand_valuation,
+                                        possible_fund_stand_valuation.clone(),
+                                        format!("{possible_fund_stand_valuation}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Group by:").on_hover_text("Aggregate the accounts by name, by account type or by country.");
+                                        ComboBox::from_id_salt("Fund stand grouping")
+                                            .selected_text(grouping_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_fund_stand_grouping in FundGrouping::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.fund_stand_grouping,
+                                        possible_fund_stand_grouping.clone(),
+                                        format!("{possible_fund_stand_grouping}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        self.real_terms_row(ui);
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if ui.button("Generate!").clicked() {
+                                            self.fund_stand_comparing = false;
+                                            match self.database.fund_stand_at(
+                                                self.fund_stand_date,
+                                                self.fund_stand_currency.as_ref(),
+                                                &self.fund_stand_valuation,
+                                                &self.fund_stand_grouping,
+                                                self.real_terms.as_ref(),
+                                            ) {
+                                                Ok(fund_stand_csv) => self.fund_stand_csv = fund_stand_csv,
+                                                Err(e) => self.export_message = e,
+                                            };
+                                        }
+                                        if ui.button("Compare").on_hover_text("Shows the fund stands at both dates side by side. Needs a currency.").clicked() {
+                                            if let Some(currency) = self.fund_stand_currency.as_ref() {
+                                                self.fund_stand_comparing = true;
+                                                self.fund_stand_csv = self.database.fund_stand_comparison(
+                                                    self.fund_stand_compare_date,
+                                                    self.fund_stand_date,
+                                                    currency,
+                                                    &self.fund_stand_valuation,
+                                                    &self.fund_stand_grouping,
+                                                );
+                                            }
+                                        }
+                                        if ui.button("Export").on_hover_text("Writes the fund stand, or the comparison, to a spreadsheet in the data folder.").clicked() {
+                                            let result = match (self.fund_stand_comparing, self.fund_stand_currency.as_ref()) {
+                                                (true, Some(currency)) => self.database.export_fund_stand_comparison(
+                                                    self.fund_stand_compare_date,
+                                                    self.fund_stand_date,
+                                                    currency,
+                                                    &self.fund_stand_valuation,
+                                                    &self.fund_stand_grouping,
+                                                ),
+                                                _ => self.database.export_fund_stand(
+                                                    self.fund_stand_date,
+                                                    self.fund_stand_currency.as_ref(),
+                                                    &self.fund_stand_valuation,
+                                                    &self.fund_stand_grouping,
+                                                    self.real_terms.as_ref(),
+                                                ),
+                                            };
+                                            self.export_message = match result {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    for element in row_line.split(",") {
+                                                        row_ui.col(|ui| {
+                                                                ui.label(element);
+                                                        });
+                                                    }
+                                                });
+                                            }                         
+                                        });
+                                ui.separator();
+                            });
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_fund_stand_window = false;
+                }
+            },
+        )
+    }
+    pub fn handle_show_expenses_evolution_window (&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("expenses_evolution_summary_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Expenses evolution summary window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let expenses_evolution_csv = self.expenses_evolution_csv.clone();
+                    let header_line: String = expenses_evolution_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = expenses_evolution_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let column_count: usize = header_line.split(",").count();
+                    let currency_label: String = self.expenses_evolution_currency.to_string();
+                    let time_unit_label: String = self.expenses_evolution_time_unit.to_string();
+                    let transaction_type_label: String = self.expenses_evolution_transaction_type.to_string();
+                    let category_label: String = self.expenses_evolution_category.clone().unwrap_or(String::from("All categories"));
+                    let entity_label: String = self.expenses_evolution_entity_id.map_or(String::from("All entities"), |entity_id| self.database.entity(entity_id).to_string());
+                    let currency_filter_label: String = self.expenses_evolution_currency_filter.clone().map_or(String::from("All currencies"), |currency| currency.to_string());
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::initial(240.0))
+                        .size(Size::remainder().at_least(10.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("expenses_evolution")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Transactions:").on_hover_text("Whether to show the evolution of the expenses or of the incomes.");
+                                        ComboBox::from_id_salt("Expenses evolution transaction type")
+                                            .selected_text(transaction_type_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_expenses_evolution_transaction_type in [TransactionType::Expense, TransactionType::Income] {
+                                                    let label: String = format!("{possible_expenses_evolution_transaction_type}");
+                                                    if ui.selectable_value(
+                                                        &mut self.expenses_evolution_transaction_type,
+                                        possible_expenses_evolution_transaction_type,
+                                        label,
+                                        ).clicked() {
+                                                        self.expenses_evolution_category = None;
+                                                    }
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Category:").on_hover_text("Show the subcategories of this category instead of all categories.");
+                                        ComboBox::from_id_salt("Expenses evolution category")
+                                            .selected_text(category_label)
+                                            .show_ui(ui, |ui| {
+                                                ui.selectable_value(
+                                                    &mut self.expenses_evolution_category,
+                                                    None,
+                                                    String::from("All categories")
+                                                );
+                                                for possible_expenses_evolution_category in self.database.transaction_categories(&self.expenses_evolution_transaction_type) {
+                                                    ui.selectable_value(
+                                                        &mut self.expenses_evolution_category,
+                                        Some(possible_expenses_evolution_category.clone()),
+                                        possible_expenses_evolution_category,
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Entity:").on_hover_text("Entity to filter for.");
+                                        ComboBox::from_id_salt("Expenses evolution entity")
+                                            .selected_text(entity_label)
+                                            .show_ui(ui, |ui| {
+                                                ui.selectable_value(
+                                                    &mut self.expenses_evolution_entity_id,
+                                                    None,
+                                                    String::from("All entities")
+                                                );
+                                                for entity_id in self.database.iter_entity_ids() {
+                                                    ui.selectable_value(
+                                                        &mut self.expenses_evolution_entity_id,
+                                                        Some(entity_id),
+                                                        self.database.entity(entity_id).to_string(),
+                                                    );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Transaction currency:").on_hover_text("Include only the transactions made in this currency.");
+                                        ComboBox::from_id_salt("Expenses evolution currency filter")
+                                            .selected_text(currency_filter_label)
+                                            .show_ui(ui, |ui| {
+                                                ui.selectable_value(
+                                                    &mut self.expenses_evolution_currency_filter,
+                                                    None,
+                                                    String::from("All currencies")
+                                                );
+                                                for possible_expenses_evolution_currency_filter in Currency::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.expenses_evolution_currency_filter,
+                                        Some(possible_expenses_evolution_currency_filter.clone()),
+                                        format!("{possible_expenses_evolution_currency_filter}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Currency:").on_hover_text("Currency on which to express the ammounts.");
+                                        ComboBox::from_id_salt("Expenses evolution currency")
+                                            .selected_text(currency_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_expenses_evolution_currency in Currency::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.expenses_evolution_currency,
+                                        possible_expenses_evolution_currency.clone(),
+                                        format!("{possible_expenses_evolution_currency}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Time unit:").on_hover_text("Time unit to aggregate expenses.");
+                                        ComboBox::from_id_salt("Expenses evolution time unit")
+                                            .selected_text(time_unit_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_expenses_evolution_time_unit in TimeUnit::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.expenses_evolution_time_unit,
+                                        possible_expenses_evolution_time_unit.clone(),
+                                        format!("{possible_expenses_evolution_time_unit}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        self.real_terms_row(ui);
+                                        ui.end_row();
+
                                         ui.label("");
                                         if ui.button("Generate!").clicked() {
-                                            self.expense_summary_csv = self.database.expenses_summary(
-                                                self.expense_summary_date_from,
-                                                self.expense_summary_date_to,
-                                                &self.expense_summary_currency
-                                            );
+                                            match self.database.evolution_table(
+                                                &self.expenses_evolution_transaction_type,
+                                                &self.expenses_evolution_currency,
+                                                &self.expenses_evolution_time_unit,
+                                                self.expenses_evolution_category.as_deref(),
+                                                self.expenses_evolution_entity_id,
+                                                self.expenses_evolution_currency_filter.as_ref(),
+                                                self.real_terms.as_ref(),
+                                            ) {
+                                                Ok(evolution_csv) => self.expenses_evolution_csv = evolution_csv,
+                                                Err(e) => self.export_message = e,
+                                            };
+                                        }
+                                        if ui.button("Export").on_hover_text("Writes the evolution table to a spreadsheet in the data folder.").clicked() {
+                                            self.export_message = match self.database.export_evolution(
+                                                &self.expenses_evolution_transaction_type,
+                                                &self.expenses_evolution_currency,
+                                                &self.expenses_evolution_time_unit,
+                                                self.expenses_evolution_category.as_deref(),
+                                                self.expenses_evolution_entity_id,
+                                                self.expenses_evolution_currency_filter.as_ref(),
+                                                self.real_terms.as_ref(),
+                                            ) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
                                         }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
 
                                     });
                                 ui.separator();
                             });
                             strip.cell(|ui| {
-                                                TableBuilder::new(ui)
+                                TableBuilder::new(ui)
                                         .columns(Column::auto().resizable(true), column_count)
                                         .striped(true)
                                         .cell_layout(Layout::right_to_left(Align::Center))
@@ -84,37 +944,36 @@ impl AppState {
                                         .body(|mut body| {
                                             for row_line in row_lines {
                                                 body.row(30.0, |mut row_ui| {
-                                                    let mut is_last_row: bool = false;
-                                                    for element in row_line.split(",") {
-                                                        if element == "Total" {
-                                                            is_last_row = true;
-                                                        }
+                                                    let is_total_row: bool = row_line.starts_with("Total");
+                                                    let elements: Vec<&str> = row_line.split(",").collect();
+                                                    for (i, element) in elements.iter().enumerate() {
                                                         row_ui.col(|ui| {
-                                                            if is_last_row {
-                                                                ui.strong(element);
+                                                            // Totals row and totals column
+                                                            if is_total_row || i + 1 == elements.len() {
+                                                                ui.strong(*element);
                                                             } else {
-                                                                ui.label(element);
+                                                                ui.label(*element);
                                                             }
                                                         });
                                                     }
                                                 });
                                             }                         
                                         });
-                                ui.separator();
                             });
+                            strip.cell(|ui| {ui.separator();});
                         });
                 });
                 if ctx.input(|i| i.viewport().close_requested()) {
-                    self.show_expense_summary_window = false;
+                    self.show_expenses_evolution_window = false;
                 }
             },
         )
     }
-    pub fn handle_show_fund_stand_window(&mut self, ctx: &egui::Context) -> () {
+    pub fn handle_show_cash_flow_window(&mut self, ctx: &egui::Context) -> () {
         ctx.show_viewport_immediate(
-            egui::ViewportId::from_hash_of("fund_stand_summary_window"),
+            egui::ViewportId::from_hash_of("cash_flow_summary_window"),
             egui::ViewportBuilder::default()
-                .with_title("Fund stand summary window")
+                .with_title("Cash flow summary window")
                 .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
             |ctx, class| {
                 assert!(
@@ -123,53 +982,77 @@ impl AppState {
                 );
 
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    let fund_stand_csv = self.fund_stand_csv.clone();
-                    let header_line: String = fund_stand_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
-                    let row_lines: Vec<&str> = fund_stand_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let cash_flow_csv = self.cash_flow_csv.clone();
+                    let header_line: String = cash_flow_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = cash_flow_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
                     let column_count: usize = header_line.split(",").count();
-                    let currency_label: String = self.fund_stand_currency.clone().map_or("None".to_string(), |currency| currency.to_string());
-                    
+                    let currency_label: String = self.cash_flow_currency.to_string();
+                    let time_unit_label: String = self.cash_flow_time_unit.to_string();
 
                     StripBuilder::new(ui)
                         .size(Size::exact(40.0))
-                        .size(Size::remainder().at_least(120.0))
+                        .size(Size::initial(240.0))
+                        .size(Size::remainder().at_least(10.0))
                         .vertical(|mut strip| {
                             strip.cell(|ui| {
-                                egui::Grid::new("fund_stand")
+                                egui::Grid::new("cash_flow")
                                     .num_columns(3)
                                     .spacing([45.0, 4.0])
                                     .show(ui, |ui| {
-                                        ui.label("Currency:").on_hover_text("Currency to which convert all amounts. Select None to avoid converting to a single currency.");
-                                        ComboBox::from_id_salt("Fund stand currency")
-                                            .selected_text(format!("{}", currency_label))
+                                        ui.label("Currency:").on_hover_text("Currency on which to express the ammounts.");
+                                        ComboBox::from_id_salt("Cash flow currency")
+                                            .selected_text(currency_label)
                                             .show_ui(ui, |ui| {
-                                                for possible_fund_stand_currency in Currency::iter() {
+                                                for possible_cash_flow_currency in Currency::iter() {
                                                     ui.selectable_value(
-                                                        &mut self.fund_stand_currency,
-                                        Some(possible_fund_stand_currency.clone()),
-                                        format!("{possible_fund_stand_currency}"),
+                                                        &mut self.cash_flow_currency,
+                                        possible_cash_flow_currency.clone(),
+                                        format!("{possible_cash_flow_currency}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Time unit:").on_hover_text("Time unit to aggregate incomes, expenses and fund movements.");
+                                        ComboBox::from_id_salt("Cash flow time unit")
+                                            .selected_text(time_unit_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_cash_flow_time_unit in TimeUnit::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.cash_flow_time_unit,
+                                        possible_cash_flow_time_unit.clone(),
+                                        format!("{possible_cash_flow_time_unit}"),
                                         );
                                                 }
-                                                ui.selectable_value(
-                                                    &mut self.fund_stand_currency,
-                                                    None,
-                                                    String::from("None")
-                                                    );
                                             });
                                         ui.end_row();
 
                                         ui.label("");
                                         if ui.button("Generate!").clicked() {
-                                            self.fund_stand_csv = self.database.current_fund_stand(
-                                                self.fund_stand_currency.as_ref()
+                                            self.cash_flow_csv = self.database.cash_flow(
+                                                &self.cash_flow_currency,
+                                                &self.cash_flow_time_unit,
                                             );
                                         }
+                                        if ui.button("Export").on_hover_text("Writes the cash flow table to a spreadsheet in the data folder.").clicked() {
+                                            self.export_message = match self.database.export_cash_flow(
+                                                &self.cash_flow_currency,
+                                                &self.cash_flow_time_unit,
+                                            ) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
 
                                     });
                                 ui.separator();
                             });
                             strip.cell(|ui| {
-                                                TableBuilder::new(ui)
+                                TableBuilder::new(ui)
                                         .columns(Column::auto().resizable(true), column_count)
                                         .striped(true)
                                         .cell_layout(Layout::right_to_left(Align::Center))
@@ -192,21 +1075,186 @@ impl AppState {
                                                 });
                                             }                         
                                         });
+                            });
+                            strip.cell(|ui| {ui.separator();});
+                        });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.show_cash_flow_window = false;
+                }
+            },
+        )
+    }
+
+    pub fn handle_show_unit_prices_window(&mut self, ctx: &egui::Context) -> () {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("unit_prices_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Unit prices window")
+                .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+            |ctx, class| {
+                assert!(
+                    class == egui::ViewportClass::Immediate,
+                    "This egui backend doesn't support multiple viewports"
+                );
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let index_csv = self.unit_prices_index_csv.clone();
+                    let index_header_line: String = index_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let index_row_lines: Vec<&str> = index_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let index_column_count: usize = index_header_line.split(",").count();
+                    let history_csv = self.unit_prices_history_csv.clone();
+                    let history_header_line: String = history_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let history_row_lines: Vec<&str> = history_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let history_column_count: usize = history_header_line.split(",").count();
+                    let currency_label: String = self.unit_prices_currency.to_string();
+                    let time_unit_label: String = self.unit_prices_time_unit.to_string();
+
+                    StripBuilder::new(ui)
+                        .size(Size::exact(40.0))
+                        .size(Size::initial(240.0))
+                        .size(Size::remainder().at_least(120.0))
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                egui::Grid::new("unit_prices")
+                                    .num_columns(3)
+                                    .spacing([45.0, 4.0])
+                                    .show(ui, |ui| {
+                                        ui.label("Currency:").on_hover_text("Currency on which to express the prices.");
+                                        ComboBox::from_id_salt("Unit prices currency")
+                                            .selected_text(currency_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_unit_prices_currency in Currency::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.unit_prices_currency,
+                                        possible_unit_prices_currency.clone(),
+                                        format!("{possible_unit_prices_currency}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("Time unit:").on_hover_text("Time unit over which to average the unit prices.");
+                                        ComboBox::from_id_salt("Unit prices time unit")
+                                            .selected_text(time_unit_label)
+                                            .show_ui(ui, |ui| {
+                                                for possible_unit_prices_time_unit in TimeUnit::iter() {
+                                                    ui.selectable_value(
+                                                        &mut self.unit_prices_time_unit,
+                                        possible_unit_prices_time_unit.clone(),
+                                        format!("{possible_unit_prices_time_unit}"),
+                                        );
+                                                }
+                                            });
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        if ui.button("Generate!").on_hover_text("Only the expenses entered with a quantity are included.").clicked() {
+                                            self.unit_prices_index_csv = self.database.personal_price_index(
+                                                &self.unit_prices_currency,
+                                                &self.unit_prices_time_unit,
+                                            );
+                                            self.unit_prices_history_csv = self.database.unit_price_history(
+                                                &self.unit_prices_currency,
+                                                &self.unit_prices_time_unit,
+                                            );
+                                        }
+                                        if ui.button("Export").on_hover_text("Writes the personal price index and the unit prices to a spreadsheet in the data folder.").clicked() {
+                                            self.export_message = match self.database.export_unit_prices(
+                                                &self.unit_prices_currency,
+                                                &self.unit_prices_time_unit,
+                                            ) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
+
+                                    });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), index_column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in index_header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in index_row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    for element in row_line.split(",") {
+                                                        row_ui.col(|ui| {
+                                                                ui.label(element);
+                                                        });
+                                                    }
+                                                });
+                                            }
+                                        });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                TableBuilder::new(ui)
+                                        .columns(Column::auto().resizable(true), history_column_count)
+                                        .striped(true)
+                                        .cell_layout(Layout::right_to_left(Align::Center))
+                                        .header(20.0, |mut header| {
+                                            for column_name in history_header_line.split(",") {
+                                                header.col(|ui| {
+                                                    ui.strong(column_name)
+                                                        .on_hover_text(column_name);
+                                                });
+                                            }
+                                        })
+                                        .body(|mut body| {
+                                            for row_line in history_row_lines {
+                                                body.row(30.0, |mut row_ui| {
+                                                    for element in row_line.split(",") {
+                                                        row_ui.col(|ui| {
+                                                                ui.label(element);
+                                                        });
+                                                    }
+                                                });
+                                            }
+                                        });
                                 ui.separator();
                             });
                         });
                 });
                 if ctx.input(|i| i.viewport().close_requested()) {
-                    self.show_fund_stand_window = false;
+                    self.show_unit_prices_window = false;
                 }
             },
         )
     }
-    pub fn handle_show_expenses_evolution_window (&mut self, ctx: &egui::Context) -> () {
+
+    /// Computes the runway of the main window warning once a day, in the default currency.
+    pub(crate) fn refresh_runway(&mut self) -> () {
+        let today: NaiveDate = Local::now().date_naive();
+        if self.runway_date != Some(today) {
+            self.runway = Some(self.database.runway_at(
+                today,
+                &Currency::default(),
+                RUNWAY_WINDOW_MONTHS,
+            ));
+            self.runway_date = Some(today);
+        }
+    }
+
+    pub fn handle_show_runway_window(&mut self, ctx: &egui::Context) -> () {
         ctx.show_viewport_immediate(
-            egui::ViewportId::from_hash_of("expenses_evolution_summary_window"),
+            egui::ViewportId::from_hash_of("runway_window"),
             egui::ViewportBuilder::default()
-                .with_title("Expenses evolution summary window")
+                .with_title("Liquidity runway window")
                 .with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
             |ctx, class| {
                 assert!(
@@ -215,58 +1263,80 @@ impl AppState {
                 );
 
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    let expenses_evolution_csv = self.expenses_evolution_csv.clone();
-                    let header_line: String = expenses_evolution_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
-                    let row_lines: Vec<&str> = expenses_evolution_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
+                    let runway_csv = self.runway_report_csv.clone();
+                    let header_line: String = runway_csv.split("\n").collect::<Vec<&str>>()[0].to_string();
+                    let row_lines: Vec<&str> = runway_csv.split("\n").collect::<Vec<&str>>()[1..].to_vec();
                     let column_count: usize = header_line.split(",").count();
-                    let currency_label: String = self.expenses_evolution_currency.to_string();
-                    let time_unit_label: String = self.expenses_evolution_time_unit.to_string();
+                    let currency_label: String = self.runway_currency.to_string();
 
                     StripBuilder::new(ui)
                         .size(Size::exact(40.0))
                         .size(Size::initial(240.0))
-                        .size(Size::remainder().at_least(10.0))
+                        .size(Size::remainder().at_least(120.0))
                         .vertical(|mut strip| {
                             strip.cell(|ui| {
-                                egui::Grid::new("expenses_evolution")
+                                egui::Grid::new("runway")
                                     .num_columns(3)
                                     .spacing([45.0, 4.0])
                                     .show(ui, |ui| {
-                                        ui.label("Currency:").on_hover_text("Currency on which to express expense ammounts.");
-                                        ComboBox::from_id_salt("Expenses evolution currency")
-                                            .selected_text(format!("{}", currency_label))
+                                        ui.label("Currency:").on_hover_text("Currency on which to express the ammounts.");
+                                        ComboBox::from_id_salt("Runway currency")
+                                            .selected_text(currency_label)
                                             .show_ui(ui, |ui| {
-                                                for possible_expenses_evolution_currency in Currency::iter() {
+                                                for possible_runway_currency in Currency::iter() {
                                                     ui.selectable_value(
-                                                        &mut self.expenses_evolution_currency,
-                                        possible_expenses_evolution_currency.clone(),
-                                        format!("{possible_expenses_evolution_currency}"),
+                                                        &mut self.runway_currency,
+                                        possible_runway_currency.clone(),
+                                        format!("{possible_runway_currency}"),
                                         );
                                                 }
                                             });
                                         ui.end_row();
 
-                                        ui.label("Time unit:").on_hover_text("Time unit to aggregate expenses.");
-                                        ComboBox::from_id_salt("Expenses evolution time unit")
-                                            .selected_text(format!("{}", time_unit_label))
+                                        ui.label("History:").on_hover_text("Months of expenses to average the monthly expenses over.");
+                                        ComboBox::from_id_salt("Runway window months")
+                                            .selected_text(format!("{} months", self.runway_window_months))
                                             .show_ui(ui, |ui| {
-                                                for possible_expenses_evolution_time_unit in TimeUnit::iter() {
+                                                for months in [3, 6, 12, 24] {
                                                     ui.selectable_value(
-                                                        &mut self.expenses_evolution_time_unit,
-                                        possible_expenses_evolution_time_unit.clone(),
-                                        format!("{possible_expenses_evolution_time_unit}"),
-                                        );
+                                                        &mut self.runway_window_months,
+                                                        months,
+                                                        format!("{} months", months),
+                                                    );
                                                 }
                                             });
                                         ui.end_row();
 
                                         ui.label("");
-                                        if ui.button("Generate!").clicked() {
-                                            self.expenses_evolution_csv = self.database.evolution_table(
-                                                &self.expenses_evolution_currency,
-                                                &self.expenses_evolution_time_unit,
+                                        if ui.button("Generate!").on_hover_text("Only deposit and cash accounts count as liquid funds.").clicked() {
+                                            self.runway_report_csv = self.database.runway_report(
+                                                Local::now().date_naive(),
+                                                &self.runway_currency,
+                                                self.runway_window_months,
                                             );
+                                            self.database.runway_plot(
+                                                Local::now().date_naive(),
+                                                &self.runway_currency,
+                                                self.runway_window_months,
+                                            );
+
+                                            // forget the old one
+                                            ui.ctx().forget_all_images();
                                         }
+                                        if ui.button("Export").on_hover_text("Writes the runway history to a spreadsheet in the data folder.").clicked() {
+                                            self.export_message = match self.database.export_runway_report(
+                                                Local::now().date_naive(),
+                                                &self.runway_currency,
+                                                self.runway_window_months,
+                                            ) {
+                                                Ok(file_name) => format!("Exported to {}", file_name),
+                                                Err(e) => e,
+                                            };
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("");
+                                        ui.label(self.export_message.as_str());
 
                                     });
                                 ui.separator();
@@ -285,7 +1355,7 @@ impl AppState {
                                             }
                                         })
                                         .body(|mut body| {
-                                            for row_line in row_lines {
+                                            for row_line in row_lines.iter().rev() {
                                                 body.row(30.0, |mut row_ui| {
                                                     for element in row_line.split(",") {
                                                         row_ui.col(|ui| {
@@ -293,16 +1363,58 @@ impl AppState {
                                                         });
                                                     }
                                                 });
-                                            }                         
+                                            }
                                         });
+                                ui.separator();
+                            });
+                            strip.cell(|ui| {
+                                ui.image("file://figures/runway.svg");
+                                ui.separator();
                             });
-                            strip.cell(|ui| {ui.separator();});
                         });
                 });
                 if ctx.input(|i| i.viewport().close_requested()) {
-                    self.show_expenses_evolution_window = false;
+                    self.show_runway_window = false;
                 }
             },
         )
     }
+
+    /// Grid row to choose between nominal amounts and amounts in real terms, deflated by the
+    /// price index of a country to the price level of a base year.
+    fn real_terms_row(&mut self, ui: &mut egui::Ui) -> () {
+        ui.label("Real terms:").on_hover_text("Deflate the amounts, once converted, by the consumer price index of a country in data/cpi_{country}.csv, to the price level of the base year.");
+        ui.horizontal(|ui| {
+            let country_label: String = self.real_terms.as_ref().map_or("Nominal".to_string(), |real_terms| real_terms.price_index.country().to_string());
+            ComboBox::from_id_salt("Real terms country")
+                .selected_text(country_label)
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(self.real_terms.is_none(), "Nominal").clicked() {
+                        self.real_terms = None;
+                    }
+                    for country in PriceIndex::countries() {
+                        let selected: bool = self.real_terms.as_ref().is_some_and(|real_terms| real_terms.price_index.country() == country);
+                        if ui.selectable_label(selected, country.as_str()).clicked() && !selected {
+                            match PriceIndex::load(country.as_str()) {
+                                Ok(price_index) => {
+                                    let base_year: Option<i32> = price_index.years().last().copied();
+                                    self.real_terms = base_year.map(|base_year| RealTerms { price_index, base_year });
+                                }
+                                Err(e) => self.export_message = e,
+                            }
+                        }
+                    }
+                });
+            if let Some(real_terms) = self.real_terms.as_mut() {
+                let years: Vec<i32> = real_terms.price_index.years();
+                ComboBox::from_id_salt("Real terms base year")
+                    .selected_text(format!("Base year {}", real_terms.base_year))
+                    .show_ui(ui, |ui| {
+                        for year in &years {
+                            ui.selectable_value(&mut real_terms.base_year, *year, year.to_string());
+                        }
+                    });
+            }
+        });
+    }
 }
@@ -2,4 +2,5 @@ pub mod financial;
 pub mod tables;
 pub mod database;
 pub mod gui;
-pub mod currency_exchange;
\ No newline at end of file
+pub mod currency_exchange;
+pub mod price_index;
\ No newline at end of file
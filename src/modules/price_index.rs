@@ -0,0 +1,181 @@
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+use std::fmt::Display;
+use std::fs::read_dir;
+
+/// Consumer price index series of a country, read from data/cpi_{country}.csv. The files have
+/// columns date and value, like the exchange rate ones, with one observation per month or per
+/// year. Observations are kept sorted by date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceIndex {
+    country: String,
+    dates: Vec<NaiveDate>,
+    values: Vec<f64>,
+}
+
+/// Option to express the amounts of a report in real terms: deflated by the price index of a
+/// country, loaded once, at the price level of the base year.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RealTerms {
+    pub(crate) price_index: PriceIndex,
+    pub(crate) base_year: i32,
+}
+
+// Conversion to string
+impl Display for RealTerms {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "real terms, base year {} ({} CPI)",
+            self.base_year, self.price_index.country
+        )
+    }
+}
+
+impl PriceIndex {
+    pub(crate) fn new(country: &str, data_frame: DataFrame) -> PriceIndex {
+        let data_frame: DataFrame = data_frame
+            .lazy()
+            .sort(["date"], Default::default())
+            .collect()
+            .expect("Failed to sort price index");
+
+        PriceIndex {
+            country: country.to_string(),
+            dates: data_frame
+                .column("date")
+                .unwrap()
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .flatten()
+                .collect(),
+            values: data_frame
+                .column("value")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect(),
+        }
+    }
+
+    pub(crate) fn country(&self) -> &str {
+        self.country.as_str()
+    }
+
+    /// Reads the price index of a country from disk.
+    pub(crate) fn load(country: &str) -> Result<PriceIndex, String> {
+        let data_frame: DataFrame = CsvReadOptions::default()
+            .with_infer_schema_length(None)
+            .with_has_header(true)
+            .with_parse_options(CsvParseOptions::default().with_try_parse_dates(true))
+            .try_into_reader_with_file_path(Some(format!("data/cpi_{}.csv", country).into()))
+            .map_err(|e| format!("Failed to read {} price index: {}", country, e))?
+            .finish()
+            .map_err(|e| format!("Failed to load {} price index: {}", country, e))?;
+
+        if data_frame
+            .column("date")
+            .is_ok_and(|date| date.dtype() == &DataType::Date)
+            && data_frame
+                .column("value")
+                .is_ok_and(|value| value.dtype().is_primitive_numeric())
+        {
+            let data_frame: DataFrame = data_frame
+                .lazy()
+                .select([col("date"), col("value").cast(DataType::Float64)])
+                .drop_nulls(None)
+                .collect()
+                .map_err(|e| format!("Failed to load {} price index: {}", country, e))?;
+            Ok(PriceIndex::new(country, data_frame))
+        } else {
+            Err(format!(
+                "The {} price index needs a date and a numeric value column",
+                country
+            ))
+        }
+    }
+
+    /// Countries with a price index on disk, sorted by name.
+    pub(crate) fn countries() -> Vec<String> {
+        let mut countries: Vec<String> = match read_dir("data") {
+            Ok(entries) => entries
+                .flatten()
+                .filter_map(|entry| {
+                    let file_name: String = entry.file_name().to_string_lossy().to_string();
+                    file_name
+                        .strip_prefix("cpi_")
+                        .and_then(|file_name| file_name.strip_suffix(".csv"))
+                        .map(|country| country.to_string())
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        countries.sort();
+
+        countries
+    }
+
+    /// Years with at least one observation, which can serve as base years.
+    pub(crate) fn years(&self) -> Vec<i32> {
+        let mut years: Vec<i32> = self.dates.iter().map(|date| date.year()).collect();
+        years.dedup();
+
+        years
+    }
+
+    /// Price level at date: the last observation on or before it. Dates before the first
+    /// observation take the first one.
+    pub(crate) fn level(&self, date: NaiveDate) -> Result<f64, String> {
+        if self.values.is_empty() {
+            return Err(format!("The {} price index is empty", self.country));
+        }
+        let i: usize = self
+            .dates
+            .partition_point(|observation| *observation <= date);
+
+        Ok(self.values[i.max(1) - 1])
+    }
+
+    /// Average price level of a year.
+    pub(crate) fn year_level(&self, year: i32) -> Result<f64, String> {
+        let levels: Vec<f64> = self
+            .dates
+            .iter()
+            .zip(self.values.iter())
+            .filter(|(date, _value)| date.year() == year)
+            .map(|(_date, value)| *value)
+            .collect();
+        match levels.is_empty() {
+            true => Err(format!(
+                "The {} price index has no observation in {}",
+                self.country, year
+            )),
+            false => Ok(levels.iter().sum::<f64>() / levels.len() as f64),
+        }
+    }
+
+    /// Factors by which to multiply amounts of the dates, in order, to express them at the price
+    /// level of the base year. The level of the base year is computed once.
+    pub(crate) fn deflators(
+        &self,
+        dates: impl Iterator<Item = NaiveDate>,
+        base_year: i32,
+    ) -> Result<Vec<f64>, String> {
+        let base_level: f64 = self.year_level(base_year)?;
+        dates
+            .map(|date| Ok(base_level / self.level(date)?))
+            .collect()
+    }
+}
+
+impl RealTerms {
+    /// Deflators of every date, in order.
+    pub(crate) fn deflators(
+        &self,
+        dates: impl Iterator<Item = NaiveDate>,
+    ) -> Result<Vec<f64>, String> {
+        self.price_index.deflators(dates, self.base_year)
+    }
+}
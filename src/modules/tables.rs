@@ -1,4 +1,6 @@
-use super::financial::{Account, AccountType, Currency, Entity, EntityType, Party, Transaction};
+use super::financial::{
+    Account, AccountType, Currency, Entity, EntityType, Party, Quantity, Transaction,
+};
 use chrono::{Local, NaiveDate};
 use polars::prelude::*;
 use std::fs::{create_dir, File};
@@ -346,7 +348,22 @@ impl Table for ExpensesTable {
         &mut self.data_frame
     }
 
+    /// Expenses saved before they could carry a quantity get empty quantity and unit columns.
+    /// Empty columns are read as text, so they are cast to their types as well.
     fn create(data_frame: DataFrame) -> Box<Self> {
+        let mut data_frame: DataFrame = data_frame;
+        for (name, data_type) in [("quantity", DataType::Float64), ("unit", DataType::String)] {
+            let column: Column = match data_frame.column(name) {
+                Ok(column) => column
+                    .cast(&data_type)
+                    .expect("Failed to read expense quantities"),
+                Err(_) => Column::full_null(name.into(), data_frame.height(), &data_type),
+            };
+            data_frame
+                .with_column(column)
+                .expect("Failed to add expense quantities");
+        }
+
         Box::new(ExpensesTable { data_frame })
     }
 
@@ -382,6 +399,8 @@ impl Table for ExpensesTable {
                 Vec::<i64>::new(),
             )),
             Column::from(Series::new(PlSmallStr::from("party_id"), Vec::<i64>::new())),
+            Column::from(Series::new(PlSmallStr::from("quantity"), Vec::<f64>::new())),
+            Column::from(Series::new(PlSmallStr::from("unit"), Vec::<String>::new())),
         ])
         .expect("Failed to initialize empty expenses table");
 
@@ -400,6 +419,7 @@ impl ExpensesTable {
             subcategory,
             description,
             entity_id,
+            quantity,
         } = transaction
         {
             let id: i64 = self.next_id();
@@ -413,7 +433,9 @@ impl ExpensesTable {
                 "subcategory" => [subcategory.to_string()],
                 "description" => [description.to_string()],
                 "entity_id" => [*entity_id],
-                "party_id" => [party_id]
+                "party_id" => [party_id],
+                "quantity" => [quantity.as_ref().map(|quantity| quantity.amount)],
+                "unit" => [quantity.as_ref().map(|quantity| quantity.unit.clone())]
             )
             .expect(format!("Failed to create {} record", ExpensesTable::name()).as_str());
 
@@ -464,6 +486,22 @@ impl ExpensesTable {
             .collect()
     }
 
+    /// Units the quantities of the expenses were given in.
+    pub(crate) fn units(&self) -> Vec<String> {
+        self.data_frame()
+            .column("unit")
+            .unwrap()
+            .drop_nulls()
+            .unique()
+            .unwrap()
+            .str()
+            .unwrap()
+            .sort(false)
+            .into_no_null_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
     // Deletes records corresponding to a party.
     pub(crate) fn delete_party(&mut self, party_id: i64) -> () {
         self.data_frame = self
@@ -564,6 +602,17 @@ impl ExpensesTable {
                 .unwrap()
                 .get(0)
                 .unwrap(),
+            quantity: record
+                .column("quantity")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .get(0)
+                .zip(record.column("unit").unwrap().str().unwrap().get(0))
+                .map(|(amount, unit)| Quantity {
+                    amount,
+                    unit: unit.to_string(),
+                }),
         }
     }
 }
@@ -822,6 +871,20 @@ impl Table for PartyTable {
 }
 
 impl PartyTable {
+    /// Iterator over IDs
+    pub(crate) fn iter(&self) -> IntoIter<i64> {
+        self.data_frame
+            .sort([format!("{}_id", PartyTable::name())], Default::default())
+            .unwrap()
+            .column(format!("{}_id", PartyTable::name()).as_str())
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect::<Vec<i64>>()
+            .into_iter()
+    }
+
     /// Adds party record to the table
     pub fn insert_party(&mut self, party: &Party) -> () {
         let id: i64 = self.next_id();
@@ -1055,6 +1118,16 @@ impl AccountTable {
 
     /// Adds account record to the table
     pub fn insert_account(&mut self, account: &Account) -> i64 {
+        self.insert_account_created_at(account, Local::now().date_naive())
+    }
+
+    /// Adds account record to the table, with a creation date other than today. Useful when
+    /// the initial balance of the account is known at some date in the past.
+    pub fn insert_account_created_at(
+        &mut self,
+        account: &Account,
+        creation_date: NaiveDate,
+    ) -> i64 {
         let id: i64 = self.next_id();
 
         let record = df!(
@@ -1064,7 +1137,7 @@ impl AccountTable {
             "currency" => [account.currency().to_string()],
             format!("{}_type", AccountTable::name()) => [account.account_type().to_string()],
             "initial_balance" => [account.initial_balance()],
-            "creation_date" => [Local::now().date_naive()]
+            "creation_date" => [creation_date]
         )
         .expect(format!("Failed to create {} record", AccountTable::name()).as_str());
 
@@ -1149,3 +1222,986 @@ impl AccountTable {
             .collect()
     }
 }
+
+pub struct AttachmentTable {
+    pub data_frame: DataFrame,
+}
+
+impl Table for AttachmentTable {
+    fn name() -> String {
+        String::from("attachment")
+    }
+
+    fn data_frame(&self) -> &DataFrame {
+        &self.data_frame
+    }
+
+    fn mut_data_frame(&mut self) -> &mut DataFrame {
+        &mut self.data_frame
+    }
+
+    fn create(data_frame: DataFrame) -> Box<Self> {
+        Box::new(AttachmentTable { data_frame })
+    }
+
+    fn new() -> Box<Self> {
+        let data_frame = DataFrame::new(vec![
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", AttachmentTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(PlSmallStr::from("party_id"), Vec::<i64>::new())),
+            Column::from(Series::new(
+                PlSmallStr::from("file_name"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("stored_file_name"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("creation_date"),
+                Vec::<NaiveDate>::new(),
+            )),
+        ])
+        .expect(
+            format!(
+                "Failed to initialize empty {} table",
+                AttachmentTable::name()
+            )
+            .as_str(),
+        );
+
+        AttachmentTable::create(data_frame)
+    }
+}
+
+impl AttachmentTable {
+    /// Adds the record of a file attached to a party. The file is expected to be already
+    /// stored under stored_file_name.
+    pub(crate) fn insert_attachment(
+        &mut self,
+        party_id: i64,
+        file_name: &str,
+        stored_file_name: &str,
+    ) -> i64 {
+        let id: i64 = self.next_id();
+
+        let record = df!(
+            format!("{}_id", AttachmentTable::name()) => [id],
+            "party_id" => [party_id],
+            "file_name" => [file_name],
+            "stored_file_name" => [stored_file_name],
+            "creation_date" => [Local::now().date_naive()]
+        )
+        .expect(format!("Failed to create {} record", AttachmentTable::name()).as_str());
+
+        self.data_frame = self
+            .data_frame
+            .vstack(&record)
+            .expect(format!("Failed to insert {} record", AttachmentTable::name()).as_str());
+
+        id
+    }
+
+    /// Returns the IDs, original names and stored names of the files attached to a party.
+    pub(crate) fn party_attachments(&self, party_id: i64) -> Vec<(i64, String, String)> {
+        let records: DataFrame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col("party_id").eq(lit(party_id)))
+            .sort(
+                [format!("{}_id", AttachmentTable::name())],
+                Default::default(),
+            )
+            .collect()
+            .unwrap();
+
+        let ids = records
+            .column(format!("{}_id", AttachmentTable::name()).as_str())
+            .unwrap()
+            .i64()
+            .unwrap()
+            .clone();
+        let file_names = records.column("file_name").unwrap().str().unwrap().clone();
+        let stored_file_names = records
+            .column("stored_file_name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .clone();
+
+        ids.into_no_null_iter()
+            .zip(file_names.into_no_null_iter())
+            .zip(stored_file_names.into_no_null_iter())
+            .map(|((id, file_name), stored_file_name)| {
+                (id, file_name.to_string(), stored_file_name.to_string())
+            })
+            .collect()
+    }
+
+    /// Returns the stored name of every file still referenced by some record.
+    pub(crate) fn stored_file_names(&self) -> Vec<String> {
+        self.data_frame
+            .column("stored_file_name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    // Deletes records corresponding to a party.
+    pub(crate) fn delete_party(&mut self, party_id: i64) -> () {
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col("party_id").neq(lit(party_id)))
+            .collect()
+            .unwrap();
+    }
+}
+
+pub struct BudgetTable {
+    pub data_frame: DataFrame,
+}
+
+impl Table for BudgetTable {
+    fn name() -> String {
+        String::from("budget")
+    }
+
+    fn data_frame(&self) -> &DataFrame {
+        &self.data_frame
+    }
+
+    fn mut_data_frame(&mut self) -> &mut DataFrame {
+        &mut self.data_frame
+    }
+
+    fn create(data_frame: DataFrame) -> Box<Self> {
+        // A csv where no budget has a subcategory would be read with a column of nulls
+        let data_frame: DataFrame = data_frame
+            .lazy()
+            .with_column(col("subcategory").cast(DataType::String))
+            .collect()
+            .expect(format!("Failed to read {} table", BudgetTable::name()).as_str());
+
+        Box::new(BudgetTable { data_frame })
+    }
+
+    fn new() -> Box<Self> {
+        let data_frame = DataFrame::new(vec![
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", BudgetTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("category"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("subcategory"),
+                Vec::<Option<String>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("period"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(PlSmallStr::from("value"), Vec::<f64>::new())),
+            Column::from(Series::new(
+                PlSmallStr::from("currency"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("rollover"),
+                Vec::<bool>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("creation_date"),
+                Vec::<NaiveDate>::new(),
+            )),
+        ])
+        .expect(format!("Failed to initialize empty {} table", BudgetTable::name()).as_str());
+
+        BudgetTable::create(data_frame)
+    }
+}
+
+impl BudgetTable {
+    /// Adds a budget of value in currency for every period of an expense category, or of one
+    /// of its subcategories.
+    pub(crate) fn insert_budget(
+        &mut self,
+        category: &str,
+        subcategory: Option<&str>,
+        period: &str,
+        value: f64,
+        currency: &Currency,
+        rollover: bool,
+    ) -> i64 {
+        let id: i64 = self.next_id();
+
+        let record = df!(
+            format!("{}_id", BudgetTable::name()) => [id],
+            "category" => [category],
+            "subcategory" => [subcategory],
+            "period" => [period],
+            "value" => [value],
+            "currency" => [currency.to_string()],
+            "rollover" => [rollover],
+            "creation_date" => [Local::now().date_naive()]
+        )
+        .expect(format!("Failed to create {} record", BudgetTable::name()).as_str());
+
+        self.data_frame = self
+            .data_frame
+            .vstack(&record)
+            .expect(format!("Failed to insert {} record", BudgetTable::name()).as_str());
+
+        id
+    }
+
+    /// Returns the ID of the budget of a category, or subcategory, for the given period.
+    pub(crate) fn find_budget(
+        &self,
+        category: &str,
+        subcategory: Option<&str>,
+        period: &str,
+    ) -> Option<i64> {
+        let subcategory_filter: Expr = match subcategory {
+            Some(subcategory) => col("subcategory").eq(lit(subcategory)),
+            None => col("subcategory").is_null(),
+        };
+
+        self.data_frame
+            .clone()
+            .lazy()
+            .filter(
+                col("category")
+                    .eq(lit(category))
+                    .and(subcategory_filter)
+                    .and(col("period").eq(lit(period))),
+            )
+            .collect()
+            .unwrap()
+            .column(format!("{}_id", BudgetTable::name()).as_str())
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .next()
+            .flatten()
+    }
+
+    /// Sets the value, currency and rollover of a budget. Its ID and creation date are kept.
+    pub(crate) fn update_budget(
+        &mut self,
+        budget_id: i64,
+        value: f64,
+        currency: &Currency,
+        rollover: bool,
+    ) -> () {
+        let is_budget: Expr = col(format!("{}_id", BudgetTable::name())).eq(lit(budget_id));
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .with_columns([
+                when(is_budget.clone())
+                    .then(lit(value))
+                    .otherwise(col("value"))
+                    .alias("value"),
+                when(is_budget.clone())
+                    .then(lit(currency.to_string()))
+                    .otherwise(col("currency"))
+                    .alias("currency"),
+                when(is_budget)
+                    .then(lit(rollover))
+                    .otherwise(col("rollover"))
+                    .alias("rollover"),
+            ])
+            .collect()
+            .unwrap();
+    }
+
+    pub(crate) fn delete_budget(&mut self, budget_id: i64) -> () {
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col(format!("{}_id", BudgetTable::name())).neq(lit(budget_id)))
+            .collect()
+            .unwrap();
+    }
+}
+
+pub struct GoalTable {
+    pub data_frame: DataFrame,
+}
+
+impl Table for GoalTable {
+    fn name() -> String {
+        String::from("goal")
+    }
+
+    fn data_frame(&self) -> &DataFrame {
+        &self.data_frame
+    }
+
+    fn mut_data_frame(&mut self) -> &mut DataFrame {
+        &mut self.data_frame
+    }
+
+    fn create(data_frame: DataFrame) -> Box<Self> {
+        Box::new(GoalTable { data_frame })
+    }
+
+    fn new() -> Box<Self> {
+        let data_frame = DataFrame::new(vec![
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", GoalTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(PlSmallStr::from("name"), Vec::<String>::new())),
+            Column::from(Series::new(
+                PlSmallStr::from("target_value"),
+                Vec::<f64>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("currency"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("target_date"),
+                Vec::<NaiveDate>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("creation_date"),
+                Vec::<NaiveDate>::new(),
+            )),
+        ])
+        .expect(format!("Failed to initialize empty {} table", GoalTable::name()).as_str());
+
+        GoalTable::create(data_frame)
+    }
+}
+
+impl GoalTable {
+    /// Adds a savings goal of target_value in currency, to be reached by target_date.
+    pub(crate) fn insert_goal(
+        &mut self,
+        name: &str,
+        target_value: f64,
+        currency: &Currency,
+        target_date: NaiveDate,
+    ) -> i64 {
+        let id: i64 = self.next_id();
+
+        let record = df!(
+            format!("{}_id", GoalTable::name()) => [id],
+            "name" => [name],
+            "target_value" => [target_value],
+            "currency" => [currency.to_string()],
+            "target_date" => [target_date],
+            "creation_date" => [Local::now().date_naive()]
+        )
+        .expect(format!("Failed to create {} record", GoalTable::name()).as_str());
+
+        self.data_frame = self
+            .data_frame
+            .vstack(&record)
+            .expect(format!("Failed to insert {} record", GoalTable::name()).as_str());
+
+        id
+    }
+
+    pub(crate) fn delete_goal(&mut self, goal_id: i64) -> () {
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col(format!("{}_id", GoalTable::name())).neq(lit(goal_id)))
+            .collect()
+            .unwrap();
+    }
+}
+
+pub struct AllocationTable {
+    pub data_frame: DataFrame,
+}
+
+impl Table for AllocationTable {
+    fn name() -> String {
+        String::from("allocation")
+    }
+
+    fn data_frame(&self) -> &DataFrame {
+        &self.data_frame
+    }
+
+    fn mut_data_frame(&mut self) -> &mut DataFrame {
+        &mut self.data_frame
+    }
+
+    fn create(data_frame: DataFrame) -> Box<Self> {
+        Box::new(AllocationTable { data_frame })
+    }
+
+    fn new() -> Box<Self> {
+        let data_frame = DataFrame::new(vec![
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", AllocationTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", GoalTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(PlSmallStr::from("value"), Vec::<f64>::new())),
+            Column::from(Series::new(
+                PlSmallStr::from("date"),
+                Vec::<NaiveDate>::new(),
+            )),
+        ])
+        .expect(
+            format!(
+                "Failed to initialize empty {} table",
+                AllocationTable::name()
+            )
+            .as_str(),
+        );
+
+        AllocationTable::create(data_frame)
+    }
+}
+
+impl AllocationTable {
+    /// Earmarks value, in the currency of the goal, for a goal on date. A negative value
+    /// releases part of what was earmarked.
+    pub(crate) fn insert_allocation(&mut self, goal_id: i64, value: f64, date: NaiveDate) -> i64 {
+        let id: i64 = self.next_id();
+
+        let record = df!(
+            format!("{}_id", AllocationTable::name()) => [id],
+            format!("{}_id", GoalTable::name()) => [goal_id],
+            "value" => [value],
+            "date" => [date]
+        )
+        .expect(format!("Failed to create {} record", AllocationTable::name()).as_str());
+
+        self.data_frame = self
+            .data_frame
+            .vstack(&record)
+            .expect(format!("Failed to insert {} record", AllocationTable::name()).as_str());
+
+        id
+    }
+
+    /// Returns the total allocated to a goal until the end of date.
+    pub(crate) fn allocated(&self, goal_id: i64, date: NaiveDate) -> f64 {
+        self.data_frame
+            .clone()
+            .lazy()
+            .filter(
+                col(format!("{}_id", GoalTable::name()))
+                    .eq(lit(goal_id))
+                    .and(col("date").lt_eq(lit(date))),
+            )
+            .select([col("value").sum()])
+            .collect()
+            .unwrap()
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .next()
+            .flatten()
+            .unwrap_or(0.0)
+    }
+
+    // Deletes records corresponding to a goal.
+    pub(crate) fn delete_goal(&mut self, goal_id: i64) -> () {
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col(format!("{}_id", GoalTable::name())).neq(lit(goal_id)))
+            .collect()
+            .unwrap();
+    }
+}
+
+pub struct TemplateTable {
+    pub data_frame: DataFrame,
+}
+
+impl Table for TemplateTable {
+    fn name() -> String {
+        String::from("template")
+    }
+
+    fn data_frame(&self) -> &DataFrame {
+        &self.data_frame
+    }
+
+    fn mut_data_frame(&mut self) -> &mut DataFrame {
+        &mut self.data_frame
+    }
+
+    fn create(data_frame: DataFrame) -> Box<Self> {
+        // A csv where no template has an end date would be read with a column of nulls
+        let data_frame: DataFrame = data_frame
+            .lazy()
+            .with_column(col("end_date").cast(DataType::Date))
+            .collect()
+            .expect(format!("Failed to read {} table", TemplateTable::name()).as_str());
+
+        Box::new(TemplateTable { data_frame })
+    }
+
+    fn new() -> Box<Self> {
+        let data_frame = DataFrame::new(vec![
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", TemplateTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(PlSmallStr::from("name"), Vec::<String>::new())),
+            Column::from(Series::new(PlSmallStr::from("rule"), Vec::<String>::new())),
+            Column::from(Series::new(
+                PlSmallStr::from("start_date"),
+                Vec::<NaiveDate>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("end_date"),
+                Vec::<Option<NaiveDate>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("next_date"),
+                Vec::<NaiveDate>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("creation_date"),
+                Vec::<NaiveDate>::new(),
+            )),
+        ])
+        .expect(format!("Failed to initialize empty {} table", TemplateTable::name()).as_str());
+
+        TemplateTable::create(data_frame)
+    }
+}
+
+impl TemplateTable {
+    /// Adds a recurring party template, whose first occurrence is due on next_date.
+    pub(crate) fn insert_template(
+        &mut self,
+        name: &str,
+        rule: &str,
+        start_date: NaiveDate,
+        end_date: Option<NaiveDate>,
+        next_date: NaiveDate,
+    ) -> i64 {
+        let id: i64 = self.next_id();
+
+        let record = df!(
+            format!("{}_id", TemplateTable::name()) => [id],
+            "name" => [name],
+            "rule" => [rule],
+            "start_date" => [start_date],
+            "end_date" => [end_date],
+            "next_date" => [next_date],
+            "creation_date" => [Local::now().date_naive()]
+        )
+        .expect(format!("Failed to create {} record", TemplateTable::name()).as_str());
+
+        self.data_frame = self
+            .data_frame
+            .vstack(&record)
+            .expect(format!("Failed to insert {} record", TemplateTable::name()).as_str());
+
+        id
+    }
+
+    /// Sets the date on which the next occurrence of a template is due.
+    pub(crate) fn set_next_date(&mut self, template_id: i64, next_date: NaiveDate) -> () {
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .with_column(
+                when(col(format!("{}_id", TemplateTable::name())).eq(lit(template_id)))
+                    .then(lit(next_date).cast(DataType::Date))
+                    .otherwise(col("next_date"))
+                    .alias("next_date"),
+            )
+            .collect()
+            .unwrap();
+    }
+
+    pub(crate) fn delete_template(&mut self, template_id: i64) -> () {
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col(format!("{}_id", TemplateTable::name())).neq(lit(template_id)))
+            .collect()
+            .unwrap();
+    }
+}
+
+pub struct TemplateTransactionTable {
+    pub data_frame: DataFrame,
+}
+
+impl Table for TemplateTransactionTable {
+    fn name() -> String {
+        String::from("template_transaction")
+    }
+
+    fn data_frame(&self) -> &DataFrame {
+        &self.data_frame
+    }
+
+    fn mut_data_frame(&mut self) -> &mut DataFrame {
+        &mut self.data_frame
+    }
+
+    /// Templates saved before they could carry a quantity get empty quantity and unit columns.
+    fn create(data_frame: DataFrame) -> Box<Self> {
+        let mut data_frame: DataFrame = data_frame;
+        for name in ["quantity", "unit"] {
+            if data_frame.column(name).is_err() {
+                let column: Column =
+                    Column::full_null(name.into(), data_frame.height(), &DataType::String);
+                data_frame
+                    .with_column(column)
+                    .expect("Failed to add template quantities");
+            }
+        }
+
+        // Columns that only some transaction types have may be read as columns of nulls
+        let data_frame: DataFrame = data_frame
+            .lazy()
+            .with_columns([
+                col("category").cast(DataType::String),
+                col("subcategory").cast(DataType::String),
+                col("description").cast(DataType::String),
+                col("entity_id").cast(DataType::Int64),
+                col("account_id").cast(DataType::Int64),
+                col("quantity").cast(DataType::Float64),
+                col("unit").cast(DataType::String),
+            ])
+            .collect()
+            .expect(format!("Failed to read {} table", TemplateTransactionTable::name()).as_str());
+
+        Box::new(TemplateTransactionTable { data_frame })
+    }
+
+    fn new() -> Box<Self> {
+        let data_frame = DataFrame::new(vec![
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", TemplateTransactionTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", TemplateTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("transaction_type"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(PlSmallStr::from("value"), Vec::<f64>::new())),
+            Column::from(Series::new(
+                PlSmallStr::from("currency"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("category"),
+                Vec::<Option<String>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("subcategory"),
+                Vec::<Option<String>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("description"),
+                Vec::<Option<String>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("entity_id"),
+                Vec::<Option<i64>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("account_id"),
+                Vec::<Option<i64>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("quantity"),
+                Vec::<Option<f64>>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("unit"),
+                Vec::<Option<String>>::new(),
+            )),
+        ])
+        .expect(
+            format!(
+                "Failed to initialize empty {} table",
+                TemplateTransactionTable::name()
+            )
+            .as_str(),
+        );
+
+        TemplateTransactionTable::create(data_frame)
+    }
+}
+
+impl TemplateTransactionTable {
+    /// Adds a transaction of a template. Its date is not kept, every occurrence of the template
+    /// gets its own.
+    pub(crate) fn insert_transaction(&mut self, template_id: i64, transaction: &Transaction) -> () {
+        let (category, subcategory, description, entity_id, account_id) = match transaction {
+            Transaction::Income {
+                category,
+                subcategory,
+                description,
+                entity_id,
+                ..
+            }
+            | Transaction::Expense {
+                category,
+                subcategory,
+                description,
+                entity_id,
+                ..
+            } => (
+                Some(category.clone()),
+                Some(subcategory.clone()),
+                Some(description.clone()),
+                Some(*entity_id),
+                None,
+            ),
+            Transaction::Credit { account_id, .. } | Transaction::Debit { account_id, .. } => {
+                (None, None, None, None, Some(*account_id))
+            }
+        };
+        let quantity: Option<&Quantity> = match transaction {
+            Transaction::Expense { quantity, .. } => quantity.as_ref(),
+            _ => None,
+        };
+
+        let record = df!(
+            format!("{}_id", TemplateTransactionTable::name()) => [self.next_id()],
+            format!("{}_id", TemplateTable::name()) => [template_id],
+            "transaction_type" => [transaction.transaction_type()],
+            "value" => [transaction.value()],
+            "currency" => [transaction.currency().to_string()],
+            "category" => [category],
+            "subcategory" => [subcategory],
+            "description" => [description],
+            "entity_id" => [entity_id],
+            "account_id" => [account_id],
+            "quantity" => [quantity.map(|quantity| quantity.amount)],
+            "unit" => [quantity.map(|quantity| quantity.unit.clone())]
+        )
+        .expect(
+            format!(
+                "Failed to create {} record",
+                TemplateTransactionTable::name()
+            )
+            .as_str(),
+        );
+
+        self.data_frame = self.data_frame.vstack(&record).expect(
+            format!(
+                "Failed to insert {} record",
+                TemplateTransactionTable::name()
+            )
+            .as_str(),
+        );
+    }
+
+    /// Returns the transactions of a template, dated on date.
+    pub(crate) fn transactions(&self, template_id: i64, date: NaiveDate) -> Vec<Transaction> {
+        let records: DataFrame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col(format!("{}_id", TemplateTable::name())).eq(lit(template_id)))
+            .collect()
+            .unwrap();
+
+        let text = |name: &str, i: usize| -> String {
+            records
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(i)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let id = |name: &str, i: usize| -> i64 {
+            records
+                .column(name)
+                .unwrap()
+                .i64()
+                .unwrap()
+                .get(i)
+                .unwrap_or_default()
+        };
+
+        (0..records.height())
+            .map(|i| {
+                let value: f64 = records
+                    .column("value")
+                    .unwrap()
+                    .f64()
+                    .unwrap()
+                    .get(i)
+                    .unwrap();
+                let currency: Currency = Currency::from_str(text("currency", i).as_str()).unwrap();
+                match text("transaction_type", i).as_str() {
+                    "Income" => Transaction::Income {
+                        value,
+                        currency,
+                        date,
+                        category: text("category", i),
+                        subcategory: text("subcategory", i),
+                        description: text("description", i),
+                        entity_id: id("entity_id", i),
+                    },
+                    "Expense" => Transaction::Expense {
+                        value,
+                        currency,
+                        date,
+                        category: text("category", i),
+                        subcategory: text("subcategory", i),
+                        description: text("description", i),
+                        entity_id: id("entity_id", i),
+                        quantity: records
+                            .column("quantity")
+                            .unwrap()
+                            .f64()
+                            .unwrap()
+                            .get(i)
+                            .zip(records.column("unit").unwrap().str().unwrap().get(i))
+                            .map(|(amount, unit)| Quantity {
+                                amount,
+                                unit: unit.to_string(),
+                            }),
+                    },
+                    "Credit" => Transaction::Credit {
+                        value,
+                        currency,
+                        date,
+                        account_id: id("account_id", i),
+                    },
+                    _ => Transaction::Debit {
+                        value,
+                        currency,
+                        date,
+                        account_id: id("account_id", i),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    // Deletes records corresponding to a template.
+    pub(crate) fn delete_template(&mut self, template_id: i64) -> () {
+        self.data_frame = self
+            .data_frame
+            .clone()
+            .lazy()
+            .filter(col(format!("{}_id", TemplateTable::name())).neq(lit(template_id)))
+            .collect()
+            .unwrap();
+    }
+}
+
+pub struct DismissalTable {
+    pub data_frame: DataFrame,
+}
+
+impl Table for DismissalTable {
+    fn name() -> String {
+        String::from("dismissal")
+    }
+
+    fn data_frame(&self) -> &DataFrame {
+        &self.data_frame
+    }
+
+    fn mut_data_frame(&mut self) -> &mut DataFrame {
+        &mut self.data_frame
+    }
+
+    fn create(data_frame: DataFrame) -> Box<Self> {
+        Box::new(DismissalTable { data_frame })
+    }
+
+    fn new() -> Box<Self> {
+        let data_frame = DataFrame::new(vec![
+            Column::from(Series::new(
+                PlSmallStr::from(format!("{}_id", DismissalTable::name())),
+                Vec::<i64>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("anomaly"),
+                Vec::<String>::new(),
+            )),
+            Column::from(Series::new(
+                PlSmallStr::from("date"),
+                Vec::<NaiveDate>::new(),
+            )),
+        ])
+        .expect(
+            format!(
+                "Failed to initialize empty {} table",
+                DismissalTable::name()
+            )
+            .as_str(),
+        );
+
+        DismissalTable::create(data_frame)
+    }
+}
+
+impl DismissalTable {
+    /// Records that the anomaly with the key was reviewed on date and is not to be flagged
+    /// again.
+    pub(crate) fn insert_dismissal(&mut self, anomaly: &str, date: NaiveDate) -> i64 {
+        let id: i64 = self.next_id();
+
+        let record = df!(
+            format!("{}_id", DismissalTable::name()) => [id],
+            "anomaly" => [anomaly],
+            "date" => [date]
+        )
+        .expect(format!("Failed to create {} record", DismissalTable::name()).as_str());
+
+        self.data_frame = self
+            .data_frame
+            .vstack(&record)
+            .expect(format!("Failed to insert {} record", DismissalTable::name()).as_str());
+
+        id
+    }
+
+    /// Returns whether the anomaly with the key was dismissed.
+    pub(crate) fn is_dismissed(&self, anomaly: &str) -> bool {
+        self.data_frame
+            .column("anomaly")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .any(|dismissed| dismissed == Some(anomaly))
+    }
+}
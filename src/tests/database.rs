@@ -1,9 +1,29 @@
 #[cfg(test)]
 mod tests {
+    use crate::modules::database::anomalies::AnomalyKind;
+    use crate::modules::database::attachments::Attachment;
+    use crate::modules::database::budgets::Budget;
+    use crate::modules::database::goals::{monthly_contribution, Goal};
+    use crate::modules::database::independence::IndependencePlan;
+    use crate::modules::database::journal::{
+        account_component, parse_journal, JournalFormat, JournalMapping, JournalTransaction,
+    };
+    use crate::modules::database::recurring::{Recurrence, Template};
+    use crate::modules::database::settings::{PeriodDefinition, Settings};
+    use crate::modules::database::snapshot::Snapshot;
+    use crate::modules::database::spreadsheet::{spreadsheet, Sheet};
+    use crate::modules::database::subscriptions::Cadence;
+    use crate::modules::database::suggestions::{SuggestionContext, SuggestionModel};
+    use crate::modules::database::summaries::{
+        ComparisonPeriods, FundGrouping, SummaryDimension, TimeUnit, Valuation,
+    };
     use crate::modules::database::DataBase;
     use crate::modules::financial::*;
     use crate::tests::test_helpers::init_party;
+    use chrono::{Local, NaiveDate, Weekday};
     use polars::df;
+    use polars::prelude::*;
+    use sha2::{Digest, Sha256};
 
     #[test]
     fn correct_insert_party() {
@@ -54,4 +74,1849 @@ mod tests {
 
         assert!(actual_result.equals(&expected_result));
     }
+
+    fn init_expenses_data_frame() -> DataFrame {
+        df!(
+            "expense_id" => [0i64, 1i64, 2i64, 3i64],
+            "value" => [45.3f64, 52.1f64, 1150.0f64, 3.2f64],
+            "currency" => ["EUR", "EUR", "EUR", "EUR"],
+            "date" => [
+                NaiveDate::from_ymd_opt(2024, 12, 7).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 12, 14).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 12, 25).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 12, 26).unwrap(),
+            ],
+            "category" => ["Food", "Food", "Housing", "Food"],
+            "subcategory" => ["Groceries", "Groceries", "Rent", "Bakery"],
+            "description" => ["Weekly shopping", "Weekly shopping", "December rent", "Croissants"],
+            "entity_id" => [1i64, 1i64, 2i64, 3i64],
+            "party_id" => [0i64, 1i64, 2i64, 3i64],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn correct_suggestion_from_entity() {
+        let model: SuggestionModel =
+            SuggestionModel::train(&TransactionType::Expense, &init_expenses_data_frame());
+        let context = SuggestionContext {
+            entity_id: Some(2),
+            description: String::new(),
+            value: None,
+            date: None,
+        };
+
+        assert_eq!(
+            model.most_likely(&context),
+            Some((String::from("Housing"), String::from("Rent")))
+        );
+    }
+
+    #[test]
+    fn correct_suggestion_ranking() {
+        let model: SuggestionModel =
+            SuggestionModel::train(&TransactionType::Expense, &init_expenses_data_frame());
+        let context = SuggestionContext {
+            entity_id: Some(3),
+            description: String::from("croissants"),
+            value: Some(2.9),
+            date: None,
+        };
+
+        let categories: Vec<String> = vec![String::from("Housing"), String::from("Food")];
+        let subcategories: Vec<String> = vec![String::from("Bakery"), String::from("Groceries")];
+
+        let posteriors = model.posteriors(&context);
+        assert_eq!(
+            SuggestionModel::rank_categories(&posteriors, categories),
+            vec![String::from("Food"), String::from("Housing")]
+        );
+        assert_eq!(
+            SuggestionModel::rank_subcategories(&posteriors, "Food", subcategories),
+            vec![String::from("Bakery"), String::from("Groceries")]
+        );
+    }
+
+    #[test]
+    fn correct_account_component() {
+        assert_eq!(account_component("regular salary"), "Regular-Salary");
+        assert_eq!(account_component("Food & drinks"), "Food-Drinks");
+        assert_eq!(account_component(""), "Unknown");
+    }
+
+    #[test]
+    fn correct_beancount_journal() {
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_entity(&Entity::new(
+            String::from("Systembolaget"),
+            String::from("Sweden"),
+            EntityType::State,
+            String::from("Liquor store"),
+        ));
+        data_base.insert_account(&Account::new(
+            String::from("Pocket money"),
+            String::from("Sweden"),
+            Currency::SEK,
+            AccountType::Cash,
+            200.0f64,
+        ));
+        data_base.insert_party(&mut init_party());
+
+        let journal: String = data_base.journal(&JournalFormat::Beancount).unwrap();
+
+        assert!(journal.contains("2024-12-01 open Expenses:Drugs:Alcohol"));
+        assert!(journal.contains("Equity:Opening-Balances"));
+        assert!(journal
+            .lines()
+            .any(|line| line.contains(" balance Assets:Pocket-Money ")
+                & line.ends_with("100.00 SEK")));
+        assert!(journal
+            .lines()
+            .any(|line| line.contains(" balance Assets:Unknown ") & line.ends_with("120.00 EUR")));
+    }
+
+    #[test]
+    fn correct_hledger_journal() {
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_account(&Account::new(
+            String::from("Savings account at the cooperative bank of the upper valley"),
+            String::from("Switzerland"),
+            Currency::CHF,
+            AccountType::Deposit,
+            1080.0f64,
+        ));
+
+        let journal: String = data_base.journal(&JournalFormat::Hledger).unwrap();
+
+        assert!(journal.lines().any(|line| line.starts_with(
+            "    Assets:Savings-Account-At-The-Cooperative-Bank-Of-The-Upper-Valley  "
+        ) & line.ends_with("1080.00 CHF")));
+    }
+
+    #[test]
+    fn correct_journal_parsing() {
+        let journal: &str = "2024-01-01 open Assets:Bank EUR
+
+2024/01/05 * Lidl | Weekly shopping  ; comment
+    Expenses:Food:Groceries    45,30 €
+    Assets:Bank
+
+2024-01-06 * \"Shell\" \"Fuel\"
+    Expenses:Transport    60.00 EUR
+    Assets:Bank          -60.00 EUR = 894.70 EUR
+
+2024-01-07 * Exchange office
+    Assets:Bank      -100.00 EUR @ 11.50 SEK
+    Assets:Wallet     1150.00 SEK
+";
+
+        let results: Vec<Result<JournalTransaction, String>> = parse_journal(journal);
+
+        assert_eq!(results.len(), 3);
+        let shopping: &JournalTransaction = results[0].as_ref().unwrap();
+        assert_eq!(shopping.payee, "Lidl");
+        assert_eq!(shopping.narration, "Weekly shopping");
+        assert_eq!(shopping.postings.len(), 2);
+        assert_eq!(shopping.postings[1].value, -45.3);
+        let fuel: &JournalTransaction = results[1].as_ref().unwrap();
+        assert_eq!(fuel.payee, "Shell");
+        assert_eq!(fuel.postings[1].currency, Currency::EUR);
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn correct_journal_import() {
+        let journal: &str = "2024-01-01 * Opening balances
+    Assets:Bank:Current-Account    1000.00 EUR
+    Equity:Opening-Balances
+
+2024-01-05 * Lidl | Weekly shopping
+    Expenses:Food:Groceries    45.30 EUR
+    Assets:Bank:Current-Account
+
+2024-01-25 * ACME | Salary
+    Income:Salary             -2000.00 EUR
+    Assets:Bank:Current-Account    2000.00 EUR
+
+2024-01-26 * Unbalanced
+    Expenses:Food:Groceries    10.00 EUR
+    Assets:Bank:Current-Account    -9.00 EUR
+
+2024-01-27 * \"Coffee\"
+    Expenses:Food:Groceries    3.00 EUR
+    Assets:Bank:Current-Account
+
+2024-01-28 * Unbalanced cash
+    Expenses:Food:Groceries    10.00 EUR
+    Assets:Wallet    -9.00 EUR
+";
+        let journal_transactions: Vec<JournalTransaction> = parse_journal(journal)
+            .into_iter()
+            .filter_map(|result| result.ok())
+            .collect();
+        let mapping: JournalMapping =
+            JournalMapping::guess(&journal_transactions, String::from("Germany"));
+        let mut data_base: DataBase = DataBase::new();
+
+        let report = data_base.import_journal(&journal_transactions, &mapping);
+
+        assert_eq!(report.parties, 3);
+        assert_eq!(report.entities, 2);
+        assert_eq!(report.accounts, 1);
+        assert_eq!(report.refused.len(), 2);
+
+        let expected_result = df!(
+                "table" => ["income", "expenses", "funds", "party", "entity", "account"],
+                "records" => [1, 2, 3, 3, 3, 2]
+        )
+        .unwrap();
+        assert!(data_base.size().equals(&expected_result));
+
+        let account: Account = data_base.account(1);
+        assert_eq!(account.name(), "Bank Current Account");
+        assert_eq!(account.initial_balance(), 1000.0);
+    }
+
+    #[test]
+    fn correct_snapshot_round_trip() {
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_entity(&Entity::new(
+            String::from("Systembolaget"),
+            String::from("Sweden"),
+            EntityType::State,
+            String::from("Liquor store"),
+        ));
+        data_base.insert_account(&Account::new(
+            String::from("Pocket money"),
+            String::from("Sweden"),
+            Currency::SEK,
+            AccountType::Cash,
+            200.0f64,
+        ));
+        data_base.insert_party(&mut init_party());
+
+        let snapshot: Snapshot = Snapshot::parse(data_base.snapshot().as_str()).unwrap();
+
+        assert!(snapshot.database.size().equals(&data_base.size()));
+        assert_eq!(snapshot.database.snapshot(), data_base.snapshot());
+    }
+
+    #[test]
+    fn incorrect_snapshot() {
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_party(&mut init_party());
+        let snapshot: String = data_base.snapshot();
+
+        let tampered_snapshot: String = snapshot.replace("120.0", "1200.0");
+        assert!(Snapshot::parse(tampered_snapshot.as_str())
+            .err()
+            .unwrap()
+            .contains("checksum"));
+
+        // the party refers to an entity that is not in the snapshot
+        let mut party: Party = init_party();
+        party.add_transaction(Transaction::Expense {
+            value: 0.0,
+            currency: Currency::EUR,
+            date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+            category: String::from("Food"),
+            subcategory: String::from("Groceries"),
+            description: String::new(),
+            entity_id: 7,
+            quantity: None,
+        });
+        data_base.insert_party(&mut party);
+        assert!(Snapshot::parse(data_base.snapshot().as_str())
+            .err()
+            .unwrap()
+            .contains("missing entity 7"));
+    }
+
+    #[test]
+    fn correct_snapshot_missing_attachments() {
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_entity(&Entity::new(
+            String::from("Systembolaget"),
+            String::from("Sweden"),
+            EntityType::State,
+            String::from("Liquor store"),
+        ));
+        data_base.insert_account(&Account::new(
+            String::from("Pocket money"),
+            String::from("Sweden"),
+            Currency::SEK,
+            AccountType::Cash,
+            200.0f64,
+        ));
+        let party_id: i64 = data_base.insert_party(&mut init_party());
+        let mut snapshot: serde_json::Value =
+            serde_json::from_str(data_base.snapshot().as_str()).unwrap();
+
+        // a snapshot taken on a computer with an attached file that this one lacks
+        snapshot["tables"]["attachment"]["rows"] = serde_json::json!([{
+            "attachment_id": 0,
+            "party_id": party_id,
+            "file_name": "receipt.pdf",
+            "stored_file_name": "missing.pdf",
+            "creation_date": "2024-12-01",
+        }]);
+        let payload: serde_json::Value = serde_json::json!({
+            "tables": snapshot["tables"],
+            "exchange_rates": snapshot["exchange_rates"],
+        });
+        snapshot["checksum"] = serde_json::json!(Sha256::digest(payload.to_string().as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>());
+
+        let snapshot: Snapshot = Snapshot::parse(snapshot.to_string().as_str()).unwrap();
+        assert_eq!(snapshot.missing_attachments, vec!["missing.pdf"]);
+        assert!(snapshot
+            .to_string()
+            .contains("1 attached files are missing"));
+    }
+
+    #[test]
+    fn correct_spreadsheet() {
+        let report: DataFrame = df!(
+            "Category" => ["Food", "Housing"],
+            "Date" => [
+                NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 12, 2).unwrap(),
+            ],
+            "Count" => [3i64, 1i64],
+            "SEK" => [100.5f64, 1150.0f64],
+            "% Total Expenses" => [8.0f64, 92.0f64],
+        )
+        .unwrap();
+        let sheets: Vec<Sheet> = vec![
+            Sheet::new("Report", report.clone(), Some(Currency::SEK), true),
+            Sheet::new("Raw", report, None, false),
+        ];
+
+        let content: Vec<u8> = spreadsheet(&sheets).unwrap();
+        assert_eq!(&content[..2], b"PK");
+
+        let duplicated_sheets: Vec<Sheet> = vec![
+            Sheet::new("Report", DataFrame::empty(), None, false),
+            Sheet::new("Report", DataFrame::empty(), None, false),
+        ];
+        assert!(spreadsheet(&duplicated_sheets).is_err());
+    }
+
+    #[test]
+    fn correct_attachment_stored_file_name() {
+        let receipt: Attachment = Attachment {
+            file_name: String::from("Receipt.PDF"),
+            content: b"receipt".to_vec(),
+        };
+        let copy: Attachment = Attachment {
+            file_name: String::from("copy.pdf"),
+            content: b"receipt".to_vec(),
+        };
+        let note: Attachment = Attachment {
+            file_name: String::from("note"),
+            content: b"note".to_vec(),
+        };
+
+        assert_eq!(receipt.stored_file_name(), copy.stored_file_name());
+        assert!(receipt.stored_file_name().ends_with(".pdf"));
+        assert_eq!(receipt.stored_file_name().len(), 64 + 4);
+        assert_eq!(note.stored_file_name().len(), 64);
+    }
+
+    #[test]
+    fn correct_comparison_periods() {
+        let date =
+            |year: i32, month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let today: NaiveDate = date(2024, 3, 31);
+        let calendar: PeriodDefinition = PeriodDefinition::default();
+        let salary_cycle: PeriodDefinition = PeriodDefinition {
+            month_start_day: 25,
+            year_start_month: 4,
+            year_start_day: 6,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ComparisonPeriods::MonthToDate.periods(today, &calendar),
+            (
+                (date(2024, 2, 1), date(2024, 2, 29)),
+                (date(2024, 3, 1), today)
+            )
+        );
+        assert_eq!(
+            ComparisonPeriods::LastMonth.periods(today, &calendar),
+            (
+                (date(2024, 1, 1), date(2024, 1, 31)),
+                (date(2024, 2, 1), date(2024, 2, 29))
+            )
+        );
+        assert_eq!(
+            ComparisonPeriods::YearToDate.periods(today, &calendar),
+            (
+                (date(2023, 1, 1), date(2023, 3, 31)),
+                (date(2024, 1, 1), today)
+            )
+        );
+        assert_eq!(
+            ComparisonPeriods::LastMonth.periods(date(2024, 3, 10), &salary_cycle),
+            (
+                (date(2023, 12, 25), date(2024, 1, 24)),
+                (date(2024, 1, 25), date(2024, 2, 24))
+            )
+        );
+        assert_eq!(
+            ComparisonPeriods::YearToDate.periods(today, &salary_cycle),
+            (
+                (date(2022, 4, 6), date(2023, 3, 31)),
+                (date(2023, 4, 6), today)
+            )
+        );
+    }
+
+    #[test]
+    fn correct_settings() {
+        let settings: Settings = Settings {
+            periods: PeriodDefinition {
+                month_start_day: 25,
+                week_start: Weekday::Sun,
+                year_start_month: 4,
+                year_start_day: 6,
+            },
+            min_runway_months: 6,
+        };
+
+        assert_eq!(
+            Settings::from_json(settings.to_json().as_str()),
+            Ok(settings.clone())
+        );
+        assert_eq!(settings.periods.offset(&TimeUnit::Week), "6d");
+        assert_eq!(settings.periods.offset(&TimeUnit::Month), "24d");
+        assert_eq!(settings.periods.offset(&TimeUnit::Year), "3mo5d");
+        assert_eq!(Settings::from_json("{}"), Ok(Settings::default()));
+        assert!(Settings::from_json(r#"{"periods": {"month_start_day": 31}}"#).is_err());
+        assert!(Settings::from_json(r#"{"liquidity": {"min_runway_months": -1}}"#).is_err());
+
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let day = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        assert_eq!(
+            settings.periods.period(&TimeUnit::Week, date),
+            (day(3, 10), day(3, 16))
+        );
+        assert_eq!(
+            settings.periods.period(&TimeUnit::Month, date),
+            (day(2, 25), day(3, 24))
+        );
+        assert_eq!(
+            settings.periods.period(&TimeUnit::Year, date),
+            (NaiveDate::from_ymd_opt(2023, 4, 6).unwrap(), day(4, 5))
+        );
+    }
+
+    #[test]
+    fn correct_monthly_contribution() {
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let target_date =
+            |year: i32, month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+
+        assert_eq!(
+            monthly_contribution(1200.0, date, target_date(2025, 3, 10)),
+            100.0
+        );
+        assert_eq!(
+            monthly_contribution(1100.0, date, target_date(2025, 3, 9)),
+            100.0
+        );
+        assert_eq!(
+            monthly_contribution(500.0, date, target_date(2024, 1, 1)),
+            500.0
+        );
+        assert_eq!(
+            monthly_contribution(-10.0, date, target_date(2025, 3, 10)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn correct_recurrence() {
+        let date =
+            |year: i32, month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let monthly: Recurrence = Recurrence::Monthly { day: 31 };
+        let yearly: Recurrence = Recurrence::Yearly { month: 2, day: 29 };
+        let fortnightly: Recurrence = Recurrence::Weekly { weeks: 2 };
+
+        for recurrence in [&monthly, &yearly, &fortnightly] {
+            assert_eq!(
+                Recurrence::from_rule(recurrence.rule().as_str()).as_ref(),
+                Ok(recurrence)
+            );
+        }
+        assert!(Recurrence::from_rule("monthly:32").is_err());
+
+        assert_eq!(monthly.first(date(2024, 1, 15)), date(2024, 1, 31));
+        assert_eq!(monthly.following(date(2024, 1, 31)), date(2024, 2, 29));
+        assert_eq!(monthly.following(date(2024, 2, 29)), date(2024, 3, 31));
+        assert_eq!(yearly.first(date(2024, 3, 1)), date(2025, 2, 28));
+        assert_eq!(yearly.following(date(2027, 2, 28)), date(2028, 2, 29));
+        assert_eq!(fortnightly.following(date(2024, 12, 25)), date(2025, 1, 8));
+    }
+
+    #[test]
+    fn correct_due_occurrences() {
+        let date = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let mut data_base: DataBase = DataBase::new();
+        let template: Template = Template {
+            name: String::from("Rent"),
+            transactions: vec![
+                Transaction::Expense {
+                    value: 800.0,
+                    currency: Currency::EUR,
+                    date: date(1, 1),
+                    category: String::from("Housing"),
+                    subcategory: String::from("Rent"),
+                    description: String::new(),
+                    entity_id: 0,
+                    quantity: Some(Quantity {
+                        amount: 1.0,
+                        unit: String::from("month"),
+                    }),
+                },
+                Transaction::Debit {
+                    value: 800.0,
+                    currency: Currency::EUR,
+                    date: date(1, 1),
+                    account_id: 0,
+                },
+            ],
+            recurrence: Recurrence::Monthly { day: 1 },
+            start_date: date(1, 15),
+            end_date: Some(date(5, 1)),
+        };
+        data_base.insert_template(&template);
+
+        let occurrences = data_base.due_occurrences(date(12, 31));
+        let dates: Vec<NaiveDate> = occurrences
+            .iter()
+            .map(|occurrence| occurrence.date)
+            .collect();
+        assert_eq!(dates, vec![date(2, 1), date(3, 1), date(4, 1), date(5, 1)]);
+        assert_eq!(occurrences[0].value(), 800.0);
+
+        let party: Party = occurrences[0].party(850.0);
+        assert!(party.is_valid());
+        assert!(party
+            .transactions
+            .iter()
+            .all(|transaction| transaction.value() == 850.0 && *transaction.date() == date(2, 1)));
+        assert!(party.transactions.iter().any(|transaction| matches!(
+            transaction,
+            Transaction::Expense { quantity: Some(quantity), .. } if quantity.unit == "month"
+        )));
+
+        assert!(data_base.confirm_occurrence(&occurrences[0], 0.0).is_err());
+        assert!(data_base
+            .confirm_occurrence(&occurrences[0], -850.0)
+            .is_err());
+        assert_eq!(data_base.due_occurrences(date(4, 15)).len(), 3);
+        data_base
+            .confirm_occurrence(&occurrences[0], 850.0)
+            .unwrap();
+        data_base.skip_occurrence(&occurrences[1]);
+        assert_eq!(data_base.due_occurrences(date(4, 15)).len(), 1);
+        assert_eq!(data_base.due_occurrences(date(4, 15))[0].date, date(4, 1));
+    }
+
+    #[test]
+    fn correct_subscriptions() {
+        let mut data_base: DataBase = DataBase::new();
+        let streaming_id: i64 = data_base.insert_entity(&Entity::new(
+            String::from("Streaming"),
+            String::from("Sweden"),
+            EntityType::Firm,
+            String::from("Media"),
+        ));
+        let grocer_id: i64 = data_base.insert_entity(&Entity::new(
+            String::from("Grocer"),
+            String::from("Sweden"),
+            EntityType::Firm,
+            String::from("Supermarket"),
+        ));
+        let mut insert_expense = |entity_id: i64, value: f64, date: NaiveDate| {
+            data_base.insert_party(&mut Party::new(vec![
+                Transaction::Expense {
+                    value,
+                    currency: Currency::SEK,
+                    date,
+                    category: String::from("Leisure"),
+                    subcategory: String::from("Streaming"),
+                    description: String::new(),
+                    entity_id,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value,
+                    currency: Currency::SEK,
+                    date,
+                    account_id: 0,
+                },
+            ]));
+        };
+
+        // Monthly payments around the 3rd with a price raise, and irregular groceries
+        let days: [(u32, u32); 6] = [(1, 3), (2, 3), (3, 4), (4, 3), (5, 3), (6, 5)];
+        for (i, (month, day)) in days.into_iter().enumerate() {
+            let value: f64 = if i < 4 { 99.0 } else { 119.0 };
+            insert_expense(
+                streaming_id,
+                value,
+                NaiveDate::from_ymd_opt(2024, month, day).unwrap(),
+            );
+        }
+        for (month, day, value) in [(1, 10, 250.0), (1, 12, 40.0), (3, 20, 600.0)] {
+            insert_expense(
+                grocer_id,
+                value,
+                NaiveDate::from_ymd_opt(2024, month, day).unwrap(),
+            );
+        }
+
+        let subscriptions = data_base.subscriptions();
+        assert_eq!(subscriptions.len(), 1);
+        let subscription = &subscriptions[0];
+        assert_eq!(subscription.entity_id, streaming_id);
+        assert_eq!(subscription.cadence, Cadence::Monthly);
+        assert_eq!(subscription.payments.len(), 6);
+        assert_eq!(
+            subscription.price_changes(),
+            vec![(NaiveDate::from_ymd_opt(2024, 5, 3).unwrap(), 99.0, 119.0)]
+        );
+        assert_eq!(subscription.annualized_cost(), 119.0 * 12.0);
+        assert!(subscription.is_active(NaiveDate::from_ymd_opt(2024, 7, 1).unwrap()));
+        assert!(!subscription.is_active(NaiveDate::from_ymd_opt(2024, 9, 1).unwrap()));
+
+        let report: DataFrame =
+            data_base.subscriptions_report_data_frame(NaiveDate::from_ymd_opt(2024, 7, 1).unwrap());
+        assert_eq!(report.height(), 1);
+        assert_eq!(
+            report.column("Average").unwrap().f64().unwrap().get(0),
+            Some(105.67)
+        );
+
+        let template = data_base.subscription_template(subscription).unwrap();
+        assert_eq!(template.recurrence, Recurrence::Monthly { day: 5 });
+        assert_eq!(
+            template.start_date,
+            NaiveDate::from_ymd_opt(2024, 7, 5).unwrap()
+        );
+        assert!(Party::new(template.transactions.clone()).is_valid());
+        assert!(!subscription.is_templated(&[]));
+        assert!(subscription.is_templated(&[template]));
+
+        // Without a debit in its currency, the last payment has no account to take it from
+        let mut subscription = subscription.clone();
+        subscription.currency = Currency::EUR;
+        assert!(data_base.subscription_template(&subscription).is_err());
+    }
+
+    #[test]
+    fn correct_forecast() {
+        let today: NaiveDate = Local::now().date_naive();
+        let days_ago = |days: i64| today - chrono::Duration::days(days);
+        let mut data_base: DataBase = DataBase::new();
+        let account_id: i64 = data_base.insert_account(&Account::new(
+            String::from("Checking"),
+            String::from("Sweden"),
+            Currency::SEK,
+            AccountType::Deposit,
+            5000.0,
+        ));
+        let expense = |category: &str, value: f64, date: NaiveDate| {
+            vec![
+                Transaction::Expense {
+                    value,
+                    currency: Currency::SEK,
+                    date,
+                    category: category.to_string(),
+                    subcategory: category.to_string(),
+                    description: String::new(),
+                    entity_id: 0,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value,
+                    currency: Currency::SEK,
+                    date,
+                    account_id,
+                },
+            ]
+        };
+
+        // 900 of groceries in each of the last two periods of 30 days, i.e. 30 a day
+        for days in [5, 15, 25, 35, 45, 59] {
+            data_base.insert_party(&mut Party::new(expense("Groceries", 300.0, days_ago(days))));
+        }
+        let tomorrow: NaiveDate = today + chrono::Duration::days(1);
+        data_base.insert_template(&Template {
+            name: String::from("Rent"),
+            transactions: expense("Housing", 1500.0, tomorrow),
+            recurrence: Recurrence::Weekly { weeks: 4 },
+            start_date: tomorrow,
+            end_date: None,
+        });
+
+        let forecast = data_base.forecast(today, 3);
+        let points: Vec<_> = forecast
+            .iter()
+            .filter(|point| point.account_id == account_id)
+            .collect();
+        assert_eq!(points[0].date, tomorrow);
+        assert_eq!(points[0].expected, 3200.0 - 1500.0 - 30.0);
+        assert_eq!(points[0].low, points[0].expected);
+        assert_eq!(points[27].expected, 3200.0 - 1500.0 - 30.0 * 28.0);
+        assert_eq!(points[28].expected, 3200.0 - 3000.0 - 30.0 * 29.0);
+        assert_eq!(
+            data_base.forecast_warnings(today, 3),
+            vec![(account_id, points[28].date, points.last().unwrap().expected)]
+        );
+    }
+
+    #[test]
+    fn correct_budget_vs_actual() {
+        let today: NaiveDate = Local::now().date_naive();
+        let mut data_base: DataBase = DataBase::new();
+        let (current_from, current_to) =
+            data_base.settings().periods.period(&TimeUnit::Month, today);
+        let next_from: NaiveDate = current_to + chrono::Duration::days(1);
+        for (value, date) in [(200.0, current_from), (150.0, next_from)] {
+            data_base.insert_party(&mut Party::new(vec![
+                Transaction::Expense {
+                    value,
+                    currency: Currency::EUR,
+                    date,
+                    category: String::from("Groceries"),
+                    subcategory: String::from("Food"),
+                    description: String::new(),
+                    entity_id: 0,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value,
+                    currency: Currency::EUR,
+                    date,
+                    account_id: 0,
+                },
+            ]));
+        }
+        let mut budget: Budget = Budget {
+            category: String::from("Groceries"),
+            subcategory: None,
+            period: TimeUnit::Month,
+            value: 300.0,
+            currency: Currency::EUR,
+            rollover: true,
+        };
+        let budget_id: i64 = data_base.set_budget(&budget);
+
+        let row = |data_frame: &DataFrame, name: &str| -> Option<f64> {
+            data_frame.column(name).unwrap().f64().unwrap().get(0)
+        };
+        let status = |data_frame: &DataFrame| -> String {
+            data_frame
+                .column("Status")
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(0)
+                .unwrap()
+                .to_string()
+        };
+
+        // The first period, at its end, has nothing to carry over
+        let first: DataFrame = data_base.budget_vs_actual_data_frame(current_to);
+        assert_eq!(row(&first, "Carried Over"), Some(0.0));
+        assert_eq!(row(&first, "Actual"), Some(200.0));
+        assert_eq!(row(&first, "Projected"), Some(200.0));
+        assert_eq!(status(&first), "On track");
+
+        // Ten days into the second, the 100 left of the first are carried over, and the 150
+        // spent so far project beyond the 400 available
+        let second: DataFrame =
+            data_base.budget_vs_actual_data_frame(next_from + chrono::Duration::days(9));
+        let (_next_from, next_to) = data_base
+            .settings()
+            .periods
+            .period(&TimeUnit::Month, next_from);
+        let next_days: f64 = ((next_to - next_from).num_days() + 1) as f64;
+        assert_eq!(row(&second, "Carried Over"), Some(100.0));
+        assert_eq!(row(&second, "Available"), Some(400.0));
+        assert_eq!(row(&second, "Remaining"), Some(250.0));
+        assert_eq!(row(&second, "% Used"), Some(37.5));
+        assert_eq!(row(&second, "Projected"), Some(150.0 * next_days / 10.0));
+        assert_eq!(status(&second), "Projected over");
+
+        // Editing the budget keeps it, and the overspending of the first period leaves
+        // nothing available in the second
+        budget.value = 100.0;
+        assert_eq!(data_base.set_budget(&budget), budget_id);
+        assert_eq!(data_base.budgets().len(), 1);
+        assert_eq!(
+            status(&data_base.budget_vs_actual_data_frame(current_to)),
+            "Over budget"
+        );
+        let second: DataFrame =
+            data_base.budget_vs_actual_data_frame(next_from + chrono::Duration::days(9));
+        assert_eq!(row(&second, "Carried Over"), Some(-100.0));
+        assert_eq!(row(&second, "Available"), Some(0.0));
+        assert_eq!(row(&second, "% Used"), None);
+        assert_eq!(status(&second), "Over budget");
+    }
+
+    #[test]
+    fn correct_anomalies() {
+        let date = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let mut data_base: DataBase = DataBase::new();
+        let mut insert_expense = |category: &str, value: f64, date: NaiveDate| {
+            data_base.insert_party(&mut Party::new(vec![
+                Transaction::Expense {
+                    value,
+                    currency: Currency::EUR,
+                    date,
+                    category: category.to_string(),
+                    subcategory: category.to_string(),
+                    description: String::new(),
+                    entity_id: 0,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value,
+                    currency: Currency::EUR,
+                    date,
+                    account_id: 0,
+                },
+            ]))
+        };
+
+        // Groceries every week around 50, once 400 and once entered twice
+        for month in 1..=4 {
+            for day in [1, 8, 15, 22] {
+                insert_expense("Groceries", 50.0 + day as f64, date(month, day));
+            }
+        }
+        insert_expense("Groceries", 400.0, date(4, 25));
+        insert_expense("Groceries", 58.0, date(4, 8));
+
+        let anomalies = data_base.anomalies();
+        let kinds: Vec<AnomalyKind> = anomalies
+            .iter()
+            .map(|anomaly| anomaly.kind.clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                AnomalyKind::Outlier,
+                AnomalyKind::Duplicate,
+                AnomalyKind::Spike
+            ]
+        );
+        assert_eq!(anomalies[0].value, 400.0);
+        assert_eq!(anomalies[1].date, date(4, 8));
+        assert_eq!(anomalies[1].party_ids.len(), 2);
+        assert_eq!(anomalies[2].date, date(4, 1));
+
+        data_base.dismiss_anomaly(&anomalies[1], date(4, 30));
+        assert_eq!(data_base.anomalies().len(), 2);
+        assert!(data_base
+            .anomalies()
+            .iter()
+            .all(|anomaly| anomaly.kind != AnomalyKind::Duplicate));
+
+        // Dismissals outlive a change of the day months start on
+        data_base.dismiss_anomaly(&anomalies[0], date(4, 30));
+        data_base.dismiss_anomaly(&anomalies[2], date(4, 30));
+        let mut settings: Settings = data_base.settings().clone();
+        settings.periods.month_start_day = 2;
+        data_base.set_settings(settings).unwrap();
+        assert_eq!(data_base.anomalies(), vec![]);
+    }
+
+    #[test]
+    fn correct_unit_prices() {
+        let date = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let mut data_base: DataBase = DataBase::new();
+        let mut insert_expense =
+            |subcategory: &str, value: f64, date: NaiveDate, quantity: Option<Quantity>| {
+                data_base.insert_party(&mut Party::new(vec![
+                    Transaction::Expense {
+                        value,
+                        currency: Currency::EUR,
+                        date,
+                        category: String::from("Living"),
+                        subcategory: subcategory.to_string(),
+                        description: String::new(),
+                        entity_id: 0,
+                        quantity,
+                    },
+                    Transaction::Debit {
+                        value,
+                        currency: Currency::EUR,
+                        date,
+                        account_id: 0,
+                    },
+                ]))
+            };
+        let litres = |amount: f64| {
+            Some(Quantity {
+                amount,
+                unit: String::from("l"),
+            })
+        };
+        let kilograms = |amount: f64| {
+            Some(Quantity {
+                amount,
+                unit: String::from("kg"),
+            })
+        };
+
+        // Fuel goes from 1.5 to 1.6 a litre and coffee from 10 to 12 a kilo
+        let party_id: i64 = insert_expense("Fuel", 60.0, date(1, 5), litres(40.0));
+        insert_expense("Coffee", 10.0, date(1, 10), kilograms(1.0));
+        insert_expense("Fuel", 80.0, date(2, 5), litres(50.0));
+        insert_expense("Rent", 900.0, date(2, 1), None);
+        insert_expense("Fuel", 64.0, date(3, 5), litres(40.0));
+        insert_expense("Coffee", 12.0, date(3, 10), kilograms(1.0));
+
+        if let Transaction::Expense { quantity, .. } = &data_base.party(party_id).transactions[0] {
+            assert_eq!(quantity, &litres(40.0));
+        } else {
+            panic!("Expected an expense");
+        }
+
+        let history: DataFrame =
+            data_base.unit_price_history_data_frame(&Currency::EUR, &TimeUnit::Month);
+        assert_eq!(history.height(), 5);
+        let unit_prices: Vec<f64> = history
+            .column("Unit Price")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(unit_prices, vec![10.0, 12.0, 1.5, 1.6, 1.6]);
+
+        let points = data_base.personal_price_index_points(&Currency::EUR, &TimeUnit::Month);
+        let indices: Vec<f64> = points.iter().map(|point| point.1).collect();
+        assert_eq!(indices.len(), 3);
+        assert_eq!(indices[0], 100.0);
+        assert!((indices[1] - 100.0 * 1.6 / 1.5).abs() < 1e-9);
+        assert!((indices[2] - indices[1] * (64.0 + 12.0 * 1.2) / 76.0).abs() < 1e-9);
+        assert_eq!(points[2].2, 2);
+    }
+
+    #[test]
+    fn correct_independence_projection() {
+        let today: NaiveDate = Local::now().date_naive();
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_account(&Account::new(
+            String::from("Index fund"),
+            String::from("Sweden"),
+            Currency::EUR,
+            AccountType::Investment,
+            20000.0,
+        ));
+        let deposit_id: i64 = data_base.insert_account(&Account::new(
+            String::from("Current account"),
+            String::from("Sweden"),
+            Currency::EUR,
+            AccountType::Deposit,
+            10000.0,
+        ));
+
+        // A year earning 3000 and spending 2000 a month
+        for i in 0..12 {
+            let date: NaiveDate = today - chrono::Months::new(i);
+            data_base.insert_party(&mut Party::new(vec![
+                Transaction::Income {
+                    value: 3000.0,
+                    currency: Currency::EUR,
+                    date,
+                    category: String::from("Salary"),
+                    subcategory: String::from("Salary"),
+                    description: String::new(),
+                    entity_id: 0,
+                },
+                Transaction::Credit {
+                    value: 3000.0,
+                    currency: Currency::EUR,
+                    date,
+                    account_id: deposit_id,
+                },
+            ]));
+            data_base.insert_party(&mut Party::new(vec![
+                Transaction::Expense {
+                    value: 2000.0,
+                    currency: Currency::EUR,
+                    date,
+                    category: String::from("Living"),
+                    subcategory: String::from("Rent"),
+                    description: String::new(),
+                    entity_id: 0,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value: 2000.0,
+                    currency: Currency::EUR,
+                    date,
+                    account_id: deposit_id,
+                },
+            ]));
+        }
+
+        let plan: IndependencePlan = IndependencePlan {
+            savings_change: 100.0,
+            ..IndependencePlan::default()
+        };
+        let baseline =
+            data_base.independence_projection(today, &Currency::EUR, &plan.baseline(), 50);
+        assert_eq!(baseline.net_worth, 42000.0);
+        assert!((baseline.monthly_savings - 1000.0).abs() < 1e-9);
+        assert!((baseline.target - 600000.0).abs() < 1e-6);
+        assert!(
+            (baseline.expected_return - (22000.0 * 1.0 + 20000.0 * 5.0) / 42000.0).abs() < 1e-9
+        );
+        assert_eq!(baseline.trajectory.len(), 50 * 12 + 1);
+        assert_eq!(baseline.trajectory[0], (today, 42000.0));
+
+        // A longer window than the history averages over the history only
+        let longer_window: IndependencePlan = IndependencePlan {
+            window_months: 24,
+            ..plan.baseline()
+        };
+        let longer = data_base.independence_projection(today, &Currency::EUR, &longer_window, 50);
+        assert!((longer.monthly_savings - 1000.0).abs() < 1e-9);
+        assert!((longer.yearly_expenses - 24000.0).abs() < 1e-9);
+
+        let what_if = data_base.independence_projection(today, &Currency::EUR, &plan, 50);
+        assert!((what_if.monthly_savings - 2000.0).abs() < 1e-9);
+        let years = |months: Option<u32>| months.unwrap() as f64 / 12.0;
+        assert!((27.0..29.0).contains(&years(baseline.months_to_independence)));
+        assert!((16.5..18.0).contains(&years(what_if.months_to_independence)));
+
+        // An overdrawn account reduces the net worth, but not the mix of returns
+        data_base.insert_account(&Account::new(
+            String::from("Credit card"),
+            String::from("Sweden"),
+            Currency::EUR,
+            AccountType::Cash,
+            -2000.0,
+        ));
+        let overdrawn =
+            data_base.independence_projection(today, &Currency::EUR, &plan.baseline(), 50);
+        assert_eq!(overdrawn.net_worth, 40000.0);
+        assert!((overdrawn.expected_return - baseline.expected_return).abs() < 1e-9);
+
+        assert!(plan.is_valid());
+        assert!(!IndependencePlan {
+            withdrawal_rate: 0.0,
+            ..plan
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn correct_runway() {
+        let today: NaiveDate = Local::now().date_naive();
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_account(&Account::new(
+            String::from("Index fund"),
+            String::from("Sweden"),
+            Currency::EUR,
+            AccountType::Investment,
+            50000.0,
+        ));
+        data_base.insert_account(&Account::new(
+            String::from("Wallet"),
+            String::from("Sweden"),
+            Currency::EUR,
+            AccountType::Cash,
+            1000.0,
+        ));
+        let deposit_id: i64 = data_base.insert_account(&Account::new(
+            String::from("Current account"),
+            String::from("Sweden"),
+            Currency::EUR,
+            AccountType::Deposit,
+            11000.0,
+        ));
+
+        // Six months spending 1000 a month
+        for i in 0..6 {
+            data_base.insert_party(&mut Party::new(vec![
+                Transaction::Expense {
+                    value: 1000.0,
+                    currency: Currency::EUR,
+                    date: today - chrono::Months::new(i),
+                    category: String::from("Living"),
+                    subcategory: String::from("Rent"),
+                    description: String::new(),
+                    entity_id: 0,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value: 1000.0,
+                    currency: Currency::EUR,
+                    date: today - chrono::Months::new(i),
+                    account_id: deposit_id,
+                },
+            ]));
+        }
+
+        let runway = data_base.runway_at(today, &Currency::EUR, 6);
+        assert_eq!(runway.liquid_funds, 6000.0);
+        assert_eq!(runway.monthly_expenses, 1000.0);
+        assert_eq!(runway.months(), Some(6.0));
+        // Six months of history averaged over a longer window still give six months
+        assert_eq!(
+            data_base.runway_at(today, &Currency::EUR, 12).months(),
+            Some(6.0)
+        );
+
+        let history = data_base.runway_history(today, &Currency::EUR, 6);
+        assert_eq!(history.len(), 6);
+        assert_eq!(history.last(), Some(&runway));
+        assert_eq!(history[0].date, today - chrono::Months::new(5));
+        assert_eq!(history[0].monthly_expenses, 1000.0);
+
+        // Overdrawn deposits eat into the liquid funds
+        data_base.insert_account(&Account::new(
+            String::from("Credit line"),
+            String::from("Sweden"),
+            Currency::EUR,
+            AccountType::Deposit,
+            -6000.0,
+        ));
+        assert_eq!(data_base.liquid_funds_at(today, &Currency::EUR), 0.0);
+    }
+
+    #[test]
+    fn correct_evolution_table() {
+        let mut data_base: DataBase = DataBase::new();
+        let day = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        for (date, category, subcategory, value) in [
+            (day(1, 5), "Food", "Groceries", 100.0),
+            (day(1, 20), "Food", "Restaurant", 50.0),
+            (day(2, 10), "Transport", "Fuel", 30.0),
+        ] {
+            data_base.insert_party(&mut Party::new(vec![Transaction::Expense {
+                value,
+                currency: Currency::EUR,
+                date,
+                category: String::from(category),
+                subcategory: String::from(subcategory),
+                description: String::new(),
+                entity_id: 0,
+                quantity: None,
+            }]));
+        }
+
+        let evolution: DataFrame = data_base
+            .evolution_data_frame(
+                &TransactionType::Expense,
+                &Currency::EUR,
+                &TimeUnit::Month,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let column = |name: &str| -> Vec<Option<f64>> {
+            evolution
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(column("Food"), vec![Some(150.0), Some(0.0), Some(150.0)]);
+        assert_eq!(column("Transport"), vec![Some(0.0), Some(30.0), Some(30.0)]);
+        assert_eq!(column("Total"), vec![Some(150.0), Some(30.0), Some(180.0)]);
+        let months: Vec<Option<NaiveDate>> = evolution
+            .column("Month")
+            .unwrap()
+            .date()
+            .unwrap()
+            .as_date_iter()
+            .collect();
+        assert_eq!(months, vec![Some(day(1, 1)), Some(day(2, 1)), None]);
+        let evolution_csv: String = data_base
+            .evolution_table(
+                &TransactionType::Expense,
+                &Currency::EUR,
+                &TimeUnit::Month,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(evolution_csv.contains("\nTotal,150.00,30.00,180.00"));
+
+        assert!(data_base
+            .evolution_data_frame(
+                &TransactionType::Credit,
+                &Currency::EUR,
+                &TimeUnit::Month,
+                None,
+                None,
+                None,
+                None,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn correct_cash_flow() {
+        let mut data_base: DataBase = DataBase::new();
+        let day = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let account_id: i64 = data_base.insert_account(&Account::new(
+            String::from("Checking"),
+            String::from("Spain"),
+            Currency::EUR,
+            AccountType::Deposit,
+            0.0,
+        ));
+        data_base.insert_party(&mut Party::new(vec![
+            Transaction::Income {
+                value: 1000.0,
+                currency: Currency::EUR,
+                date: day(1, 10),
+                category: String::from("Salary"),
+                subcategory: String::from("Salary"),
+                description: String::new(),
+                entity_id: 0,
+            },
+            Transaction::Credit {
+                value: 1000.0,
+                currency: Currency::EUR,
+                date: day(1, 10),
+                account_id,
+            },
+        ]));
+        for (date, value) in [(day(1, 20), 300.0), (day(3, 5), 200.0)] {
+            data_base.insert_party(&mut Party::new(vec![
+                Transaction::Expense {
+                    value,
+                    currency: Currency::EUR,
+                    date,
+                    category: String::from("Housing"),
+                    subcategory: String::from("Rent"),
+                    description: String::new(),
+                    entity_id: 0,
+                    quantity: None,
+                },
+                Transaction::Debit {
+                    value,
+                    currency: Currency::EUR,
+                    date,
+                    account_id,
+                },
+            ]));
+        }
+
+        let cash_flow: DataFrame = data_base.cash_flow_data_frame(&Currency::EUR, &TimeUnit::Month);
+        let column = |name: &str| -> Vec<Option<f64>> {
+            cash_flow
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        // February has no flows, but still gets its row
+        assert_eq!(cash_flow.height(), 3);
+        assert_eq!(column("Income"), vec![Some(1000.0), Some(0.0), Some(0.0)]);
+        assert_eq!(
+            column("Expenses"),
+            vec![Some(300.0), Some(0.0), Some(200.0)]
+        );
+        assert_eq!(
+            column("Net Savings"),
+            vec![Some(700.0), Some(0.0), Some(-200.0)]
+        );
+        assert_eq!(column("% Savings Rate"), vec![Some(70.0), None, None]);
+        assert_eq!(column("Funds Change"), column("Net Savings"));
+        assert_eq!(
+            column("Unexplained Difference"),
+            vec![Some(0.0), Some(0.0), Some(0.0)]
+        );
+        assert!(data_base
+            .cash_flow(&Currency::EUR, &TimeUnit::Month)
+            .starts_with("Month,Income,Expenses,Net Savings"));
+
+        let path: String = std::env::temp_dir()
+            .join("cash_flow.svg")
+            .to_string_lossy()
+            .to_string();
+        data_base.cash_flow_plot(&Currency::EUR, &TimeUnit::Month, path.as_str());
+        let plot: String = std::fs::read_to_string(path.as_str()).unwrap();
+        std::fs::remove_file(path.as_str()).unwrap();
+        assert!(plot.contains("Net Savings"));
+        assert!(plot.contains("Savings Rate"));
+    }
+
+    #[test]
+    fn correct_account_balance_history() {
+        let today: NaiveDate = Local::now().date_naive();
+        let days_later = |days: i64| today + chrono::Duration::days(days);
+        let mut data_base: DataBase = DataBase::new();
+        let checking_id: i64 = data_base.insert_account(&Account::new(
+            String::from("Checking"),
+            String::from("Spain"),
+            Currency::EUR,
+            AccountType::Deposit,
+            100.0,
+        ));
+        let savings_id: i64 = data_base.insert_account(&Account::new(
+            String::from("Savings"),
+            String::from("Spain"),
+            Currency::EUR,
+            AccountType::Deposit,
+            0.0,
+        ));
+        for (account_id, value, days) in [(checking_id, 50.0, 1), (savings_id, 10.0, 2)] {
+            data_base.insert_party(&mut Party::new(vec![Transaction::Credit {
+                value,
+                currency: Currency::EUR,
+                date: days_later(days),
+                account_id,
+            }]));
+        }
+        data_base.insert_party(&mut Party::new(vec![Transaction::Debit {
+            value: 30.0,
+            currency: Currency::EUR,
+            date: days_later(3),
+            account_id: checking_id,
+        }]));
+
+        let balances = |history: &DataFrame, account_id: i64| -> Vec<(NaiveDate, f64)> {
+            let history: DataFrame = history
+                .clone()
+                .lazy()
+                .filter(col("account_id").eq(lit(account_id)))
+                .collect()
+                .unwrap();
+            history
+                .column("date")
+                .unwrap()
+                .date()
+                .unwrap()
+                .as_date_iter()
+                .flatten()
+                .zip(
+                    history
+                        .column("balance")
+                        .unwrap()
+                        .f64()
+                        .unwrap()
+                        .into_no_null_iter(),
+                )
+                .collect()
+        };
+
+        // Every account runs from its creation until the last movement of all of them
+        let history: DataFrame = data_base.account_balance_history(None, &[]);
+        assert_eq!(
+            balances(&history, checking_id),
+            vec![
+                (today, 100.0),
+                (days_later(1), 150.0),
+                (days_later(2), 150.0),
+                (days_later(3), 120.0)
+            ]
+        );
+        assert_eq!(
+            balances(&history, savings_id),
+            vec![
+                (today, 0.0),
+                (days_later(1), 0.0),
+                (days_later(2), 10.0),
+                (days_later(3), 10.0)
+            ]
+        );
+
+        let history: DataFrame =
+            data_base.account_balance_history(Some(&Currency::EUR), &[savings_id]);
+        // Only the selected accounts, until their own last movement
+        assert!(balances(&history, checking_id).is_empty());
+        assert_eq!(
+            balances(&history, savings_id).last(),
+            Some(&(days_later(2), 10.0))
+        );
+    }
+
+    #[test]
+    fn correct_entity_transactions() {
+        let mut data_base: DataBase = DataBase::new();
+        let day = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        for (entity_id, value, date) in [
+            (1, 20.0, day(1, 5)),
+            (1, 35.0, day(2, 5)),
+            (2, 99.0, day(2, 6)),
+            (1, 40.0, day(3, 5)),
+        ] {
+            data_base.insert_party(&mut Party::new(vec![Transaction::Expense {
+                value,
+                currency: Currency::EUR,
+                date,
+                category: String::from("Food"),
+                subcategory: String::from("Groceries"),
+                description: String::new(),
+                entity_id,
+                quantity: None,
+            }]));
+        }
+
+        // Only the expenses to the entity within the dates, most recent first
+        let transactions: DataFrame =
+            data_base.entity_transactions_data_frame(1, day(1, 5), day(2, 29));
+        assert_eq!(
+            transactions
+                .column("Value")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<f64>>(),
+            vec![35.0, 20.0]
+        );
+        assert!(data_base
+            .entity_transactions(1, day(1, 5), day(2, 29))
+            .starts_with("Date,Value,Currency,Category,Subcategory,Description,Party Id"));
+    }
+
+    #[test]
+    fn correct_income_summary() {
+        let mut data_base: DataBase = DataBase::new();
+        let day = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let employer_id: i64 = data_base.insert_entity(&Entity::new(
+            String::from("Employer"),
+            String::from("Spain"),
+            EntityType::Firm,
+            String::from("Software"),
+        ));
+        for (subcategory, entity_id, value, date) in [
+            ("Salary", employer_id, 2000.0, day(1, 25)),
+            ("Salary", employer_id, 2000.0, day(2, 25)),
+            ("Bonus", employer_id, 500.0, day(2, 25)),
+            ("Salary", 99, 100.0, day(3, 1)),
+        ] {
+            data_base.insert_party(&mut Party::new(vec![Transaction::Income {
+                value,
+                currency: Currency::EUR,
+                date,
+                category: String::from("Work"),
+                subcategory: String::from(subcategory),
+                description: String::new(),
+                entity_id,
+            }]));
+        }
+
+        let summary: DataFrame = data_base
+            .income_summary_data_frame(day(1, 1), day(3, 1), &Currency::EUR, None)
+            .unwrap();
+        let text = |name: &str| -> Vec<&str> {
+            summary
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_no_null_iter()
+                .collect()
+        };
+        let number = |name: &str| -> Vec<f64> {
+            summary
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect()
+        };
+        // Incomes of unknown entities are kept, and the last row holds the totals
+        assert_eq!(
+            text("Subcategory"),
+            vec!["Bonus", "Salary", "Salary", "Total"]
+        );
+        assert_eq!(
+            text("Entity"),
+            vec!["Employer", "Employer", "Unknown", "Total"]
+        );
+        assert_eq!(number("EUR"), vec![500.0, 4000.0, 100.0, 4600.0]);
+        assert_eq!(number("EUR / Day"), vec![8.33, 66.67, 1.67, 76.67]);
+        assert_eq!(number("% Total Income"), vec![10.87, 86.96, 2.17, 100.0]);
+
+        let single_day: DataFrame = data_base
+            .income_summary_data_frame(day(3, 1), day(3, 1), &Currency::EUR, None)
+            .unwrap();
+        let per_day: Vec<f64> = single_day
+            .column("EUR / Day")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(per_day, vec![100.0, 100.0]);
+    }
+
+    #[test]
+    fn correct_expenses_summary() {
+        let mut data_base: DataBase = DataBase::new();
+        let day = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        for (category, subcategory, value) in [
+            ("Food", "Groceries", 300.0),
+            ("Food", "Groceries", 100.0),
+            ("Food", "Restaurant", 100.0),
+            ("Housing", "Rent", 500.0),
+        ] {
+            data_base.insert_party(&mut Party::new(vec![Transaction::Expense {
+                value,
+                currency: Currency::EUR,
+                date: day(1, 10),
+                category: String::from(category),
+                subcategory: String::from(subcategory),
+                description: String::new(),
+                entity_id: 0,
+                quantity: None,
+            }]));
+        }
+        data_base.insert_party(&mut Party::new(vec![Transaction::Income {
+            value: 2000.0,
+            currency: Currency::EUR,
+            date: day(1, 1),
+            category: String::from("Work"),
+            subcategory: String::from("Salary"),
+            description: String::new(),
+            entity_id: 0,
+        }]));
+
+        let summary: DataFrame = data_base
+            .expenses_summary_data_frame(
+                day(1, 1),
+                day(1, 31),
+                &Currency::EUR,
+                &[SummaryDimension::Category, SummaryDimension::Subcategory],
+                None,
+            )
+            .unwrap();
+        let text = |summary: &DataFrame, name: &str| -> Vec<String> {
+            summary
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_no_null_iter()
+                .map(String::from)
+                .collect()
+        };
+        let number = |summary: &DataFrame, name: &str| -> Vec<f64> {
+            summary
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect()
+        };
+        // Every category is followed by its subtotal, and the grand total comes last
+        assert_eq!(
+            text(&summary, "Category"),
+            vec!["Food", "Food", "Food", "Housing", "Housing", "Total"]
+        );
+        assert_eq!(
+            text(&summary, "Subcategory"),
+            vec!["Groceries", "Restaurant", "Total", "Rent", "Total", "Total"]
+        );
+        assert_eq!(
+            number(&summary, "EUR"),
+            vec![400.0, 100.0, 500.0, 500.0, 500.0, 1000.0]
+        );
+        assert_eq!(
+            number(&summary, "% Total Expenses"),
+            vec![40.0, 10.0, 50.0, 50.0, 50.0, 100.0]
+        );
+        assert_eq!(
+            number(&summary, "% Total Income"),
+            vec![20.0, 5.0, 25.0, 25.0, 25.0, 50.0]
+        );
+
+        // A repeated dimension is only grouped by once
+        let summary: DataFrame = data_base
+            .expenses_summary_data_frame(
+                day(1, 1),
+                day(1, 31),
+                &Currency::EUR,
+                &[SummaryDimension::Category, SummaryDimension::Category],
+                None,
+            )
+            .unwrap();
+        assert_eq!(text(&summary, "Category"), vec!["Food", "Housing", "Total"]);
+        assert_eq!(number(&summary, "EUR"), vec![500.0, 500.0, 1000.0]);
+    }
+
+    #[test]
+    fn correct_expenses_comparison() {
+        let mut data_base: DataBase = DataBase::new();
+        let day = |month: u32, day: u32| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        for (date, category, subcategory, value) in [
+            (day(1, 10), "Food", "Groceries", 100.0),
+            (day(1, 10), "Housing", "Rent", 500.0),
+            (day(1, 15), "Transport", "Fuel", 60.0),
+            (day(2, 10), "Food", "Groceries", 150.0),
+            (day(2, 12), "Food", "Restaurant", 40.0),
+            (day(2, 10), "Housing", "Rent", 500.0),
+        ] {
+            data_base.insert_party(&mut Party::new(vec![Transaction::Expense {
+                value,
+                currency: Currency::EUR,
+                date,
+                category: String::from(category),
+                subcategory: String::from(subcategory),
+                description: String::new(),
+                entity_id: 0,
+                quantity: None,
+            }]));
+        }
+
+        let comparison: DataFrame = data_base.expenses_comparison_data_frame(
+            (day(1, 1), day(1, 31)),
+            (day(2, 1), day(2, 29)),
+            &Currency::EUR,
+            10.0,
+        );
+        let text = |name: &str| -> Vec<String> {
+            comparison
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_no_null_iter()
+                .map(String::from)
+                .collect()
+        };
+        let number = |name: &str| -> Vec<Option<f64>> {
+            comparison
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        // Subcategories and subtotals of both periods line up, also when they only have
+        // expenses in one of them
+        assert_eq!(
+            text("Category"),
+            vec![
+                "Food",
+                "Food",
+                "Food",
+                "Housing",
+                "Housing",
+                "Transport",
+                "Transport",
+                "Total"
+            ]
+        );
+        assert_eq!(
+            text("Subcategory"),
+            vec![
+                "Groceries",
+                "Restaurant",
+                "Total",
+                "Rent",
+                "Total",
+                "Fuel",
+                "Total",
+                "Total"
+            ]
+        );
+        assert_eq!(
+            number("EUR 2024-01-01 to 2024-01-31"),
+            vec![
+                Some(100.0),
+                Some(0.0),
+                Some(100.0),
+                Some(500.0),
+                Some(500.0),
+                Some(60.0),
+                Some(60.0),
+                Some(660.0)
+            ]
+        );
+        assert_eq!(
+            number("EUR 2024-02-01 to 2024-02-29"),
+            vec![
+                Some(150.0),
+                Some(40.0),
+                Some(190.0),
+                Some(500.0),
+                Some(500.0),
+                Some(0.0),
+                Some(0.0),
+                Some(690.0)
+            ]
+        );
+        assert_eq!(
+            number("% Change"),
+            vec![
+                Some(50.0),
+                None,
+                Some(90.0),
+                Some(0.0),
+                Some(0.0),
+                Some(-100.0),
+                Some(-100.0),
+                Some(4.55)
+            ]
+        );
+        assert_eq!(
+            text("Significant Increase"),
+            vec!["Yes", "Yes", "Yes", "No", "No", "No", "No", "No"]
+        );
+    }
+
+    #[test]
+    fn correct_fund_stand() {
+        let today: NaiveDate = Local::now().date_naive();
+        let tomorrow: NaiveDate = today + chrono::Duration::days(1);
+        let mut data_base: DataBase = DataBase::new();
+        let checking_id: i64 = data_base.insert_account(&Account::new(
+            String::from("Checking"),
+            String::from("Spain"),
+            Currency::EUR,
+            AccountType::Deposit,
+            100.0,
+        ));
+        data_base.insert_account(&Account::new(
+            String::from("Savings"),
+            String::from("Spain"),
+            Currency::EUR,
+            AccountType::Deposit,
+            200.0,
+        ));
+        data_base.insert_party(&mut Party::new(vec![
+            Transaction::Expense {
+                value: 150.0,
+                currency: Currency::EUR,
+                date: tomorrow,
+                category: String::from("Housing"),
+                subcategory: String::from("Rent"),
+                description: String::new(),
+                entity_id: 0,
+                quantity: None,
+            },
+            Transaction::Debit {
+                value: 150.0,
+                currency: Currency::EUR,
+                date: tomorrow,
+                account_id: checking_id,
+            },
+        ]));
+
+        // The overdrawn account is still part of the balance sheet
+        let fund_stand: DataFrame = data_base
+            .fund_stand_at_data_frame(
+                tomorrow,
+                Some(&Currency::EUR),
+                &Valuation::Historical,
+                &FundGrouping::Account,
+                None,
+            )
+            .unwrap();
+        let names: Vec<&str> = fund_stand
+            .column("Name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(names, vec!["Savings", "Checking"]);
+        assert_eq!(
+            fund_stand
+                .column("EUR")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<f64>>(),
+            vec![200.0, -50.0]
+        );
+
+        let comparison: DataFrame = data_base.fund_stand_comparison_data_frame(
+            today,
+            tomorrow,
+            &Currency::EUR,
+            &Valuation::Historical,
+            &FundGrouping::Account,
+        );
+        let number = |name: &str| -> Vec<Option<f64>> {
+            comparison
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(number("Change"), vec![Some(0.0), Some(-150.0)]);
+        assert_eq!(number("% Change"), vec![Some(0.0), Some(-150.0)]);
+    }
+
+    #[test]
+    fn correct_goals_report() {
+        let today: NaiveDate = Local::now().date_naive();
+        let mut data_base: DataBase = DataBase::new();
+        data_base.insert_account(&Account::new(
+            String::from("Savings"),
+            String::from("Spain"),
+            Currency::EUR,
+            AccountType::Deposit,
+            5000.0,
+        ));
+        data_base.insert_account(&Account::new(
+            String::from("Credit card"),
+            String::from("Spain"),
+            Currency::EUR,
+            AccountType::Deposit,
+            -1000.0,
+        ));
+        let car: i64 = data_base.insert_goal(&Goal {
+            name: String::from("Car"),
+            target_value: 10000.0,
+            currency: Currency::EUR,
+            target_date: today + chrono::Months::new(12),
+        });
+        data_base.allocate(car, 3000.0, today);
+
+        let report: DataFrame = data_base.goals_report_data_frame(&Currency::EUR, today);
+        let goals: Vec<&str> = report
+            .column("Goal")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(goals, vec!["Car", "Unallocated", "Total Funds"]);
+        let allocations: Vec<f64> = report
+            .column("Allocated")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(allocations, vec![3000.0, 1000.0, 4000.0]);
+    }
 }
@@ -21,6 +21,7 @@ mod tests {
             subcategory: "Electricity".to_string(),
             description: "Monthly electricity bill".to_string(),
             entity_id: 1,
+            quantity: None,
         };
 
         let t2 = Transaction::Debit {
@@ -1,5 +1,6 @@
 mod currency_exchange;
 mod database;
 mod financial;
+mod price_index;
 mod tables;
 pub(crate) mod test_helpers;
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::price_index::PriceIndex;
+    use chrono::NaiveDate;
+    use polars::prelude::*;
+
+    fn init_testing_price_index() -> PriceIndex {
+        let data_frame: DataFrame = df!(
+            "date" => [
+                NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2020, 7, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            ],
+            "value" => [110.0, 100.0, 102.0, 121.0]
+        )
+        .unwrap();
+
+        PriceIndex::new("testland", data_frame)
+    }
+
+    #[test]
+    fn correct_level() {
+        let price_index: PriceIndex = init_testing_price_index();
+
+        assert_eq!(price_index.years(), vec![2020, 2021, 2022]);
+        // Before the first observation, on one and between two
+        assert_eq!(
+            price_index.level(NaiveDate::from_ymd_opt(2019, 5, 1).unwrap()),
+            Ok(100.0)
+        );
+        assert_eq!(
+            price_index.level(NaiveDate::from_ymd_opt(2020, 7, 1).unwrap()),
+            Ok(102.0)
+        );
+        assert_eq!(
+            price_index.level(NaiveDate::from_ymd_opt(2021, 6, 30).unwrap()),
+            Ok(110.0)
+        );
+        assert_eq!(price_index.year_level(2020), Ok(101.0));
+        assert!(price_index.year_level(2019).is_err());
+    }
+
+    #[test]
+    fn correct_deflator() {
+        let price_index: PriceIndex = init_testing_price_index();
+
+        // Amounts of 2022 and 2021 at the prices of 2021
+        let dates: Vec<NaiveDate> = vec![
+            NaiveDate::from_ymd_opt(2022, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 3, 1).unwrap(),
+        ];
+        let deflators: Vec<f64> = price_index.deflators(dates.iter().cloned(), 2021).unwrap();
+        assert!((121.0 * deflators[0] - 110.0).abs() < 1e-9);
+        assert_eq!(deflators[1], 1.0);
+        assert!(price_index.deflators(dates.into_iter(), 2019).is_err());
+    }
+}
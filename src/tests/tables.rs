@@ -175,4 +175,193 @@ mod tests {
                 & (original_transaction.date() == returned_transaction.date())
         );
     }
+
+    #[test]
+    fn correct_attachment_table() {
+        let mut attachment_table = *AttachmentTable::new();
+        attachment_table.insert_attachment(0, "receipt.pdf", "a1.pdf");
+        attachment_table.insert_attachment(1, "invoice.png", "b2.png");
+        attachment_table.insert_attachment(0, "warranty.pdf", "a1.pdf");
+
+        let attachments = attachment_table.party_attachments(0);
+        assert_eq!(
+            attachments,
+            vec![
+                (0, String::from("receipt.pdf"), String::from("a1.pdf")),
+                (2, String::from("warranty.pdf"), String::from("a1.pdf")),
+            ]
+        );
+
+        attachment_table.delete_party(0);
+        assert!(attachment_table.party_attachments(0).is_empty());
+        assert_eq!(
+            attachment_table.stored_file_names(),
+            vec![String::from("b2.png")]
+        );
+    }
+
+    #[test]
+    fn correct_budget_table() {
+        let mut budget_table = *BudgetTable::new();
+        budget_table.insert_budget("Food", None, "Month", 400.0, &Currency::EUR, false);
+        budget_table.insert_budget(
+            "Food",
+            Some("Restaurants"),
+            "Month",
+            100.0,
+            &Currency::EUR,
+            true,
+        );
+        budget_table.insert_budget("Leisure", None, "Year", 1200.0, &Currency::CHF, false);
+
+        assert_eq!(budget_table.find_budget("Food", None, "Month"), Some(0));
+        assert_eq!(
+            budget_table.find_budget("Food", Some("Restaurants"), "Month"),
+            Some(1)
+        );
+        assert_eq!(budget_table.find_budget("Food", None, "Year"), None);
+
+        budget_table.delete_budget(0);
+        assert_eq!(budget_table.find_budget("Food", None, "Month"), None);
+        assert_eq!(budget_table.next_id(), 3);
+    }
+
+    #[test]
+    fn correct_goal_tables() {
+        let mut goal_table = *GoalTable::new();
+        let mut allocation_table = *AllocationTable::new();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let car: i64 = goal_table.insert_goal("Car", 15000.0, &Currency::EUR, date);
+        let vacation: i64 = goal_table.insert_goal("Vacation", 2000.0, &Currency::CHF, date);
+
+        allocation_table.insert_allocation(
+            car,
+            1000.0,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        );
+        allocation_table.insert_allocation(
+            car,
+            -200.0,
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+        );
+        allocation_table.insert_allocation(
+            car,
+            500.0,
+            NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(),
+        );
+        allocation_table.insert_allocation(vacation, 300.0, date);
+
+        assert_eq!(allocation_table.allocated(car, date), 800.0);
+        assert_eq!(allocation_table.allocated(vacation, date), 300.0);
+
+        goal_table.delete_goal(car);
+        allocation_table.delete_goal(car);
+        assert_eq!(goal_table.data_frame.height(), 1);
+        assert_eq!(allocation_table.allocated(car, date), 0.0);
+    }
+
+    #[test]
+    fn correct_template_transaction_table() {
+        let mut template_transaction_table = *TemplateTransactionTable::new();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        template_transaction_table.insert_transaction(
+            0,
+            &Transaction::Income {
+                value: 2500.0,
+                currency: Currency::EUR,
+                date,
+                category: String::from("Salary"),
+                subcategory: String::from("Regular salary"),
+                description: String::new(),
+                entity_id: 1,
+            },
+        );
+        template_transaction_table.insert_transaction(
+            0,
+            &Transaction::Credit {
+                value: 2500.0,
+                currency: Currency::EUR,
+                date,
+                account_id: 2,
+            },
+        );
+        template_transaction_table.insert_transaction(
+            1,
+            &Transaction::Debit {
+                value: 20.0,
+                currency: Currency::CHF,
+                date,
+                account_id: 2,
+            },
+        );
+
+        let next_date: NaiveDate = NaiveDate::from_ymd_opt(2024, 4, 25).unwrap();
+        let transactions = template_transaction_table.transactions(0, next_date);
+        assert_eq!(transactions.len(), 2);
+        assert!(matches!(
+            &transactions[0],
+            Transaction::Income { category, entity_id: 1, .. } if category == "Salary"
+        ));
+        assert!(matches!(
+            transactions[1],
+            Transaction::Credit { account_id: 2, .. }
+        ));
+        assert!(transactions
+            .iter()
+            .all(|transaction| *transaction.date() == next_date));
+
+        template_transaction_table.delete_template(0);
+        assert!(template_transaction_table
+            .transactions(0, next_date)
+            .is_empty());
+        assert_eq!(
+            template_transaction_table.transactions(1, next_date).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn correct_expenses_table_without_quantities() {
+        // Expenses saved before they had a quantity
+        let data_frame: DataFrame = df!(
+            "expense_id" => [0i64],
+            "value" => [12.5f64],
+            "currency" => [Currency::EUR.to_string()],
+            "date" => [NaiveDate::from_ymd_opt(2023, 4, 2).unwrap()],
+            "category" => ["Food"],
+            "subcategory" => ["Coffee"],
+            "description" => [""],
+            "entity_id" => [0i64],
+            "party_id" => [0i64],
+        )
+        .unwrap();
+        let mut expenses_table: ExpensesTable = *ExpensesTable::create(data_frame);
+
+        assert_eq!(
+            expenses_table.data_frame.schema(),
+            ExpensesTable::new().data_frame.schema()
+        );
+        expenses_table.insert_transaction(
+            &Transaction::Expense {
+                value: 14.0,
+                currency: Currency::EUR,
+                date: NaiveDate::from_ymd_opt(2024, 4, 2).unwrap(),
+                category: String::from("Food"),
+                subcategory: String::from("Coffee"),
+                description: String::new(),
+                entity_id: 0,
+                quantity: Some(Quantity {
+                    amount: 1.0,
+                    unit: String::from("kg"),
+                }),
+            },
+            1,
+        );
+        if let Transaction::Expense { quantity, .. } = expenses_table.transaction(0) {
+            assert_eq!(quantity, None);
+        }
+        if let Transaction::Expense { quantity, .. } = expenses_table.transaction(1) {
+            assert_eq!(quantity.unwrap().to_string(), "1 kg");
+        }
+    }
 }
@@ -20,6 +20,7 @@ pub(crate) fn init_party() -> Party {
         subcategory: "Alcohol".to_string(),
         description: "Bought some beers to celebrate".to_string(),
         entity_id: 1,
+        quantity: None,
     };
 
     let t3 = Transaction::Credit {